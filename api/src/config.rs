@@ -1,5 +1,7 @@
-use jsonwebtoken::{DecodingKey, EncodingKey};
-use std::{env, sync::LazyLock};
+use crate::utils::jwt_keys::JwtKeys;
+use entity::role::Role;
+use ipnet::IpNet;
+use std::{env, net::IpAddr, sync::LazyLock};
 
 pub static GLOBAL_CONFIG: LazyLock<Config> = LazyLock::new(|| {
     let port = env::var("PORT").unwrap_or("3000".to_string());
@@ -16,11 +18,45 @@ pub static GLOBAL_CONFIG: LazyLock<Config> = LazyLock::new(|| {
     let smtp_user = env::var("SMTP_USER").unwrap_or_default();
     let smtp_password = env::var("SMTP_PASSWORD").unwrap_or_default();
 
-    let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
-    let encoding_key = EncodingKey::from_secret(secret.as_bytes());
-    let decoding_key = DecodingKey::from_secret(secret.as_bytes());
+    let jwt_keys = JwtKeys::load(
+        env::var("JWT_SECRET").ok(),
+        env::var("JWT_KEYS_DIR").ok(),
+        env::var("JWT_ACTIVE_KID").ok(),
+    );
     let avatar_path = env::var("AVATAR_PATH").expect("AVATAR_PATH must be set");
 
+    let trusted_proxies = env::var("TRUSTED_PROXIES")
+        .map(|value| parse_trusted_proxies(&value))
+        .unwrap_or_default();
+
+    let oidc = env::var("OIDC_ISSUER_URL")
+        .ok()
+        .map(|issuer_url| OidcConfig {
+            issuer_url: issuer_url.trim_end_matches('/').to_string(),
+            client_id: env::var("OIDC_CLIENT_ID").expect("OIDC_CLIENT_ID must be set"),
+            client_secret: env::var("OIDC_CLIENT_SECRET").ok(),
+            redirect_url: env::var("OIDC_REDIRECT_URL").expect("OIDC_REDIRECT_URL must be set"),
+            auto_accept_invitations: env::var("OIDC_AUTO_ACCEPT_INVITATIONS")
+                .map(|value| value == "true")
+                .unwrap_or(true),
+        });
+
+    let ldap = env::var("LDAP_URL").ok().map(|url| LdapConfig {
+        url,
+        base_dn: env::var("LDAP_BASE_DN").expect("LDAP_BASE_DN must be set"),
+        bind_dn: env::var("LDAP_BIND_DN").ok(),
+        bind_password: env::var("LDAP_BIND_PASSWORD").ok(),
+        user_filter: env::var("LDAP_USER_FILTER").unwrap_or("(mail={email})".to_string()),
+        first_name_attr: env::var("LDAP_FIRST_NAME_ATTR").unwrap_or("givenName".to_string()),
+        last_name_attr: env::var("LDAP_LAST_NAME_ATTR").unwrap_or("sn".to_string()),
+        study_group_attr: env::var("LDAP_STUDY_GROUP_ATTR")
+            .unwrap_or("departmentNumber".to_string()),
+        group_attr: env::var("LDAP_GROUP_ATTR").unwrap_or("memberOf".to_string()),
+        group_roles: env::var("LDAP_GROUP_ROLES")
+            .map(|value| parse_group_roles(&value))
+            .unwrap_or_default(),
+    });
+
     Config {
         port,
         admin_username,
@@ -32,12 +68,48 @@ pub static GLOBAL_CONFIG: LazyLock<Config> = LazyLock::new(|| {
         smtp_from,
         smtp_user,
         smtp_password,
-        encoding_key,
-        decoding_key,
+        jwt_keys,
         avatar_path,
+        trusted_proxies,
+        oidc,
+        ldap,
     }
 });
 
+/// Parses `TRUSTED_PROXIES`: comma-separated addresses or CIDR ranges,
+/// e.g. `10.0.0.0/8, 127.0.0.1`.
+fn parse_trusted_proxies(value: &str) -> Vec<IpNet> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|proxy| !proxy.is_empty())
+        .map(|proxy| {
+            proxy
+                .parse::<IpNet>()
+                .or_else(|_| proxy.parse::<IpAddr>().map(IpNet::from))
+                .expect("TRUSTED_PROXIES must contain IP addresses or CIDR ranges")
+        })
+        .collect()
+}
+
+/// Parses `LDAP_GROUP_ROLES` in the form `<group dn>=<role>;<group dn>=<role>`.
+/// Roles are spelled as in the API, e.g. `Admin` or `ProjectOffice`.
+fn parse_group_roles(value: &str) -> Vec<(String, Role)> {
+    value
+        .split(';')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            let (group, role) = pair
+                .rsplit_once('=')
+                .expect("LDAP_GROUP_ROLES must contain <group dn>=<role> pairs");
+            let role = serde_json::from_value(serde_json::Value::String(role.trim().to_string()))
+                .expect("LDAP_GROUP_ROLES must contain valid roles");
+
+            (group.trim().to_string(), role)
+        })
+        .collect()
+}
+
 pub struct Config {
     pub port: String,
     pub admin_username: String,
@@ -49,7 +121,31 @@ pub struct Config {
     pub smtp_from: String,
     pub smtp_user: String,
     pub smtp_password: String,
-    pub encoding_key: EncodingKey,
-    pub decoding_key: DecodingKey,
+    pub jwt_keys: JwtKeys,
     pub avatar_path: String,
+    /// Reverse proxies whose `X-Forwarded-For` and `X-Real-IP` headers are trusted.
+    pub trusted_proxies: Vec<IpNet>,
+    pub oidc: Option<OidcConfig>,
+    pub ldap: Option<LdapConfig>,
+}
+
+pub struct OidcConfig {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_url: String,
+    pub auto_accept_invitations: bool,
+}
+
+pub struct LdapConfig {
+    pub url: String,
+    pub base_dn: String,
+    pub bind_dn: Option<String>,
+    pub bind_password: Option<String>,
+    pub user_filter: String,
+    pub first_name_attr: String,
+    pub last_name_attr: String,
+    pub study_group_attr: String,
+    pub group_attr: String,
+    pub group_roles: Vec<(String, Role)>,
 }
@@ -1,10 +1,20 @@
+pub mod api_token;
 pub mod auth;
+pub mod comment;
 pub mod common;
 pub mod company;
 pub mod group;
+pub mod idea;
 pub mod invitation;
+pub mod market;
+pub mod matching;
+pub mod permission;
 pub mod profile;
+pub mod rating;
+pub mod search;
+pub mod session;
 pub mod skill;
 pub mod smtp;
+pub mod team;
+pub mod two_factor;
 pub mod user;
-// pub mod idea;
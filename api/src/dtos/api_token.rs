@@ -0,0 +1,44 @@
+use entity::role::Role;
+use macros::IntoDataResponse;
+use sea_orm::{
+    DerivePartialModel,
+    prelude::{DateTimeWithTimeZone, Uuid},
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateApiTokenRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Название токена должно содержать от 1 до 100 символов"
+    ))]
+    pub name: String,
+    #[validate(length(min = 1, message = "Нужно указать хотя бы одну роль"))]
+    pub scopes: Vec<Role>,
+    #[validate(range(
+        min = 1,
+        max = 365,
+        message = "Срок действия токена должен быть от 1 до 365 дней"
+    ))]
+    pub expires_in_days: i64,
+}
+
+#[derive(Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::api_token::Entity")]
+pub struct ApiTokenDto {
+    pub id: Uuid,
+    pub name: String,
+    pub scopes: Vec<Role>,
+    pub created_at: DateTimeWithTimeZone,
+    pub expires_at: DateTimeWithTimeZone,
+    pub last_used_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize)]
+pub struct CreatedApiTokenResponse {
+    #[serde(flatten)]
+    pub token: ApiTokenDto,
+    pub secret: String,
+}
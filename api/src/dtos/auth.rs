@@ -1,3 +1,6 @@
+use crate::utils::security::{ACCESS_TOKEN_TTL_MINUTES, TokenPair};
+use entity::role::Role;
+use macros::IntoDataResponse;
 use sea_orm::prelude::Uuid;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
@@ -34,3 +37,35 @@ pub struct EmailResetPayload {
     #[validate(length(equal = 6, message = "Код должен состоять из 6 цифр"))]
     pub code: String,
 }
+#[derive(Debug, Deserialize)]
+pub struct RefreshTokenPayload {
+    pub refresh_token: String,
+}
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackParams {
+    pub code: Option<String>,
+    pub state: String,
+    pub error: Option<String>,
+}
+#[derive(IntoDataResponse, Debug, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+}
+
+impl From<TokenPair> for TokenResponse {
+    fn from(tokens: TokenPair) -> Self {
+        Self {
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+            token_type: "Bearer".to_string(),
+            expires_in: ACCESS_TOKEN_TTL_MINUTES * 60,
+        }
+    }
+}
+#[derive(Debug, Deserialize)]
+pub struct ActiveRolePayload {
+    pub role: Option<Role>,
+}
@@ -0,0 +1,44 @@
+use macros::IntoDataResponse;
+use sea_orm::{
+    DerivePartialModel,
+    prelude::{DateTimeWithTimeZone, Uuid},
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dtos::profile::UserDto;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateCommentRequest {
+    pub parent_id: Option<Uuid>,
+    #[validate(length(
+        min = 1,
+        max = 5000,
+        message = "Комментарий должен содержать от 1 до 5000 символов"
+    ))]
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateCommentRequest {
+    #[validate(length(
+        min = 1,
+        max = 5000,
+        message = "Комментарий должен содержать от 1 до 5000 символов"
+    ))]
+    pub text: String,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::idea_comment::Entity")]
+pub struct CommentDto {
+    pub id: Uuid,
+    pub idea_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub text: String,
+    pub created_at: DateTimeWithTimeZone,
+    pub edited_at: Option<DateTimeWithTimeZone>,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    #[sea_orm(nested)]
+    pub author: UserDto,
+}
@@ -1,12 +1,45 @@
+use crate::utils::spreadsheet::SpreadsheetFormat;
 use macros::IntoDataResponse;
-use sea_orm::prelude::Uuid;
+use sea_orm::{Order, prelude::Uuid};
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Validate)]
 pub struct PaginationParams {
     pub page: u64,
+    #[validate(range(
+        min = 1,
+        max = 100,
+        message = "Размер страницы должен быть от 1 до 100"
+    ))]
     pub page_size: u64,
 }
+
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Asc => Order::Asc,
+            SortOrder::Desc => Order::Desc,
+        }
+    }
+}
+
+#[derive(IntoDataResponse, Debug, Serialize)]
+pub struct PagedResponse<T: Serialize> {
+    pub items: Vec<T>,
+    pub total: u64,
+    pub page: u64,
+    pub page_size: u64,
+}
+
 #[derive(IntoDataResponse, Debug, Serialize)]
 pub struct MessageResponse {
     pub message: String,
@@ -16,3 +49,10 @@ pub struct MessageResponse {
 pub struct IdResponse {
     pub id: Uuid,
 }
+
+/// Export format: `?format=csv` (default) or `?format=xlsx`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ExportParams {
+    pub format: SpreadsheetFormat,
+}
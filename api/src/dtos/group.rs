@@ -1,29 +1,29 @@
 use entity::role::Role;
 use macros::IntoDataResponse;
-use sea_orm::{prelude::Uuid, DerivePartialModel};
+use sea_orm::{DerivePartialModel, prelude::Uuid};
 use serde::{Deserialize, Serialize};
 
 use crate::dtos::profile::UserDto;
 
-#[derive(Serialize, IntoDataResponse, Debug, DerivePartialModel)]
+#[derive(Serialize, IntoDataResponse, Debug, Clone, DerivePartialModel)]
 #[sea_orm(entity = "entity::group::Entity")]
 pub struct GroupDto {
     pub id: Uuid,
     pub name: String,
     pub roles: Vec<Role>,
     #[sea_orm(skip)]
-    pub members: Vec<UserDto>
+    pub members: Vec<UserDto>,
 }
 #[derive(Deserialize, Debug)]
 pub struct CreateGroupRequest {
     pub name: String,
     pub roles: Vec<Role>,
-    pub members: Vec<Uuid>
+    pub members: Vec<Uuid>,
 }
 #[derive(Deserialize, Debug)]
 pub struct UpdateGroupRequest {
     pub id: Uuid,
     pub name: Option<String>,
     pub roles: Option<Vec<Role>>,
-    pub members: Option<Vec<Uuid>>
-}
\ No newline at end of file
+    pub members: Option<Vec<Uuid>>,
+}
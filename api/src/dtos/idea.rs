@@ -1,13 +1,16 @@
-use chrono::{DateTime, Utc};
+use entity::{idea_status::IdeaStatus, role::Role};
+use macros::IntoDataResponse;
+use sea_orm::{
+    DerivePartialModel,
+    prelude::{DateTimeWithTimeZone, Uuid},
+};
 use serde::{Deserialize, Serialize};
-use sea_orm::prelude::Uuid;
-use validator::Validate;
+use std::borrow::Cow;
+use validator::{Validate, ValidationError};
 
-use crate::dtos::{group::GroupDto, profile::UserDto, skill::SkillDto};
+use crate::dtos::{common::SortOrder, group::GroupDto, profile::UserDto, skill::SkillDto};
 
-use entity::idea::IdeaStatus;
-
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(IntoDataResponse, Debug, Serialize)]
 pub struct IdeaResponse {
     pub id: Uuid,
     pub initiator: UserDto,
@@ -18,8 +21,8 @@ pub struct IdeaResponse {
     pub project_office: Option<GroupDto>,
     pub is_checked: bool,
     pub status: IdeaStatus,
-    pub created_at: DateTime<Utc>,
-    pub modified_at: DateTime<Utc>,
+    pub created_at: DateTimeWithTimeZone,
+    pub modified_at: DateTimeWithTimeZone,
     pub is_active: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub problem: Option<String>,
@@ -41,81 +44,130 @@ pub struct IdeaResponse {
     pub pre_assessment: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rating: Option<f64>,
-    pub max_team_size: i16,
-    pub min_team_size: i16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_team_size: Option<i16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_team_size: Option<i16>,
 }
 
-#[derive(Serialize, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_create_team_size"))]
 pub struct CreateIdeaRequest {
-    #[validate(length(min = 1))]
+    #[validate(length(min = 1, message = "Название идеи не может быть пустым"))]
     pub name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub group_expert_id: Option<Uuid>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub group_project_office_id: Option<Uuid>,
-    #[validate(length(min = 1))]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "Поле не может быть пустым"))]
     pub problem: Option<String>,
-    #[validate(length(min = 1))]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "Поле не может быть пустым"))]
     pub solution: Option<String>,
-    #[validate(length(min = 1))]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "Поле не может быть пустым"))]
     pub result: Option<String>,
-    #[validate(length(min = 1))]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "Поле не может быть пустым"))]
     pub customer: Option<String>,
-    #[validate(length(min = 1))]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "Поле не может быть пустым"))]
     pub contact_person: Option<String>,
-    #[validate(length(min = 1))]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "Поле не может быть пустым"))]
     pub description: Option<String>,
     pub suitability: Option<i64>,
     pub budget: Option<i64>,
+    #[validate(range(min = 1, message = "Размер команды должен быть больше нуля"))]
     pub max_team_size: i16,
+    #[validate(range(min = 1, message = "Размер команды должен быть больше нуля"))]
     pub min_team_size: i16,
 }
 
-#[derive(Serialize, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_update_team_size"))]
 pub struct UpdateIdeaRequest {
     pub id: Uuid,
-    #[validate(length(min = 1))]
+    #[validate(length(min = 1, message = "Название идеи не может быть пустым"))]
     pub name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub group_expert_id: Option<Uuid>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub group_project_office_id: Option<Uuid>,
-    #[validate(length(min = 1))]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "Поле не может быть пустым"))]
     pub problem: Option<String>,
-    #[validate(length(min = 1))]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "Поле не может быть пустым"))]
     pub solution: Option<String>,
-    #[validate(length(min = 1))]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "Поле не может быть пустым"))]
     pub result: Option<String>,
-    #[validate(length(min = 1))]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "Поле не может быть пустым"))]
     pub customer: Option<String>,
-    #[validate(length(min = 1))]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "Поле не может быть пустым"))]
     pub contact_person: Option<String>,
-    #[validate(length(min = 1))]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, message = "Поле не может быть пустым"))]
     pub description: Option<String>,
     pub suitability: Option<i64>,
     pub budget: Option<i64>,
+    #[validate(range(min = 1, message = "Размер команды должен быть больше нуля"))]
     pub max_team_size: i16,
+    #[validate(range(min = 1, message = "Размер команды должен быть больше нуля"))]
     pub min_team_size: i16,
 }
 
-#[derive(Serialize, Deserialize, Validate)]
+fn validate_team_size(min: i16, max: i16) -> Result<(), ValidationError> {
+    if min > max {
+        return Err(ValidationError::new("team_size").with_message(Cow::from(
+            "Минимальный размер команды не может быть больше максимального",
+        )));
+    }
+    Ok(())
+}
+
+fn validate_create_team_size(payload: &CreateIdeaRequest) -> Result<(), ValidationError> {
+    validate_team_size(payload.min_team_size, payload.max_team_size)
+}
+
+fn validate_update_team_size(payload: &UpdateIdeaRequest) -> Result<(), ValidationError> {
+    validate_team_size(payload.min_team_size, payload.max_team_size)
+}
+
+#[derive(Debug, Deserialize)]
 pub struct UpdateIdeaStatusRequest {
     pub status: IdeaStatus,
+    pub comment: Option<String>,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::idea_status_history::Entity")]
+pub struct IdeaStatusHistoryDto {
+    pub id: Uuid,
+    pub previous_status: IdeaStatus,
+    pub new_status: IdeaStatus,
+    pub comment: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    #[sea_orm(nested)]
+    pub actor: Option<UserDto>,
+    /// Active role `actor` was acting under.
+    pub actor_role: Option<Role>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IdeaSortField {
+    #[default]
+    CreatedAt,
+    Rating,
+    Budget,
+}
+
+/// Query filters for `GET /idea`. List fields are passed as repeated keys,
+/// e.g. `?status=New&status=OnEditing`.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct IdeaFilterParams {
+    pub status: Vec<IdeaStatus>,
+    pub initiator_id: Option<Uuid>,
+    pub group_expert_id: Option<Uuid>,
+    pub skill_ids: Vec<Uuid>,
+    pub created_from: Option<DateTimeWithTimeZone>,
+    pub created_to: Option<DateTimeWithTimeZone>,
+    pub search: Option<String>,
+    pub sort_by: IdeaSortField,
+    pub sort_order: SortOrder,
 }
 
-#[derive(Serialize, Deserialize, Validate)]
+#[derive(Debug, Deserialize)]
 pub struct IdeaSkillRequest {
     pub idea_id: Uuid,
     pub skills: Vec<SkillDto>,
@@ -13,4 +13,4 @@ pub struct InvitationPayload {
 pub struct InvitationResponse {
     pub email: String,
     pub code: Uuid,
-}
\ No newline at end of file
+}
@@ -0,0 +1,94 @@
+use entity::{
+    idea_status::IdeaStatus, market_idea_status::MarketIdeaStatus,
+    market_request_status::MarketRequestStatus, market_status::MarketStatus,
+};
+use macros::IntoDataResponse;
+use sea_orm::{
+    DerivePartialModel,
+    prelude::{Date, DateTimeWithTimeZone, Uuid},
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dtos::team::TeamShortDto;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateMarketRequest {
+    #[validate(length(min = 1, message = "Название биржи не может быть пустым"))]
+    pub name: String,
+    pub start_date: Date,
+    pub finish_date: Date,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMarketRequest {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub start_date: Option<Date>,
+    pub finish_date: Option<Date>,
+    pub status: Option<MarketStatus>,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::market::Entity")]
+pub struct MarketDto {
+    pub id: Uuid,
+    pub name: String,
+    pub start_date: Date,
+    pub finish_date: Date,
+    pub status: MarketStatus,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublishIdeaRequest {
+    pub idea_id: Uuid,
+}
+
+#[derive(Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::idea::Entity")]
+pub struct MarketIdeaShortDto {
+    pub id: Uuid,
+    pub name: String,
+    pub initiator_id: Uuid,
+    pub status: IdeaStatus,
+    pub min_team_size: Option<i16>,
+    pub max_team_size: Option<i16>,
+}
+
+/// `team_id` is set once the initiator accepts a team's request.
+#[derive(IntoDataResponse, Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::market_idea::Entity")]
+pub struct MarketIdeaDto {
+    pub id: Uuid,
+    pub market_id: Uuid,
+    pub team_id: Option<Uuid>,
+    pub status: MarketIdeaStatus,
+    pub created_at: DateTimeWithTimeZone,
+    #[sea_orm(nested)]
+    pub idea: MarketIdeaShortDto,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ApplyToIdeaRequest {
+    pub team_id: Uuid,
+    #[validate(length(
+        min = 1,
+        max = 5000,
+        message = "Сопроводительное письмо должно содержать от 1 до 5000 символов"
+    ))]
+    pub letter: String,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::market_idea_request::Entity")]
+pub struct MarketIdeaRequestDto {
+    pub id: Uuid,
+    pub market_idea_id: Uuid,
+    pub letter: String,
+    pub status: MarketRequestStatus,
+    pub created_at: DateTimeWithTimeZone,
+    pub responded_at: Option<DateTimeWithTimeZone>,
+    #[sea_orm(nested)]
+    pub team: TeamShortDto,
+}
@@ -0,0 +1,37 @@
+use entity::skill_type::SkillType;
+use macros::IntoDataResponse;
+use sea_orm::prelude::Uuid;
+use serde::Serialize;
+
+use crate::dtos::{profile::UserDto, skill::SkillDto, team::TeamShortDto};
+
+#[derive(Debug, Serialize)]
+pub struct SkillTypeCoverage {
+    pub skill_type: SkillType,
+    pub required: usize,
+    pub matched: usize,
+    pub percentage: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserMatchDto {
+    pub user: UserDto,
+    pub matched_skills: Vec<Uuid>,
+    pub coverage: Vec<SkillTypeCoverage>,
+    pub percentage: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TeamMatchDto {
+    pub team: TeamShortDto,
+    pub matched_skills: Vec<Uuid>,
+    pub coverage: Vec<SkillTypeCoverage>,
+    pub percentage: f64,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize)]
+pub struct IdeaMatchResponse {
+    pub required_skills: Vec<SkillDto>,
+    pub users: Vec<UserMatchDto>,
+    pub teams: Vec<TeamMatchDto>,
+}
@@ -0,0 +1,20 @@
+use entity::{permission::Permission, role::Role};
+use macros::IntoDataResponse;
+use serde::{Deserialize, Serialize};
+
+#[derive(IntoDataResponse, Debug, Serialize)]
+pub struct RolePermissionsDto {
+    pub role: Role,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize)]
+pub struct PermissionsResponse {
+    pub permissions: Vec<Permission>,
+    pub roles: Vec<RolePermissionsDto>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRolePermissionsRequest {
+    pub permissions: Vec<Permission>,
+}
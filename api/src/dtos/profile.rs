@@ -5,6 +5,9 @@ use sea_orm::{
     prelude::{DateTimeLocal, Uuid},
 };
 use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dtos::skill::SkillDto;
 
 #[derive(Debug, Deserialize)]
 pub struct ProfileUpdatePayload {
@@ -13,7 +16,7 @@ pub struct ProfileUpdatePayload {
     pub study_group: Option<String>,
     pub telephone: Option<String>,
 }
-#[derive(IntoDataResponse, Debug, Serialize, Deserialize, DerivePartialModel)]
+#[derive(IntoDataResponse, Debug, Clone, Serialize, Deserialize, DerivePartialModel)]
 #[sea_orm(entity = "entity::users::Entity")]
 pub struct UserDto {
     pub id: Uuid,
@@ -25,3 +28,25 @@ pub struct UserDto {
     pub first_name: String,
     pub created_at: DateTimeLocal,
 }
+
+/// `level` is the self-assessed proficiency from 1 (beginner) to 5 (expert).
+#[derive(IntoDataResponse, Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::user_skill::Entity")]
+pub struct UserSkillDto {
+    pub level: i16,
+    #[sea_orm(nested)]
+    pub skill: SkillDto,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UserSkillItem {
+    pub skill_id: Uuid,
+    #[validate(range(min = 1, max = 5, message = "Уровень навыка должен быть от 1 до 5"))]
+    pub level: i16,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateUserSkillsRequest {
+    #[validate(nested)]
+    pub skills: Vec<UserSkillItem>,
+}
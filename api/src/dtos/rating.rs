@@ -0,0 +1,41 @@
+use macros::IntoDataResponse;
+use sea_orm::{
+    DerivePartialModel,
+    prelude::{DateTimeWithTimeZone, Uuid},
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dtos::profile::UserDto;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RatingRequest {
+    pub idea_id: Uuid,
+    #[validate(range(min = 1, max = 5, message = "Оценка должна быть от 1 до 5"))]
+    pub market_value: Option<i16>,
+    #[validate(range(min = 1, max = 5, message = "Оценка должна быть от 1 до 5"))]
+    pub originality: Option<i16>,
+    #[validate(range(min = 1, max = 5, message = "Оценка должна быть от 1 до 5"))]
+    pub technical_realizability: Option<i16>,
+    #[validate(range(min = 1, max = 5, message = "Оценка должна быть от 1 до 5"))]
+    pub suitability: Option<i16>,
+    #[validate(range(min = 1, max = 5, message = "Оценка должна быть от 1 до 5"))]
+    pub budget: Option<i16>,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::rating::Entity")]
+pub struct RatingDto {
+    pub id: Uuid,
+    pub idea_id: Uuid,
+    pub market_value: Option<i16>,
+    pub originality: Option<i16>,
+    pub technical_realizability: Option<i16>,
+    pub suitability: Option<i16>,
+    pub budget: Option<i16>,
+    pub rating: Option<f64>,
+    pub is_confirmed: bool,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(nested)]
+    pub expert: UserDto,
+}
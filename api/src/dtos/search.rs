@@ -0,0 +1,55 @@
+use entity::{idea_status::IdeaStatus, skill_type::SkillType};
+use macros::IntoDataResponse;
+use sea_orm::{FromQueryResult, prelude::Uuid};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SearchParams {
+    #[validate(length(
+        min = 2,
+        message = "Поисковый запрос должен содержать минимум 2 символа"
+    ))]
+    pub q: String,
+    #[validate(range(min = 1, max = 50, message = "Лимит должен быть от 1 до 50"))]
+    pub limit: Option<u64>,
+}
+
+/// `headline` contains the matched fragment with hits wrapped in `<mark>` tags.
+/// The source text is HTML-escaped, so `<mark>` is the only markup in it.
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct IdeaSearchHit {
+    pub id: Uuid,
+    pub name: String,
+    pub status: IdeaStatus,
+    pub rank: f32,
+    pub headline: String,
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct UserSearchHit {
+    pub id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    /// `None` unless the caller holds `user:manage:any`.
+    pub email: Option<String>,
+    pub study_group: Option<String>,
+    pub rank: f32,
+    pub headline: String,
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct SkillSearchHit {
+    pub id: Uuid,
+    pub name: String,
+    pub skill_type: SkillType,
+    pub rank: f32,
+    pub headline: String,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize)]
+pub struct SearchResponse {
+    pub ideas: Vec<IdeaSearchHit>,
+    pub users: Vec<UserSearchHit>,
+    pub skills: Vec<SkillSearchHit>,
+}
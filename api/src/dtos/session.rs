@@ -0,0 +1,12 @@
+use sea_orm::prelude::{DateTimeLocal, Uuid};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct SessionDto {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTimeLocal,
+    pub last_seen_at: DateTimeLocal,
+    pub current: bool,
+}
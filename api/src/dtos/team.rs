@@ -0,0 +1,95 @@
+use entity::{role::Role, team_invitation_status::TeamInvitationStatus};
+use macros::IntoDataResponse;
+use sea_orm::{
+    DerivePartialModel,
+    prelude::{DateTimeWithTimeZone, Uuid},
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dtos::{profile::UserDto, skill::SkillDto};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateTeamRequest {
+    #[validate(length(min = 1, message = "Название команды не может быть пустым"))]
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateTeamRequest {
+    pub id: Uuid,
+    #[validate(length(min = 1, message = "Название команды не может быть пустым"))]
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::team::Entity")]
+pub struct TeamResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    #[sea_orm(nested)]
+    pub owner: UserDto,
+    #[sea_orm(skip)]
+    pub members: Vec<TeamMemberDto>,
+}
+
+#[derive(Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::team::Entity")]
+pub struct TeamShortDto {
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::team_member::Entity")]
+pub struct TeamMemberDto {
+    pub role: Role,
+    pub joined_at: DateTimeWithTimeZone,
+    #[sea_orm(nested)]
+    pub user: UserDto,
+}
+
+/// Exactly one of `user_id` and `email` must be set. Inviting by email also
+/// works for people who are not registered yet.
+#[derive(Debug, Deserialize, Validate)]
+pub struct InviteTeamMemberRequest {
+    pub user_id: Option<Uuid>,
+    #[validate(email(message = "Некорректный email"))]
+    pub email: Option<String>,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize, DerivePartialModel)]
+#[sea_orm(entity = "entity::team_invitation::Entity")]
+pub struct TeamInvitationDto {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub email: String,
+    pub inviter_id: Uuid,
+    pub status: TeamInvitationStatus,
+    pub created_at: DateTimeWithTimeZone,
+    pub responded_at: Option<DateTimeWithTimeZone>,
+    #[sea_orm(nested)]
+    pub team: TeamShortDto,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransferOwnershipRequest {
+    pub user_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTeamMemberRoleRequest {
+    pub role: Role,
+}
+
+/// A skill of the team together with the number of members who have it.
+#[derive(Debug, Serialize)]
+pub struct TeamSkillDto {
+    #[serde(flatten)]
+    pub skill: SkillDto,
+    pub members_count: usize,
+}
@@ -0,0 +1,48 @@
+use entity::role::Role;
+use macros::IntoDataResponse;
+use sea_orm::prelude::Uuid;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(IntoDataResponse, Debug, Serialize)]
+pub struct TwoFactorStatusResponse {
+    pub enabled: bool,
+    pub required: bool,
+    pub recovery_codes_left: u64,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize)]
+pub struct TwoFactorEnrollmentResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+    pub qr_code: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct TwoFactorCodePayload {
+    #[validate(length(min = 6, max = 32, message = "Некорректный код"))]
+    pub code: String,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize)]
+pub struct TwoFactorChallengeResponse {
+    pub two_factor_required: bool,
+    pub challenge_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct TwoFactorLoginPayload {
+    pub challenge_id: Uuid,
+    #[validate(length(min = 6, max = 32, message = "Некорректный код"))]
+    pub code: String,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize, Deserialize)]
+pub struct TwoFactorPolicyDto {
+    pub roles: Vec<Role>,
+}
@@ -1,9 +1,15 @@
 use entity::role::Role;
 use macros::IntoDataResponse;
-use sea_orm::{DerivePartialModel, prelude::Uuid};
+use sea_orm::{
+    DerivePartialModel,
+    prelude::{DateTimeWithTimeZone, Uuid},
+};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use validator::Validate;
 
+use crate::dtos::common::SortOrder;
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct UserCreatePayload {
     pub email: String,
@@ -25,4 +31,88 @@ pub struct UserUpdatePayload {
     pub email: String,
     pub last_name: String,
     pub first_name: String,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UserSortField {
+    #[default]
+    CreatedAt,
+    Email,
+    LastName,
+    FirstName,
+    StudyGroup,
+    Telephone,
+}
+
+/// Query filters for `GET /users/all`. Roles are passed as repeated keys,
+/// e.g. `?roles=Expert&roles=ProjectOffice`, and match users with any of them.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct UserFilterParams {
+    pub roles: Vec<Role>,
+    pub study_group: Option<String>,
+    pub is_deleted: bool,
+    pub created_from: Option<DateTimeWithTimeZone>,
+    pub created_to: Option<DateTimeWithTimeZone>,
+    pub search: Option<String>,
+    pub sort_by: UserSortField,
+    pub sort_order: SortOrder,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserImportMode {
+    /// Users are created right away and set their password via password reset.
+    #[default]
+    Create,
+    /// Users are sent registration invitations.
+    Invite,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct UserImportParams {
+    pub mode: UserImportMode,
+    pub dry_run: bool,
+}
+
+/// A row of the import file after its columns have been mapped.
+#[derive(Debug, Validate)]
+pub struct UserImportRow {
+    #[validate(email(message = "Некорректный формат email"))]
+    pub email: String,
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Имя должно содержать от 1 до 100 символов"
+    ))]
+    pub first_name: String,
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Фамилия должна содержать от 1 до 100 символов"
+    ))]
+    pub last_name: String,
+    pub study_group: Option<String>,
+    pub telephone: Option<String>,
+    #[validate(length(min = 1, message = "Нужно указать хотя бы одну роль"))]
+    pub roles: Vec<Role>,
+}
+
+/// `row` is the line number in the file, the header being line 1.
+#[derive(Debug, Serialize)]
+pub struct UserImportRowError {
+    pub row: usize,
+    pub email: String,
+    pub errors: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(IntoDataResponse, Debug, Serialize)]
+pub struct UserImportResponse {
+    pub total: usize,
+    pub valid: usize,
+    pub imported: usize,
+    pub dry_run: bool,
+    pub errors: Vec<UserImportRowError>,
+}
@@ -1,10 +1,12 @@
 use axum::{
-    http::StatusCode,
-    response::{IntoResponse, Response},
     Json,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
 };
+use entity::idea_status::IdeaStatus;
 use serde_json::json;
 use thiserror::Error;
+use validator::{ValidationErrors, ValidationErrorsKind};
 
 #[derive(Debug, Error)]
 pub enum AppError {
@@ -29,6 +31,15 @@ pub enum AppError {
     #[error("{0}")]
     Custom(String),
 
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("Too many requests")]
+    TooManyRequests(u64),
+
+    #[error("Invalid idea status transition")]
+    InvalidStatusTransition { from: IdeaStatus, to: IdeaStatus },
+
     #[error("Internal Server Error")]
     InternalServerError,
 
@@ -48,6 +59,45 @@ pub enum AppError {
         #[source]
         redis::RedisError,
     ),
+
+    #[error("An error occurred with the identity provider")]
+    OidcProviderErr(
+        #[from]
+        #[source]
+        reqwest::Error,
+    ),
+}
+
+/// Flattens nested errors into keys such as `skills[1].level`, so `#[validate(nested)]`
+/// fields are reported the same way as top-level ones.
+fn collect_validation_errors(
+    errors: &ValidationErrors,
+    prefix: &str,
+    out: &mut serde_json::Map<String, serde_json::Value>,
+) {
+    for (field, kind) in errors.errors() {
+        let key = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                let messages = errors
+                    .iter()
+                    .map(|err| err.message.as_ref().unwrap().to_string())
+                    .collect::<Vec<_>>();
+                out.insert(key, json!(messages));
+            }
+            ValidationErrorsKind::Struct(errors) => collect_validation_errors(errors, &key, out),
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    collect_validation_errors(errors, &format!("{}[{}]", key, index), out);
+                }
+            }
+        }
+    }
 }
 
 impl IntoResponse for AppError {
@@ -63,19 +113,32 @@ impl IntoResponse for AppError {
             AppError::BadRequest => (StatusCode::BAD_REQUEST, self.to_string()),
             AppError::Forbidden => (StatusCode::FORBIDDEN, self.to_string()),
             AppError::Custom(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
+
+            AppError::TooManyRequests(retry_after) => {
+                return (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(header::RETRY_AFTER, retry_after.to_string())],
+                    Json(json!({ "error": self.to_string(), "retry_after": retry_after })),
+                )
+                    .into_response();
+            }
+
+            AppError::InvalidStatusTransition { from, to } => {
+                return (
+                    StatusCode::CONFLICT,
+                    Json(json!({
+                        "error": "Недопустимый переход статуса идеи",
+                        "from": from,
+                        "to": to,
+                    })),
+                )
+                    .into_response();
+            }
 
             AppError::ValidationError(e) => {
-                let errors = e
-                    .field_errors()
-                    .into_iter()
-                    .map(|(field, errors)| {
-                        let messages = errors
-                            .iter()
-                            .map(|err| err.message.as_ref().unwrap().to_string())
-                            .collect::<Vec<_>>();
-                        (field, messages)
-                    })
-                    .collect::<serde_json::Value>();
+                let mut errors = serde_json::Map::new();
+                collect_validation_errors(e, "", &mut errors);
                 return (
                     StatusCode::UNPROCESSABLE_ENTITY,
                     Json(json!({ "errors": errors })),
@@ -97,6 +160,10 @@ impl IntoResponse for AppError {
                     "An internal service error occurred".to_string(),
                 )
             }
+            AppError::OidcProviderErr(e) => {
+                tracing::error!("OIDC provider source error: {:?}", e);
+                (StatusCode::BAD_GATEWAY, self.to_string())
+            }
         };
 
         let body = Json(json!({ "error": error_message }));
@@ -4,22 +4,30 @@ use crate::{
     AppState,
     config::GLOBAL_CONFIG,
     handlers::{
-        auth::auth_router, company::company_router, group::group_router,
-        invitation::invitation_router, profile::profile_router, skill::skill_router,
-        user::user_router,
+        auth::auth_router, company::company_router, group::group_router, idea::idea_router,
+        invitation::invitation_router, market::market_router, permission::permission_router,
+        profile::profile_router, rating::rating_router, search::search_router, skill::skill_router,
+        team::team_router, user::user_router,
     },
 };
 use axum::Router;
 use tower_http::services::ServeDir;
 
 pub mod auth;
+pub mod comment;
 pub mod company;
 pub mod group;
+pub mod idea;
 pub mod invitation;
+pub mod market;
+pub mod permission;
 pub mod profile;
+pub mod rating;
+pub mod search;
 pub mod skill;
+pub mod team;
 pub mod user;
-// pub mod idea;
+pub mod well_known;
 
 pub fn main_router() -> Router<AppState> {
     let avatar_dir = PathBuf::from(GLOBAL_CONFIG.avatar_path.clone());
@@ -30,8 +38,13 @@ pub fn main_router() -> Router<AppState> {
         .nest("/company", company_router())
         .nest("/profile", profile_router())
         .nest("/users", user_router())
+        .nest("/permissions", permission_router())
         .nest("/skill", skill_router())
         .nest("/group", group_router())
-        // .nest("/idea", idea_router())
+        .nest("/idea", idea_router())
+        .nest("/rating", rating_router())
+        .nest("/search", search_router())
+        .nest("/team", team_router())
+        .nest("/market", market_router())
         .nest_service("/images/avatar", ServeDir::new(avatar_dir))
 }
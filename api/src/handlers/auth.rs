@@ -1,87 +1,269 @@
 use crate::{
     AppState,
+    config::GLOBAL_CONFIG,
     dtos::{
-        auth::{LoginPayload, PasswordResetPayload, RegisterPayload},
+        auth::{
+            ActiveRolePayload, LoginPayload, OidcCallbackParams, PasswordResetPayload,
+            RefreshTokenPayload, RegisterPayload, TokenResponse,
+        },
         common::{IdResponse, MessageResponse},
+        two_factor::TwoFactorLoginPayload,
     },
     error::AppError,
-    services::{auth::AuthService, profile::ProfileService},
-    utils::security::generate_tokens,
+    services::{
+        auth::AuthService, oidc::OidcService, profile::ProfileService, session::SessionService,
+        two_factor::TwoFactorService,
+    },
+    utils::{
+        rate_limit::{RateLimit, rate_limit},
+        security::{ClientInfo, SessionClaims, refresh_token_from_jar},
+    },
 };
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    response::IntoResponse,
-    routing::{post, put},
+    extract::{Path, Query, State},
+    middleware::from_fn_with_state,
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post, put},
 };
 use axum_extra::extract::{CookieJar, cookie::Cookie};
 use sea_orm::prelude::Uuid;
 
+const LOGIN_LIMIT: RateLimit = RateLimit {
+    scope: "login",
+    window_secs: 60,
+    per_ip: Some(20),
+    per_email: Some(10),
+};
+
+const TWO_FACTOR_LIMIT: RateLimit = RateLimit {
+    scope: "two_factor",
+    window_secs: 10 * 60,
+    per_ip: Some(20),
+    per_email: None,
+};
+
+const PASSWORD_RESET_REQUEST_LIMIT: RateLimit = RateLimit {
+    scope: "password_reset_request",
+    window_secs: 60 * 60,
+    per_ip: Some(10),
+    per_email: Some(3),
+};
+
+const PASSWORD_RESET_CONFIRM_LIMIT: RateLimit = RateLimit {
+    scope: "password_reset_confirm",
+    window_secs: 10 * 60,
+    per_ip: Some(10),
+    per_email: None,
+};
+
 pub fn auth_router() -> Router<AppState> {
     Router::new()
-        .route("/login", post(login))
+        .route(
+            "/login",
+            post(login).route_layer(from_fn_with_state(LOGIN_LIMIT, rate_limit)),
+        )
+        .route(
+            "/token",
+            post(login_with_token).route_layer(from_fn_with_state(LOGIN_LIMIT, rate_limit)),
+        )
+        .route(
+            "/2fa",
+            post(login_two_factor).route_layer(from_fn_with_state(TWO_FACTOR_LIMIT, rate_limit)),
+        )
+        .route(
+            "/token/2fa",
+            post(login_two_factor_with_token)
+                .route_layer(from_fn_with_state(TWO_FACTOR_LIMIT, rate_limit)),
+        )
+        .route("/oidc/login", get(oidc_login))
+        .route("/oidc/callback", get(oidc_callback))
         .route("/registration/{id}", post(registration))
         .route("/refresh", post(refresh))
+        .route("/token/refresh", post(refresh_token))
+        .route("/role", post(switch_role))
+        .route("/token/role", post(switch_role_with_token))
         .route("/logout", post(logout))
+        .route("/token/revoke", post(revoke_token))
         .route(
             "/password/verification/{email}",
-            post(request_to_update_password),
+            post(request_to_update_password)
+                .route_layer(from_fn_with_state(PASSWORD_RESET_REQUEST_LIMIT, rate_limit)),
+        )
+        .route(
+            "/password",
+            put(confirm_and_update_password)
+                .route_layer(from_fn_with_state(PASSWORD_RESET_CONFIRM_LIMIT, rate_limit)),
         )
-        .route("/password", put(confirm_and_update_password))
 }
 
 async fn login(
     State(state): State<AppState>,
+    client: ClientInfo,
     Json(payload): Json<LoginPayload>,
-) -> Result<impl IntoResponse, AppError> {
+) -> Result<Response, AppError> {
+    let user = AuthService::login(&state, payload).await?;
+
+    if let Some(challenge) = TwoFactorService::challenge(&state, &user).await? {
+        return Ok(challenge.into_response());
+    }
+
+    let tokens = SessionService::create(&state, &user, &client).await?;
+
+    Ok(tokens.into_cookie_jar().into_response())
+}
+
+async fn login_with_token(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<LoginPayload>,
+) -> Result<Response, AppError> {
     let user = AuthService::login(&state, payload).await?;
 
-    generate_tokens(
-        user.id,
-        user.email,
-        user.first_name,
-        user.last_name,
-        user.roles,
+    if let Some(challenge) = TwoFactorService::challenge(&state, &user).await? {
+        return Ok(challenge.into_response());
+    }
+
+    let tokens = SessionService::create(&state, &user, &client).await?;
+
+    Ok(TokenResponse::from(tokens).into_response())
+}
+
+async fn login_two_factor(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<TwoFactorLoginPayload>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = TwoFactorService::verify_challenge(&state, payload).await?;
+    let tokens = SessionService::create(&state, &user, &client).await?;
+
+    Ok(tokens.into_cookie_jar())
+}
+
+async fn login_two_factor_with_token(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<TwoFactorLoginPayload>,
+) -> Result<TokenResponse, AppError> {
+    let user = TwoFactorService::verify_challenge(&state, payload).await?;
+    let tokens = SessionService::create(&state, &user, &client).await?;
+
+    Ok(tokens.into())
+}
+
+async fn oidc_login(State(state): State<AppState>) -> Result<Redirect, AppError> {
+    let url = OidcService::authorization_url(&state).await?;
+
+    Ok(Redirect::to(&url))
+}
+
+async fn oidc_callback(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Query(params): Query<OidcCallbackParams>,
+) -> Result<Response, AppError> {
+    let user = OidcService::callback(&state, params).await?;
+
+    if let Some(challenge) = TwoFactorService::challenge(&state, &user).await? {
+        return Ok(Redirect::to(&format!(
+            "{}/login/2fa?challenge_id={}",
+            GLOBAL_CONFIG.client_url, challenge.challenge_id
+        ))
+        .into_response());
+    }
+
+    let tokens = SessionService::create(&state, &user, &client).await?;
+
+    Ok((
+        tokens.into_cookie_jar(),
+        Redirect::to(&GLOBAL_CONFIG.client_url),
     )
+        .into_response())
 }
 
 async fn registration(
     State(state): State<AppState>,
+    client: ClientInfo,
     Path(id): Path<Uuid>,
     Json(payload): Json<RegisterPayload>,
 ) -> Result<impl IntoResponse, AppError> {
     let user = AuthService::register_user(&state, id, payload).await?;
+    let tokens = SessionService::create(&state, &user, &client).await?;
 
-    generate_tokens(
-        user.id,
-        user.email,
-        user.first_name,
-        user.last_name,
-        user.roles,
-    )
+    Ok(tokens.into_cookie_jar())
 }
 
-pub async fn refresh(jar: CookieJar) -> Result<impl IntoResponse, AppError> {
-    let claims = AuthService::refresh(jar).await?;
+pub async fn refresh(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, AppError> {
+    let refresh_token = refresh_token_from_jar(&jar)?;
+    let tokens = SessionService::rotate(&state, &refresh_token, &client).await?;
 
-    generate_tokens(
-        claims.sub,
-        claims.email,
-        claims.first_name,
-        claims.last_name,
-        claims.roles,
-    )
+    Ok(tokens.into_cookie_jar())
+}
+
+async fn refresh_token(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<RefreshTokenPayload>,
+) -> Result<TokenResponse, AppError> {
+    let tokens = SessionService::rotate(&state, &payload.refresh_token, &client).await?;
+
+    Ok(tokens.into())
+}
+
+async fn switch_role(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    client: ClientInfo,
+    Json(payload): Json<ActiveRolePayload>,
+) -> Result<impl IntoResponse, AppError> {
+    let tokens = SessionService::switch_role(&state, &claims, payload.role, &client).await?;
+
+    Ok(tokens.into_cookie_jar())
+}
+
+async fn switch_role_with_token(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    client: ClientInfo,
+    Json(payload): Json<ActiveRolePayload>,
+) -> Result<TokenResponse, AppError> {
+    let tokens = SessionService::switch_role(&state, &claims, payload.role, &client).await?;
+
+    Ok(tokens.into())
 }
 
-pub async fn logout(jar: CookieJar) -> impl IntoResponse {
+pub async fn logout(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, AppError> {
+    if let Ok(refresh_token) = refresh_token_from_jar(&jar) {
+        SessionService::revoke_current(&state, &refresh_token).await?;
+    }
+
     let mut access_cookie = Cookie::from("access_token");
     access_cookie.set_path("/");
 
     let mut refresh_cookie = Cookie::from("refresh_token");
     refresh_cookie.set_path("/");
 
-    jar.remove(access_cookie).remove(refresh_cookie)
+    Ok(jar.remove(access_cookie).remove(refresh_cookie))
 }
+
+async fn revoke_token(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshTokenPayload>,
+) -> Result<MessageResponse, AppError> {
+    SessionService::revoke_current(&state, &payload.refresh_token).await?;
+
+    Ok(MessageResponse {
+        message: "Токен отозван".to_string(),
+    })
+}
+
 async fn request_to_update_password(
     State(state): State<AppState>,
     Path(email): Path<String>,
@@ -0,0 +1,62 @@
+use crate::{
+    AppState,
+    dtos::{
+        comment::{CommentDto, CreateCommentRequest, UpdateCommentRequest},
+        common::MessageResponse,
+    },
+    error::AppError,
+    services::comment::CommentService,
+    utils::security::Claims,
+};
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    routing::{get, put},
+};
+use sea_orm::prelude::Uuid;
+
+/// Nested under `/idea/{id}/comments`.
+pub fn comment_router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_comments).post(create_comment))
+        .route("/{comment_id}", put(update_comment).delete(delete_comment))
+}
+
+async fn get_comments(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(idea_id): Path<Uuid>,
+) -> Result<Json<Vec<CommentDto>>, AppError> {
+    let comments = CommentService::get_all(&state, idea_id, &claims).await?;
+    Ok(Json(comments))
+}
+
+async fn create_comment(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(idea_id): Path<Uuid>,
+    Json(payload): Json<CreateCommentRequest>,
+) -> Result<CommentDto, AppError> {
+    CommentService::create(&state, idea_id, payload, &claims).await
+}
+
+async fn update_comment(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((idea_id, comment_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateCommentRequest>,
+) -> Result<CommentDto, AppError> {
+    CommentService::update(&state, idea_id, comment_id, payload, &claims).await
+}
+
+async fn delete_comment(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((idea_id, comment_id)): Path<(Uuid, Uuid)>,
+) -> Result<MessageResponse, AppError> {
+    CommentService::delete(&state, idea_id, comment_id, &claims).await?;
+
+    Ok(MessageResponse {
+        message: "Комментарий успешно удален".to_string(),
+    })
+}
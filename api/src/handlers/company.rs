@@ -1,20 +1,24 @@
 use crate::{
     AppState,
     dtos::{
-        common::MessageResponse,
+        common::{ExportParams, MessageResponse},
         company::{CompanyResponse, CreateCompanyRequest, UpdateCompanyRequest},
         profile::UserDto,
     },
     error::AppError,
-    services::company::CompanyService,
+    services::{
+        company::CompanyService,
+        export::{ExportService, ExportSource},
+    },
     utils::security::Claims,
 };
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    response::Response,
     routing::get,
 };
-use macros::has_role;
+use macros::requires;
 use sea_orm::prelude::Uuid;
 
 pub fn company_router() -> Router<AppState> {
@@ -28,11 +32,20 @@ pub fn company_router() -> Router<AppState> {
         .route("/{id}", get(get_company_by_id).delete(delete_company))
         .route("/{id}/members", get(get_company_members))
         .route("/my", get(get_my_companies))
+        .route("/export", get(export_companies))
 }
 async fn get_all_companies(State(state): State<AppState>, _: Claims) -> Json<Vec<CompanyResponse>> {
     let companies = CompanyService::get_all(&state).await;
     Json(companies)
 }
+#[requires("report:export")]
+async fn export_companies(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, AppError> {
+    ExportService::export(&state, &claims, ExportSource::Companies, params.format).await
+}
 async fn get_company_members(
     State(state): State<AppState>,
     _: Claims,
@@ -57,7 +70,7 @@ async fn get_company_by_id(
     Ok(company)
 }
 
-#[has_role(Admin)]
+#[requires("company:manage:any")]
 async fn create_company(
     State(state): State<AppState>,
     claims: Claims,
@@ -66,23 +79,21 @@ async fn create_company(
     let company = CompanyService::create(&state, payload).await?;
     Ok(company)
 }
-#[has_role(Admin)]
 async fn update_company(
     State(state): State<AppState>,
     claims: Claims,
     Json(payload): Json<UpdateCompanyRequest>,
 ) -> Result<CompanyResponse, AppError> {
-    let company = CompanyService::update(&state, payload).await?;
+    let company = CompanyService::update(&state, payload, &claims).await?;
     Ok(company)
 }
 
-#[has_role(Admin)]
 async fn delete_company(
     State(state): State<AppState>,
     claims: Claims,
     Path(id): Path<Uuid>,
 ) -> Result<MessageResponse, AppError> {
-    CompanyService::delete(&state, id).await?;
+    CompanyService::delete(&state, id, &claims).await?;
     Ok(MessageResponse {
         message: "Компания успешно удалена".to_string(),
     })
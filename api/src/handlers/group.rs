@@ -1,7 +1,8 @@
 use crate::{
     AppState,
     dtos::{
-        common::MessageResponse, group::{CreateGroupRequest, GroupDto, UpdateGroupRequest}
+        common::MessageResponse,
+        group::{CreateGroupRequest, GroupDto, UpdateGroupRequest},
     },
     error::AppError,
     services::group::GroupService,
@@ -12,19 +13,19 @@ use axum::{
     extract::{Path, State},
     routing::get,
 };
-use macros::has_role;
+use macros::requires;
 use sea_orm::prelude::Uuid;
 
 pub fn group_router() -> Router<AppState> {
     Router::new()
-        .route("/", get(get_all_groups).post(create_group).put(update_group))
+        .route(
+            "/",
+            get(get_all_groups).post(create_group).put(update_group),
+        )
         .route("/{id}", get(get_group_by_id).delete(delete_group))
 }
 
-async fn get_all_groups(
-    State(state): State<AppState>,
-    _: Claims,
-) -> Json<Vec<GroupDto>> {
+async fn get_all_groups(State(state): State<AppState>, _: Claims) -> Json<Vec<GroupDto>> {
     let groups = GroupService::get_all(&state).await;
     Json(groups)
 }
@@ -38,7 +39,7 @@ async fn get_group_by_id(
     Ok(group)
 }
 
-#[has_role(Admin)]
+#[requires("group:manage:any")]
 async fn create_group(
     State(state): State<AppState>,
     claims: Claims,
@@ -48,7 +49,7 @@ async fn create_group(
     Ok(group)
 }
 
-#[has_role(Admin)]
+#[requires("group:manage:any")]
 async fn update_group(
     State(state): State<AppState>,
     claims: Claims,
@@ -58,7 +59,7 @@ async fn update_group(
     Ok(group)
 }
 
-#[has_role(Admin)]
+#[requires("group:manage:any")]
 async fn delete_group(
     State(state): State<AppState>,
     claims: Claims,
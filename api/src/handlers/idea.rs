@@ -1,58 +1,66 @@
-use axum::{
-    extract::{Path, State},
-    routing::{get, post, put},
-    Json, Router,
-};
-use macros::has_role;
-use sea_orm::prelude::Uuid;
-
 use crate::{
+    AppState,
     dtos::{
-        common::MessageResponse,
+        common::{ExportParams, MessageResponse, PagedResponse, PaginationParams},
         idea::{
-            CreateIdeaRequest, IdeaResponse, IdeaSkillRequest, UpdateIdeaRequest,
-            UpdateIdeaStatusRequest,
+            CreateIdeaRequest, IdeaFilterParams, IdeaResponse, IdeaSkillRequest,
+            IdeaStatusHistoryDto, UpdateIdeaRequest, UpdateIdeaStatusRequest,
         },
+        matching::IdeaMatchResponse,
         skill::SkillDto,
     },
     error::AppError,
-    services::idea::IdeaService,
+    handlers::comment::comment_router,
+    services::{
+        export::{ExportService, ExportSource},
+        idea::IdeaService,
+        matching::MatchingService,
+    },
     utils::security::Claims,
-    AppState,
 };
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    response::Response,
+    routing::{get, post, put},
+};
+use axum_extra::extract::Query as MultiQuery;
+use macros::requires;
+use sea_orm::prelude::Uuid;
 
 pub fn idea_router() -> Router<AppState> {
     Router::new()
-        .route("/", get(get_all_ideas).post(create_idea))
+        .route("/", get(get_all_ideas).post(create_idea).put(update_idea))
         .route("/draft", post(create_draft_idea))
         .route("/my", get(get_my_ideas))
-        .route(
-            "/:id",
-            get(get_idea_by_id)
-                .delete(delete_idea)
-                .put(update_idea),
-        )
-        .route("/:id/send", put(send_idea_to_approval))
-        .route("/:id/status", put(update_status))
-        .route("/:id/skills", get(get_idea_skills))
+        .route("/export", get(export_ideas))
         .route("/skills", put(update_idea_skills))
+        .route("/{id}", get(get_idea_by_id).delete(delete_idea))
+        .route("/{id}/send", put(send_idea_to_approval))
+        .route("/{id}/status", put(update_status))
+        .route("/{id}/skills", get(get_idea_skills))
+        .route("/{id}/history", get(get_status_history))
+        .route("/{id}/matches", get(get_idea_matches))
+        .nest("/{id}/comments", comment_router())
 }
 
-async fn get_idea_by_id(
+async fn get_all_ideas(
     State(state): State<AppState>,
     claims: Claims,
-    Path(id): Path<Uuid>,
-) -> Result<Json<IdeaResponse>, AppError> {
-    let idea = IdeaService::get_idea(&state, id, claims.sub).await?;
-    Ok(Json(idea))
+    Query(pagination): Query<PaginationParams>,
+    MultiQuery(filters): MultiQuery<IdeaFilterParams>,
+) -> Result<PagedResponse<IdeaResponse>, AppError> {
+    IdeaService::get_all(&state, &claims, filters, pagination).await
 }
 
-async fn get_all_ideas(
+#[requires("report:export")]
+async fn export_ideas(
     State(state): State<AppState>,
-    _: Claims,
-) -> Result<Json<Vec<IdeaResponse>>, AppError> {
-    let ideas = IdeaService::get_all(&state).await?;
-    Ok(Json(ideas))
+    claims: Claims,
+    Query(params): Query<ExportParams>,
+    MultiQuery(filters): MultiQuery<IdeaFilterParams>,
+) -> Result<Response, AppError> {
+    ExportService::export(&state, &claims, ExportSource::Ideas(filters), params.format).await
 }
 
 async fn get_my_ideas(
@@ -63,83 +71,100 @@ async fn get_my_ideas(
     Ok(Json(ideas))
 }
 
+async fn get_idea_by_id(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<IdeaResponse, AppError> {
+    IdeaService::get_idea(&state, id, &claims).await
+}
+
 async fn create_idea(
     State(state): State<AppState>,
     claims: Claims,
     Json(payload): Json<CreateIdeaRequest>,
-) -> Result<Json<IdeaResponse>, AppError> {
-    let idea = IdeaService::create(&state, payload, claims.sub).await?;
-    // a little bit of duplication, but it's better than having a separate status in the request
-    IdeaService::update_status_by_initiator(&state, idea.id, claims.sub).await?;
-    let idea = IdeaService::get_idea(&state, idea.id, claims.sub).await?;
-    Ok(Json(idea))
+) -> Result<IdeaResponse, AppError> {
+    IdeaService::create(&state, payload, &claims, true).await
 }
 
 async fn create_draft_idea(
     State(state): State<AppState>,
     claims: Claims,
     Json(payload): Json<CreateIdeaRequest>,
-) -> Result<Json<IdeaResponse>, AppError> {
-    let idea = IdeaService::create(&state, payload, claims.sub).await?;
-    Ok(Json(idea))
+) -> Result<IdeaResponse, AppError> {
+    IdeaService::create(&state, payload, &claims, false).await
 }
 
-#[has_role(Admin)]
 async fn update_idea(
     State(state): State<AppState>,
-    _: Claims,
+    claims: Claims,
     Json(payload): Json<UpdateIdeaRequest>,
-) -> Result<Json<IdeaResponse>, AppError> {
-    let idea = IdeaService::update_by_admin(&state, payload).await?;
-    Ok(Json(idea))
+) -> Result<IdeaResponse, AppError> {
+    IdeaService::update(&state, payload, &claims).await
 }
 
 async fn delete_idea(
     State(state): State<AppState>,
     claims: Claims,
     Path(id): Path<Uuid>,
-) -> Result<Json<MessageResponse>, AppError> {
-    if claims.roles.contains(&entity::role::Role::Admin) {
-        IdeaService::delete_by_admin(&state, id).await?;
-    } else {
-        IdeaService::delete_by_initiator(&state, id, claims.sub).await?;
-    }
-
-    Ok(Json(MessageResponse {
+) -> Result<MessageResponse, AppError> {
+    IdeaService::delete(&state, id, &claims).await?;
+
+    Ok(MessageResponse {
         message: "Идея успешно удалена".to_string(),
-    }))
+    })
 }
 
 async fn send_idea_to_approval(
     State(state): State<AppState>,
     claims: Claims,
     Path(id): Path<Uuid>,
-) -> Result<Json<MessageResponse>, AppError> {
-    IdeaService::update_status_by_initiator(&state, id, claims.sub).await?;
-    Ok(Json(MessageResponse {
+) -> Result<MessageResponse, AppError> {
+    IdeaService::submit(&state, id, &claims).await?;
+
+    Ok(MessageResponse {
         message: "Идея успешно отправлена на согласование".to_string(),
-    }))
+    })
 }
 
-#[has_role(ProjectOffice, Expert, Admin)]
 async fn update_status(
     State(state): State<AppState>,
-    _: Claims,
+    claims: Claims,
     Path(id): Path<Uuid>,
     Json(payload): Json<UpdateIdeaStatusRequest>,
-) -> Result<Json<MessageResponse>, AppError> {
-    IdeaService::update_status(&state, id, payload).await?;
-    Ok(Json(MessageResponse {
+) -> Result<MessageResponse, AppError> {
+    IdeaService::update_status(&state, id, payload, &claims).await?;
+
+    Ok(MessageResponse {
         message: "Статус идеи успешно обновлен".to_string(),
-    }))
+    })
+}
+
+#[requires("idea_history:read", active)]
+async fn get_status_history(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<IdeaStatusHistoryDto>>, AppError> {
+    let history = IdeaService::get_status_history(&state, id, &claims).await?;
+    Ok(Json(history))
+}
+
+#[requires("idea_match:read", active)]
+async fn get_idea_matches(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<IdeaMatchResponse, AppError> {
+    MatchingService::match_idea(&state, id, &claims).await
 }
 
 async fn get_idea_skills(
     State(state): State<AppState>,
-    _: Claims,
+    claims: Claims,
     Path(id): Path<Uuid>,
 ) -> Result<Json<Vec<SkillDto>>, AppError> {
-    let skills = IdeaService::get_idea_skills(&state, id).await?;
+    let skills = IdeaService::get_idea_skills(&state, id, &claims).await?;
     Ok(Json(skills))
 }
 
@@ -147,10 +172,10 @@ async fn update_idea_skills(
     State(state): State<AppState>,
     claims: Claims,
     Json(payload): Json<IdeaSkillRequest>,
-) -> Result<Json<MessageResponse>, AppError> {
-    let is_admin = claims.roles.contains(&entity::role::Role::Admin);
-    IdeaService::update_idea_skills(&state, payload, claims.sub, is_admin).await?;
-    Ok(Json(MessageResponse {
+) -> Result<MessageResponse, AppError> {
+    IdeaService::update_idea_skills(&state, payload, &claims).await?;
+
+    Ok(MessageResponse {
         message: "Навыки для идеи успешно обновлены".to_string(),
-    }))
+    })
 }
@@ -1,7 +1,8 @@
 use crate::{
     AppState,
     dtos::{
-        common::MessageResponse, invitation::{InvitationPayload, InvitationResponse},
+        common::MessageResponse,
+        invitation::{InvitationPayload, InvitationResponse},
     },
     error::AppError,
     services::invitation::InvitationService,
@@ -12,7 +13,7 @@ use axum::{
     extract::{Path, State},
     routing::{get, post},
 };
-use macros::has_role;
+use macros::requires;
 use sea_orm::prelude::Uuid;
 
 pub fn invitation_router() -> Router<AppState> {
@@ -33,7 +34,7 @@ async fn get_invitation(
     })
 }
 
-#[has_role(Admin)]
+#[requires("invitation:send")]
 async fn send_invitations(
     State(state): State<AppState>,
     claims: Claims,
@@ -0,0 +1,137 @@
+use crate::{
+    AppState,
+    dtos::{
+        common::MessageResponse,
+        market::{
+            ApplyToIdeaRequest, CreateMarketRequest, MarketDto, MarketIdeaDto,
+            MarketIdeaRequestDto, PublishIdeaRequest, UpdateMarketRequest,
+        },
+    },
+    error::AppError,
+    services::market::MarketService,
+    utils::security::Claims,
+};
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    routing::{get, put},
+};
+use macros::requires;
+use sea_orm::prelude::Uuid;
+
+pub fn market_router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            get(get_all_markets).post(create_market).put(update_market),
+        )
+        .route("/{id}", get(get_market_by_id))
+        .route("/{id}/ideas", get(get_market_ideas).post(publish_idea))
+        .route(
+            "/ideas/{market_idea_id}/requests",
+            get(get_idea_requests).post(apply_to_idea),
+        )
+        .route("/requests/{request_id}/accept", put(accept_request))
+        .route("/requests/{request_id}/decline", put(decline_request))
+        .route("/teams/{team_id}/requests", get(get_team_requests))
+}
+
+async fn get_all_markets(
+    State(state): State<AppState>,
+    _: Claims,
+) -> Result<Json<Vec<MarketDto>>, AppError> {
+    let markets = MarketService::get_all(&state).await?;
+    Ok(Json(markets))
+}
+
+async fn get_market_by_id(
+    State(state): State<AppState>,
+    _: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<MarketDto, AppError> {
+    MarketService::get_one(&state, id).await
+}
+
+#[requires("market:manage", active)]
+async fn create_market(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(payload): Json<CreateMarketRequest>,
+) -> Result<MarketDto, AppError> {
+    MarketService::create(&state, payload).await
+}
+
+#[requires("market:manage", active)]
+async fn update_market(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(payload): Json<UpdateMarketRequest>,
+) -> Result<MarketDto, AppError> {
+    MarketService::update(&state, payload).await
+}
+
+async fn get_market_ideas(
+    State(state): State<AppState>,
+    _: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<MarketIdeaDto>>, AppError> {
+    let ideas = MarketService::get_ideas(&state, id).await?;
+    Ok(Json(ideas))
+}
+
+#[requires("market:manage", active)]
+async fn publish_idea(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<PublishIdeaRequest>,
+) -> Result<MarketIdeaDto, AppError> {
+    MarketService::publish_idea(&state, id, payload, &claims).await
+}
+
+async fn get_idea_requests(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(market_idea_id): Path<Uuid>,
+) -> Result<Json<Vec<MarketIdeaRequestDto>>, AppError> {
+    let requests = MarketService::get_idea_requests(&state, market_idea_id, &claims).await?;
+    Ok(Json(requests))
+}
+
+async fn apply_to_idea(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(market_idea_id): Path<Uuid>,
+    Json(payload): Json<ApplyToIdeaRequest>,
+) -> Result<MarketIdeaRequestDto, AppError> {
+    MarketService::apply(&state, market_idea_id, payload, &claims).await
+}
+
+async fn get_team_requests(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(team_id): Path<Uuid>,
+) -> Result<Json<Vec<MarketIdeaRequestDto>>, AppError> {
+    let requests = MarketService::get_team_requests(&state, team_id, &claims).await?;
+    Ok(Json(requests))
+}
+
+async fn accept_request(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(request_id): Path<Uuid>,
+) -> Result<MarketIdeaDto, AppError> {
+    MarketService::accept_request(&state, request_id, &claims).await
+}
+
+async fn decline_request(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(request_id): Path<Uuid>,
+) -> Result<MessageResponse, AppError> {
+    MarketService::decline_request(&state, request_id, &claims).await?;
+
+    Ok(MessageResponse {
+        message: "Заявка отклонена".to_string(),
+    })
+}
@@ -0,0 +1,38 @@
+use crate::{
+    AppState,
+    dtos::permission::{PermissionsResponse, RolePermissionsDto, UpdateRolePermissionsRequest},
+    error::AppError,
+    services::authorizer::Authorizer,
+    utils::security::Claims,
+};
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    routing::{get, put},
+};
+use entity::role::Role;
+use macros::requires;
+
+pub fn permission_router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_permissions))
+        .route("/{role}", put(update_role_permissions))
+}
+
+#[requires("role:manage")]
+async fn get_permissions(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<PermissionsResponse, AppError> {
+    Authorizer::get_role_permissions(&state).await
+}
+
+#[requires("role:manage")]
+async fn update_role_permissions(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(role): Path<Role>,
+    Json(payload): Json<UpdateRolePermissionsRequest>,
+) -> Result<RolePermissionsDto, AppError> {
+    Authorizer::set_role_permissions(&state, role, payload).await
+}
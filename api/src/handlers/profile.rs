@@ -1,29 +1,77 @@
 use crate::{
     AppState,
     dtos::{
+        api_token::{ApiTokenDto, CreateApiTokenRequest, CreatedApiTokenResponse},
         auth::EmailResetPayload,
         common::{IdResponse, MessageResponse},
-        profile::ProfileUpdatePayload,
+        profile::{ProfileUpdatePayload, UpdateUserSkillsRequest, UserSkillDto},
+        session::SessionDto,
+        two_factor::{
+            RecoveryCodesResponse, TwoFactorCodePayload, TwoFactorEnrollmentResponse,
+            TwoFactorStatusResponse,
+        },
     },
     error::AppError,
-    services::profile::ProfileService,
-    utils::security::Claims,
+    services::{
+        api_token::ApiTokenService, profile::ProfileService, session::SessionService,
+        two_factor::TwoFactorService,
+    },
+    utils::{
+        rate_limit::{RateLimit, rate_limit},
+        security::{Claims, SessionClaims},
+    },
 };
 use axum::{
     Json, Router,
     extract::{Multipart, Path, State},
-    routing::{post, put},
+    middleware::from_fn_with_state,
+    routing::{delete, get, post, put},
+};
+use sea_orm::prelude::Uuid;
+
+const EMAIL_CHANGE_REQUEST_LIMIT: RateLimit = RateLimit {
+    scope: "email_change_request",
+    window_secs: 60 * 60,
+    per_ip: Some(10),
+    per_email: Some(3),
+};
+
+const EMAIL_CHANGE_CONFIRM_LIMIT: RateLimit = RateLimit {
+    scope: "email_change_confirm",
+    window_secs: 10 * 60,
+    per_ip: Some(10),
+    per_email: None,
 };
 
 pub fn profile_router() -> Router<AppState> {
     Router::new()
         .route("/", put(update_profile))
         .route("/avatar", post(upload_avatar))
+        .route("/skills", get(get_my_skills).put(update_my_skills))
+        .route(
+            "/sessions",
+            get(get_my_sessions).delete(revoke_all_my_sessions),
+        )
+        .route("/sessions/{id}", delete(revoke_my_session))
+        .route("/tokens", get(get_my_tokens).post(create_token))
+        .route("/tokens/{id}", delete(revoke_token))
+        .route(
+            "/2fa",
+            get(get_two_factor_status).delete(disable_two_factor),
+        )
+        .route("/2fa/enrollment", post(enroll_two_factor))
+        .route("/2fa/confirmation", post(confirm_two_factor))
+        .route("/2fa/codes", post(regenerate_recovery_codes))
         .route(
             "/email/verification/{new_email}",
-            post(request_to_update_email),
+            post(request_to_update_email)
+                .route_layer(from_fn_with_state(EMAIL_CHANGE_REQUEST_LIMIT, rate_limit)),
+        )
+        .route(
+            "/email",
+            put(confirm_and_update_email)
+                .route_layer(from_fn_with_state(EMAIL_CHANGE_CONFIRM_LIMIT, rate_limit)),
         )
-        .route("/email", put(confirm_and_update_email))
 }
 
 async fn upload_avatar(
@@ -50,6 +98,124 @@ async fn upload_avatar(
     Err(AppError::BadRequest)
 }
 
+async fn get_my_skills(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<Json<Vec<UserSkillDto>>, AppError> {
+    let skills = ProfileService::get_skills(&state, claims.sub).await?;
+    Ok(Json(skills))
+}
+
+async fn update_my_skills(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(payload): Json<UpdateUserSkillsRequest>,
+) -> Result<Json<Vec<UserSkillDto>>, AppError> {
+    let skills = ProfileService::update_skills(&state, payload, claims.sub).await?;
+    Ok(Json(skills))
+}
+
+async fn get_my_sessions(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+) -> Result<Json<Vec<SessionDto>>, AppError> {
+    let sessions = SessionService::get_all(&state, &claims).await?;
+    Ok(Json(sessions))
+}
+
+async fn revoke_my_session(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    Path(id): Path<Uuid>,
+) -> Result<MessageResponse, AppError> {
+    SessionService::revoke(&state, claims.sub, id).await?;
+
+    Ok(MessageResponse {
+        message: "Сессия завершена".to_string(),
+    })
+}
+
+async fn revoke_all_my_sessions(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+) -> Result<MessageResponse, AppError> {
+    SessionService::revoke_all(&state, claims.sub).await?;
+
+    Ok(MessageResponse {
+        message: "Все сессии завершены".to_string(),
+    })
+}
+
+async fn get_my_tokens(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+) -> Result<Json<Vec<ApiTokenDto>>, AppError> {
+    let tokens = ApiTokenService::get_all(&state, claims.sub).await?;
+    Ok(Json(tokens))
+}
+
+async fn create_token(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    Json(payload): Json<CreateApiTokenRequest>,
+) -> Result<CreatedApiTokenResponse, AppError> {
+    ApiTokenService::create(&state, payload, &claims).await
+}
+
+async fn revoke_token(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    Path(id): Path<Uuid>,
+) -> Result<MessageResponse, AppError> {
+    ApiTokenService::revoke(&state, id, claims.sub).await?;
+
+    Ok(MessageResponse {
+        message: "Токен отозван".to_string(),
+    })
+}
+
+async fn get_two_factor_status(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+) -> Result<TwoFactorStatusResponse, AppError> {
+    TwoFactorService::status(&state, &claims).await
+}
+
+async fn enroll_two_factor(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+) -> Result<TwoFactorEnrollmentResponse, AppError> {
+    TwoFactorService::enroll(&state, &claims).await
+}
+
+async fn confirm_two_factor(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    Json(payload): Json<TwoFactorCodePayload>,
+) -> Result<RecoveryCodesResponse, AppError> {
+    TwoFactorService::confirm(&state, &claims, payload).await
+}
+
+async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    Json(payload): Json<TwoFactorCodePayload>,
+) -> Result<RecoveryCodesResponse, AppError> {
+    TwoFactorService::regenerate_recovery_codes(&state, &claims, payload).await
+}
+
+async fn disable_two_factor(
+    State(state): State<AppState>,
+    SessionClaims(claims): SessionClaims,
+    Json(payload): Json<TwoFactorCodePayload>,
+) -> Result<MessageResponse, AppError> {
+    TwoFactorService::disable(&state, &claims, payload).await?;
+
+    Ok(MessageResponse {
+        message: "Двухфакторная аутентификация отключена".to_string(),
+    })
+}
+
 async fn update_profile(
     State(state): State<AppState>,
     claims: Claims,
@@ -64,7 +230,7 @@ async fn update_profile(
 
 async fn request_to_update_email(
     State(state): State<AppState>,
-    _: Claims,
+    _: SessionClaims,
     Path(new_email): Path<String>,
 ) -> Result<IdResponse, AppError> {
     let verification_id = ProfileService::request_email_change(&state, new_email).await?;
@@ -76,7 +242,7 @@ async fn request_to_update_email(
 
 async fn confirm_and_update_email(
     State(state): State<AppState>,
-    claims: Claims,
+    SessionClaims(claims): SessionClaims,
     Json(payload): Json<EmailResetPayload>,
 ) -> Result<MessageResponse, AppError> {
     ProfileService::confirm_email_change(&state, claims, payload).await?;
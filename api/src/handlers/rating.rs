@@ -0,0 +1,59 @@
+use crate::{
+    AppState,
+    dtos::rating::{RatingDto, RatingRequest},
+    error::AppError,
+    services::rating::RatingService,
+    utils::security::Claims,
+};
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    routing::{get, put},
+};
+use macros::requires;
+use sea_orm::prelude::Uuid;
+
+pub fn rating_router() -> Router<AppState> {
+    Router::new()
+        .route("/", put(save_rating))
+        .route("/confirm", put(confirm_rating))
+        .route("/{idea_id}", get(get_idea_ratings))
+        .route("/{idea_id}/my", get(get_my_rating))
+}
+
+#[requires("rating:read:any", active)]
+async fn get_idea_ratings(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(idea_id): Path<Uuid>,
+) -> Result<Json<Vec<RatingDto>>, AppError> {
+    let ratings = RatingService::get_idea_ratings(&state, idea_id).await?;
+    Ok(Json(ratings))
+}
+
+#[requires("rating:manage:own", active)]
+async fn get_my_rating(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(idea_id): Path<Uuid>,
+) -> Result<RatingDto, AppError> {
+    RatingService::get_my_rating(&state, idea_id, claims.sub).await
+}
+
+#[requires("rating:manage:own", active)]
+async fn save_rating(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(payload): Json<RatingRequest>,
+) -> Result<RatingDto, AppError> {
+    RatingService::save(&state, payload, claims.sub).await
+}
+
+#[requires("rating:manage:own", active)]
+async fn confirm_rating(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(payload): Json<RatingRequest>,
+) -> Result<RatingDto, AppError> {
+    RatingService::confirm(&state, payload, &claims).await
+}
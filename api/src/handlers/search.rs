@@ -0,0 +1,24 @@
+use crate::{
+    AppState,
+    dtos::search::{SearchParams, SearchResponse},
+    error::AppError,
+    services::search::SearchService,
+    utils::security::Claims,
+};
+use axum::{
+    Router,
+    extract::{Query, State},
+    routing::get,
+};
+
+pub fn search_router() -> Router<AppState> {
+    Router::new().route("/", get(search))
+}
+
+async fn search(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(params): Query<SearchParams>,
+) -> Result<SearchResponse, AppError> {
+    SearchService::search(&state, params, &claims).await
+}
@@ -5,7 +5,7 @@ use crate::{
         skill::{CreateSkillRequest, SkillDto, UpdateSkillRequest},
     },
     error::AppError,
-    services::skill::SkillService,
+    services::{authorizer::Authorizer, skill::SkillService},
     utils::security::Claims,
 };
 use axum::{
@@ -13,23 +13,23 @@ use axum::{
     extract::{Path, State},
     routing::{delete, get},
 };
-use entity::{role::Role, skill_type::SkillType};
-use macros::has_role;
+use entity::{permission::Permission, skill_type::SkillType};
+use macros::requires;
 use sea_orm::prelude::Uuid;
 use std::collections::HashMap;
 
 pub fn skill_router() -> Router<AppState> {
     Router::new()
-        .route("/", get(get_all_skills).post(create_skill).put(update_skill))
+        .route(
+            "/",
+            get(get_all_skills).post(create_skill).put(update_skill),
+        )
         .route("/{id}", delete(delete_skill))
         .route("/type/{skill_type}", get(get_skills_by_type))
-        .route("/my",get(get_all_my_or_confirmed))
+        .route("/my", get(get_all_my_or_confirmed))
 }
 
-async fn get_all_skills(
-    State(state): State<AppState>,
-    _: Claims,
-) -> Json<Vec<SkillDto>> {
+async fn get_all_skills(State(state): State<AppState>, _: Claims) -> Json<Vec<SkillDto>> {
     let skills = SkillService::get_all(&state).await;
     Json(skills)
 }
@@ -56,12 +56,12 @@ async fn create_skill(
     claims: Claims,
     Json(payload): Json<CreateSkillRequest>,
 ) -> Result<SkillDto, AppError> {
-    let is_confirmed = claims.roles.contains(&Role::Admin);
+    let is_confirmed = Authorizer::can(&state, &claims, Permission::SkillManageAny).await?;
     let skill = SkillService::create(&state, payload, claims.sub, is_confirmed).await?;
     Ok(skill)
 }
 
-#[has_role(Admin)]
+#[requires("skill:manage:any")]
 async fn update_skill(
     State(state): State<AppState>,
     claims: Claims,
@@ -73,7 +73,7 @@ async fn update_skill(
     })
 }
 
-#[has_role(Admin)]
+#[requires("skill:manage:any")]
 async fn delete_skill(
     State(state): State<AppState>,
     claims: Claims,
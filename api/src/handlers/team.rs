@@ -0,0 +1,195 @@
+use crate::{
+    AppState,
+    dtos::{
+        common::MessageResponse,
+        team::{
+            CreateTeamRequest, InviteTeamMemberRequest, TeamInvitationDto, TeamResponse,
+            TeamSkillDto, TransferOwnershipRequest, UpdateTeamMemberRoleRequest, UpdateTeamRequest,
+        },
+    },
+    error::AppError,
+    services::team::TeamService,
+    utils::security::Claims,
+};
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    routing::{delete, get, post, put},
+};
+use macros::requires;
+use sea_orm::prelude::Uuid;
+
+pub fn team_router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_all_teams).post(create_team).put(update_team))
+        .route("/my", get(get_my_teams))
+        .route("/invitations/my", get(get_my_invitations))
+        .route(
+            "/invitations/{invitation_id}/accept",
+            put(accept_invitation),
+        )
+        .route(
+            "/invitations/{invitation_id}/decline",
+            put(decline_invitation),
+        )
+        .route("/{id}", get(get_team_by_id).delete(delete_team))
+        .route("/{id}/skills", get(get_team_skills))
+        .route(
+            "/{id}/invitations",
+            get(get_team_invitations).post(invite_member),
+        )
+        .route("/{id}/leave", post(leave_team))
+        .route("/{id}/owner", put(transfer_ownership))
+        .route("/{id}/members/{user_id}", delete(kick_member))
+        .route("/{id}/members/{user_id}/role", put(update_member_role))
+}
+
+#[requires("team:read:any")]
+async fn get_all_teams(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<Json<Vec<TeamResponse>>, AppError> {
+    let teams = TeamService::get_all(&state).await?;
+    Ok(Json(teams))
+}
+
+async fn get_my_teams(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<Json<Vec<TeamResponse>>, AppError> {
+    let teams = TeamService::get_my(&state, claims.sub).await?;
+    Ok(Json(teams))
+}
+
+async fn get_team_by_id(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<TeamResponse, AppError> {
+    TeamService::get_by_id(&state, id, &claims).await
+}
+
+async fn create_team(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(payload): Json<CreateTeamRequest>,
+) -> Result<TeamResponse, AppError> {
+    TeamService::create(&state, payload, claims.sub).await
+}
+
+async fn update_team(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(payload): Json<UpdateTeamRequest>,
+) -> Result<TeamResponse, AppError> {
+    TeamService::update(&state, payload, &claims).await
+}
+
+async fn delete_team(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<MessageResponse, AppError> {
+    TeamService::delete(&state, id, &claims).await?;
+
+    Ok(MessageResponse {
+        message: "Команда успешно удалена".to_string(),
+    })
+}
+
+async fn get_team_skills(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<TeamSkillDto>>, AppError> {
+    let skills = TeamService::get_skill_profile(&state, id, &claims).await?;
+    Ok(Json(skills))
+}
+
+async fn get_team_invitations(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<TeamInvitationDto>>, AppError> {
+    let invitations = TeamService::get_team_invitations(&state, id, &claims).await?;
+    Ok(Json(invitations))
+}
+
+async fn invite_member(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<InviteTeamMemberRequest>,
+) -> Result<TeamInvitationDto, AppError> {
+    TeamService::invite(&state, id, payload, &claims).await
+}
+
+async fn get_my_invitations(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<Json<Vec<TeamInvitationDto>>, AppError> {
+    let invitations = TeamService::get_my_invitations(&state, &claims).await?;
+    Ok(Json(invitations))
+}
+
+async fn accept_invitation(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(invitation_id): Path<Uuid>,
+) -> Result<TeamResponse, AppError> {
+    TeamService::accept_invitation(&state, invitation_id, &claims).await
+}
+
+async fn decline_invitation(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(invitation_id): Path<Uuid>,
+) -> Result<MessageResponse, AppError> {
+    TeamService::decline_invitation(&state, invitation_id, &claims).await?;
+
+    Ok(MessageResponse {
+        message: "Приглашение отклонено".to_string(),
+    })
+}
+
+async fn leave_team(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<MessageResponse, AppError> {
+    TeamService::leave(&state, id, claims.sub).await?;
+
+    Ok(MessageResponse {
+        message: "Вы покинули команду".to_string(),
+    })
+}
+
+async fn kick_member(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<MessageResponse, AppError> {
+    TeamService::kick(&state, id, user_id, &claims).await?;
+
+    Ok(MessageResponse {
+        message: "Участник исключен из команды".to_string(),
+    })
+}
+
+async fn transfer_ownership(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<TransferOwnershipRequest>,
+) -> Result<TeamResponse, AppError> {
+    TeamService::transfer_ownership(&state, id, payload, &claims).await
+}
+
+async fn update_member_role(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateTeamMemberRoleRequest>,
+) -> Result<TeamResponse, AppError> {
+    TeamService::update_member_role(&state, id, user_id, payload, &claims).await
+}
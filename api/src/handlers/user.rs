@@ -1,37 +1,100 @@
 use crate::{
     AppState,
     dtos::{
-        common::{MessageResponse, PaginationParams},
+        common::{ExportParams, MessageResponse, PagedResponse, PaginationParams},
         profile::UserDto,
-        user::{UserCreatePayload, UserUpdatePayload},
+        two_factor::TwoFactorPolicyDto,
+        user::{
+            UserCreatePayload, UserFilterParams, UserImportParams, UserImportResponse,
+            UserUpdatePayload,
+        },
     },
     error::AppError,
-    services::user::UserService,
+    services::{
+        export::{ExportService, ExportSource},
+        session::SessionService,
+        two_factor::TwoFactorService,
+        user::UserService,
+        user_import::UserImportService,
+    },
     utils::security::Claims,
 };
 use axum::{
     Json, Router,
-    extract::{Path, Query, State},
-    routing::{get, put},
+    extract::{Multipart, Path, Query, State},
+    response::Response,
+    routing::{delete, get, post, put},
 };
-use macros::has_role;
+use axum_extra::extract::Query as MultiQuery;
+use macros::requires;
 use sea_orm::prelude::Uuid;
 
 pub fn user_router() -> Router<AppState> {
     Router::new()
         .route("/", get(get_user).post(create_user).put(update_user))
         .route("/all", get(get_all_users))
+        .route("/import", post(import_users))
+        .route("/export", get(export_users))
         .route("/{id}", get(get_user).delete(delete_user))
+        .route("/{id}/sessions", delete(revoke_user_sessions))
+        .route("/{id}/2fa", delete(reset_user_two_factor))
+        .route(
+            "/2fa/policy",
+            get(get_two_factor_policy).put(update_two_factor_policy),
+        )
         .route("/restore/{email}", put(restore_user))
 }
 
+#[requires("user:manage:any")]
 async fn get_all_users(
     State(state): State<AppState>,
-    _: Claims,
+    claims: Claims,
     Query(pagination): Query<PaginationParams>,
-) -> Json<Vec<UserDto>> {
-    Json(UserService::get_all(&state, pagination).await)
+    MultiQuery(filters): MultiQuery<UserFilterParams>,
+) -> Result<PagedResponse<UserDto>, AppError> {
+    UserService::get_all(&state, &claims, filters, pagination).await
+}
+
+#[requires("user:manage:any")]
+async fn import_users(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(params): Query<UserImportParams>,
+    mut multipart: Multipart,
+) -> Result<UserImportResponse, AppError> {
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|_| AppError::BadRequest)?
+    {
+        if field.name() == Some("file") {
+            let file_name = field.file_name().map(str::to_string);
+            let bytes = field.bytes().await.map_err(|_| AppError::BadRequest)?;
+
+            return UserImportService::import(
+                &state,
+                &claims,
+                params,
+                file_name.as_deref(),
+                &bytes,
+            )
+            .await;
+        }
+    }
+
+    Err(AppError::BadRequest)
+}
+
+#[requires("report:export")]
+async fn export_users(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(params): Query<ExportParams>,
+    MultiQuery(filters): MultiQuery<UserFilterParams>,
+) -> Result<Response, AppError> {
+    ExportService::export(&state, &claims, ExportSource::Users(filters), params.format).await
 }
+
 async fn get_user(
     State(state): State<AppState>,
     claims: Claims,
@@ -43,7 +106,7 @@ async fn get_user(
     }
 }
 
-#[has_role(Admin)]
+#[requires("user:manage:any")]
 async fn create_user(
     State(state): State<AppState>,
     claims: Claims,
@@ -56,7 +119,7 @@ async fn create_user(
     })
 }
 
-#[has_role(Admin)]
+#[requires("user:manage:any")]
 async fn update_user(
     State(state): State<AppState>,
     claims: Claims,
@@ -69,7 +132,7 @@ async fn update_user(
     })
 }
 
-#[has_role(Admin)]
+#[requires("user:manage:any")]
 async fn restore_user(
     State(state): State<AppState>,
     claims: Claims,
@@ -82,7 +145,7 @@ async fn restore_user(
     })
 }
 
-#[has_role(Admin)]
+#[requires("user:manage:any")]
 async fn delete_user(
     State(state): State<AppState>,
     claims: Claims,
@@ -94,3 +157,46 @@ async fn delete_user(
         message: "Успешное удаление пользователя".to_string(),
     })
 }
+
+#[requires("user:manage:any")]
+async fn revoke_user_sessions(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<MessageResponse, AppError> {
+    SessionService::revoke_all(&state, id).await?;
+
+    Ok(MessageResponse {
+        message: "Все сессии пользователя завершены".to_string(),
+    })
+}
+
+#[requires("user:manage:any")]
+async fn reset_user_two_factor(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<MessageResponse, AppError> {
+    TwoFactorService::reset(&state, id).await?;
+
+    Ok(MessageResponse {
+        message: "Двухфакторная аутентификация пользователя сброшена".to_string(),
+    })
+}
+
+#[requires("role:manage")]
+async fn get_two_factor_policy(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<TwoFactorPolicyDto, AppError> {
+    TwoFactorService::get_policy(&state).await
+}
+
+#[requires("role:manage")]
+async fn update_two_factor_policy(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(payload): Json<TwoFactorPolicyDto>,
+) -> Result<TwoFactorPolicyDto, AppError> {
+    TwoFactorService::set_policy(&state, payload).await
+}
@@ -0,0 +1,11 @@
+use crate::{AppState, config::GLOBAL_CONFIG};
+use axum::{Json, Router, routing::get};
+use jsonwebtoken::jwk::JwkSet;
+
+pub fn well_known_router() -> Router<AppState> {
+    Router::new().route("/jwks.json", get(get_jwks))
+}
+
+async fn get_jwks() -> Json<JwkSet> {
+    Json(GLOBAL_CONFIG.jwt_keys.jwks())
+}
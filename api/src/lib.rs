@@ -1,12 +1,14 @@
 use crate::{
-    config::GLOBAL_CONFIG, handlers::main_router, utils::startup::create_admin,
+    config::GLOBAL_CONFIG,
+    handlers::{main_router, well_known::well_known_router},
+    utils::startup::create_admin,
     workers::invitation_worker,
 };
-use axum::Router;
 use axum::http::{HeaderValue, Method, header};
+use axum::{Extension, Router};
 use migration::{Migrator, MigratorTrait};
 use sea_orm::{Database, DatabaseConnection};
-use std::fs;
+use std::{fs, net::SocketAddr};
 use tower_http::cors::CorsLayer;
 
 mod config;
@@ -54,18 +56,25 @@ pub async fn start() -> anyhow::Result<()> {
             Method::OPTIONS,
         ])
         .allow_credentials(true)
-        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE]);
+        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+        .expose_headers([header::RETRY_AFTER]);
 
     fs::create_dir_all(GLOBAL_CONFIG.avatar_path.clone())?;
 
     let app = Router::new()
         .nest("/api", main_router())
-        .with_state(state)
+        .nest("/.well-known", well_known_router())
+        .with_state(state.clone())
+        .layer(Extension(state))
         .layer(cors);
 
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", GLOBAL_CONFIG.port)).await?;
     tracing::debug!("listening on {}", listener.local_addr()?);
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
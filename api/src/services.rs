@@ -1,8 +1,22 @@
+pub mod api_token;
 pub mod auth;
+pub mod authorizer;
+pub mod comment;
 pub mod company;
+pub mod export;
 pub mod group;
-// pub mod idea;
+pub mod idea;
 pub mod invitation;
+pub mod ldap;
+pub mod market;
+pub mod matching;
+pub mod oidc;
 pub mod profile;
+pub mod rating;
+pub mod search;
+pub mod session;
 pub mod skill;
+pub mod team;
+pub mod two_factor;
 pub mod user;
+pub mod user_import;
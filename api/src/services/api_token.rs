@@ -0,0 +1,153 @@
+use crate::{
+    AppState,
+    dtos::api_token::{ApiTokenDto, CreateApiTokenRequest, CreatedApiTokenResponse},
+    error::AppError,
+    services::two_factor::TwoFactorService,
+    utils::security::{Claims, TokenType, hash_password, verify_password},
+};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{Duration, Local};
+use entity::{
+    api_token::{self, Entity as ApiToken},
+    users::{self, Entity as User},
+};
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait, EntityTrait, QueryFilter, QueryOrder,
+    prelude::{Expr, Uuid},
+};
+use validator::Validate;
+
+/// Prefix that lets `Claims` tell an API token from a JWT.
+pub const API_TOKEN_PREFIX: &str = "hits_";
+
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub struct ApiTokenService;
+
+impl ApiTokenService {
+    pub async fn get_all(state: &AppState, user_id: Uuid) -> Result<Vec<ApiTokenDto>, AppError> {
+        Ok(ApiToken::find()
+            .filter(api_token::Column::UserId.eq(user_id))
+            .order_by_desc(api_token::Column::CreatedAt)
+            .into_partial_model::<ApiTokenDto>()
+            .all(&state.conn)
+            .await?)
+    }
+
+    pub async fn create(
+        state: &AppState,
+        payload: CreateApiTokenRequest,
+        claims: &Claims,
+    ) -> Result<CreatedApiTokenResponse, AppError> {
+        payload.validate()?;
+
+        if payload
+            .scopes
+            .iter()
+            .any(|scope| !claims.roles.contains(scope))
+        {
+            return Err(AppError::Custom(
+                "Токен может содержать только ваши роли".to_string(),
+            ));
+        }
+
+        let secret = generate_secret();
+
+        let token = api_token::ActiveModel {
+            user_id: Set(claims.sub),
+            name: Set(payload.name),
+            token_hash: Set(hash_password(&secret)?),
+            scopes: Set(payload.scopes),
+            expires_at: Set((Local::now() + Duration::days(payload.expires_in_days)).into()),
+            ..Default::default()
+        }
+        .insert(&state.conn)
+        .await?;
+
+        Ok(CreatedApiTokenResponse {
+            secret: format!("{}{}_{}", API_TOKEN_PREFIX, token.id.simple(), secret),
+            token: ApiTokenDto {
+                id: token.id,
+                name: token.name,
+                scopes: token.scopes,
+                created_at: token.created_at,
+                expires_at: token.expires_at,
+                last_used_at: token.last_used_at,
+            },
+        })
+    }
+
+    pub async fn revoke(state: &AppState, id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let result = ApiToken::delete_many()
+            .filter(api_token::Column::Id.eq(id))
+            .filter(api_token::Column::UserId.eq(user_id))
+            .exec(&state.conn)
+            .await?;
+
+        if result.rows_affected == 0 {
+            return Err(AppError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Verifies an API token and builds `Claims` for it. Roles are limited to
+    /// the intersection of the owner's current roles and the roles granted to the token.
+    pub async fn authenticate(state: &AppState, token: &str) -> Result<Claims, AppError> {
+        let (id, secret) = token
+            .strip_prefix(API_TOKEN_PREFIX)
+            .and_then(|token| token.split_once('_'))
+            .ok_or(AppError::InvalidToken)?;
+
+        let id = Uuid::parse_str(id).map_err(|_| AppError::InvalidToken)?;
+
+        let (token, user) = ApiToken::find_by_id(id)
+            .filter(api_token::Column::ExpiresAt.gt(Local::now()))
+            .find_also_related(User)
+            .filter(users::Column::IsDeleted.eq(false))
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::InvalidToken)?;
+
+        let user = user.ok_or(AppError::InvalidToken)?;
+
+        if !verify_password(&token.token_hash, secret) {
+            return Err(AppError::InvalidToken);
+        }
+
+        ApiToken::update_many()
+            .col_expr(api_token::Column::LastUsedAt, Expr::value(Local::now()))
+            .filter(api_token::Column::Id.eq(token.id))
+            .exec(&state.conn)
+            .await?;
+
+        let user = TwoFactorService::apply_policy(state, user).await?;
+
+        let roles = user
+            .roles
+            .into_iter()
+            .filter(|role| token.scopes.contains(role))
+            .collect();
+
+        Ok(Claims {
+            sub: user.id,
+            email: user.email,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            exp: token.expires_at.timestamp() as usize,
+            iat: token.created_at.timestamp() as usize,
+            token_type: TokenType::Api,
+            roles,
+            active_role: None,
+            jti: Some(token.id),
+            sid: None,
+            ver: user.token_version,
+        })
+    }
+}
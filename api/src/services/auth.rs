@@ -1,17 +1,16 @@
 use crate::{
     AppState,
-    config::GLOBAL_CONFIG,
     dtos::auth::{LoginPayload, RegisterPayload},
     error::AppError,
-    utils::security::{Claims, TokenType, hash_password, verify_password},
+    services::ldap::{LdapLogin, LdapService},
+    utils::security::{hash_password, verify_password},
 };
-use axum_extra::extract::CookieJar;
 use chrono::Local;
 use entity::{
     invitation::{self, Entity as Invitation},
     users::{self, Entity as User},
 };
-use jsonwebtoken::{Validation, decode};
+use redis::AsyncCommands;
 use sea_orm::{
     ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter,
     TransactionTrait, prelude::Uuid,
@@ -19,39 +18,65 @@ use sea_orm::{
 use serde_json::json;
 use validator::Validate;
 
+const LOGIN_FAILURES_PREFIX: &str = "login_failures";
+const LOGIN_LOCK_PREFIX: &str = "login_lock";
+const MAX_FAILED_LOGINS: u32 = 5;
+const LOGIN_LOCKOUT_SECONDS: u64 = 15 * 60;
+
 pub struct AuthService;
 
 impl AuthService {
-    pub async fn refresh(jar: CookieJar) -> Result<Claims, AppError> {
-        let refresh_cookie = jar.get("refresh_token").ok_or(AppError::WrongCredentials)?;
-
-        let refresh_token = refresh_cookie.value();
+    pub async fn login(state: &AppState, payload: LoginPayload) -> Result<users::Model, AppError> {
+        payload.validate()?;
 
-        let token_data = decode::<Claims>(
-            refresh_token,
-            &GLOBAL_CONFIG.decoding_key,
-            &Validation::default(),
-        )
-        .map_err(|_| AppError::InvalidToken)?;
+        let email = payload.email.to_lowercase();
 
-        if token_data.claims.token_type != TokenType::Refresh {
-            return Err(AppError::InvalidToken);
-        }
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
 
-        Ok(token_data.claims)
-    }
+        let lock_key = format!("{}:{}", LOGIN_LOCK_PREFIX, email);
+        let failures_key = format!("{}:{}", LOGIN_FAILURES_PREFIX, email);
 
-    pub async fn login(state: &AppState, payload: LoginPayload) -> Result<users::Model, AppError> {
-        payload.validate()?;
+        let lock_ttl: i64 = redis_con.ttl(&lock_key).await?;
+        if lock_ttl > 0 {
+            return Err(AppError::TooManyRequests(lock_ttl as u64));
+        }
 
-        let user = User::find_by_email(payload.email.to_lowercase())
-            .one(&state.conn)
-            .await?
-            .ok_or(AppError::WrongCredentials)?;
+        let user = match LdapService::login(state, &email, &payload.password).await? {
+            LdapLogin::Authenticated(user) => Some(*user),
+            LdapLogin::Rejected => None,
+            LdapLogin::Local => User::find_by_email(email)
+                .one(&state.conn)
+                .await?
+                .filter(|user| {
+                    !user.is_deleted && verify_password(&user.password, &payload.password)
+                }),
+        };
+
+        let Some(user) = user else {
+            let failures: u32 = redis_con.incr(&failures_key, 1).await?;
+
+            if failures == 1 {
+                let _: () = redis_con
+                    .expire(&failures_key, LOGIN_LOCKOUT_SECONDS as i64)
+                    .await?;
+            }
+
+            if failures >= MAX_FAILED_LOGINS {
+                let _: () = redis::pipe()
+                    .atomic()
+                    .set_ex(&lock_key, 1, LOGIN_LOCKOUT_SECONDS)
+                    .del(&failures_key)
+                    .query_async(&mut redis_con)
+                    .await?;
+            }
 
-        if !verify_password(&user.password, &payload.password) {
             return Err(AppError::WrongCredentials);
-        }
+        };
+
+        let _: () = redis_con.del(&failures_key).await?;
 
         Ok(user)
     }
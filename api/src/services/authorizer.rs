@@ -0,0 +1,275 @@
+use crate::{
+    AppState,
+    dtos::permission::{PermissionsResponse, RolePermissionsDto, UpdateRolePermissionsRequest},
+    error::AppError,
+    utils::security::Claims,
+};
+use entity::{permission::Permission, prelude::RolePermission, role::Role, role_permission};
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, EntityTrait, Iterable, QueryFilter, TransactionTrait,
+    prelude::Uuid,
+};
+use std::{
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::RwLock;
+
+/// How long the in-process copy of `role_permission` lives. Changes made through
+/// another API instance become visible within this period.
+const PERMISSIONS_CACHE_TTL: Duration = Duration::from_secs(30);
+
+type Mapping = Arc<Vec<role_permission::Model>>;
+
+static MAPPING: LazyLock<RwLock<Option<(Instant, Mapping)>>> = LazyLock::new(|| RwLock::new(None));
+
+/// Bumped on every change of the mapping. A reload that started before the
+/// change doesn't put its stale result into the cache.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Permissions granted to at least one of the roles.
+fn granted_permissions(mapping: &[role_permission::Model], roles: &[Role]) -> Vec<Permission> {
+    let mut permissions: Vec<Permission> = Vec::new();
+    for row in mapping {
+        if roles.contains(&row.role) && !permissions.contains(&row.permission) {
+            permissions.push(row.permission);
+        }
+    }
+    permissions
+}
+
+fn is_granted(mapping: &[role_permission::Model], roles: &[Role], permission: Permission) -> bool {
+    mapping
+        .iter()
+        .any(|row| row.permission == permission && roles.contains(&row.role))
+}
+
+pub struct Authorizer;
+
+impl Authorizer {
+    async fn mapping(state: &AppState) -> Result<Mapping, AppError> {
+        if let Some((loaded_at, mapping)) = MAPPING.read().await.as_ref()
+            && loaded_at.elapsed() < PERMISSIONS_CACHE_TTL
+        {
+            return Ok(mapping.clone());
+        }
+
+        let generation = GENERATION.load(Ordering::Acquire);
+        let mapping = Arc::new(RolePermission::find().all(&state.conn).await?);
+
+        let mut cached = MAPPING.write().await;
+        if GENERATION.load(Ordering::Acquire) == generation {
+            *cached = Some((Instant::now(), mapping.clone()));
+        }
+
+        Ok(mapping)
+    }
+
+    /// Permissions granted to at least one of the roles.
+    pub async fn permissions(
+        state: &AppState,
+        roles: &[Role],
+    ) -> Result<Vec<Permission>, AppError> {
+        Ok(granted_permissions(&Self::mapping(state).await?, roles))
+    }
+
+    pub async fn can(
+        state: &AppState,
+        claims: &Claims,
+        permission: Permission,
+    ) -> Result<bool, AppError> {
+        Ok(is_granted(
+            &Self::mapping(state).await?,
+            &claims.roles,
+            permission,
+        ))
+    }
+
+    pub async fn require(
+        state: &AppState,
+        claims: &Claims,
+        permission: Permission,
+    ) -> Result<(), AppError> {
+        if !Self::can(state, claims, permission).await? {
+            return Err(AppError::Forbidden);
+        }
+        Ok(())
+    }
+
+    /// Like `can`, but only takes the active role into account when one is selected.
+    pub async fn can_active(
+        state: &AppState,
+        claims: &Claims,
+        permission: Permission,
+    ) -> Result<bool, AppError> {
+        Ok(is_granted(
+            &Self::mapping(state).await?,
+            claims.effective_roles(),
+            permission,
+        ))
+    }
+
+    pub async fn require_active(
+        state: &AppState,
+        claims: &Claims,
+        permission: Permission,
+    ) -> Result<(), AppError> {
+        if !Self::can_active(state, claims, permission).await? {
+            return Err(AppError::Forbidden);
+        }
+        Ok(())
+    }
+
+    /// Ownership-aware check: `any` grants access to every resource, `own` only
+    /// when the resource belongs to the user. Like `can_active`, it only takes
+    /// the active role into account when one is selected.
+    pub async fn can_owned(
+        state: &AppState,
+        claims: &Claims,
+        owner_id: Uuid,
+        own: Permission,
+        any: Permission,
+    ) -> Result<bool, AppError> {
+        let mapping = Self::mapping(state).await?;
+        let roles = claims.effective_roles();
+
+        Ok(is_granted(&mapping, roles, any)
+            || (owner_id == claims.sub && is_granted(&mapping, roles, own)))
+    }
+
+    pub async fn require_owned(
+        state: &AppState,
+        claims: &Claims,
+        owner_id: Uuid,
+        own: Permission,
+        any: Permission,
+    ) -> Result<(), AppError> {
+        if !Self::can_owned(state, claims, owner_id, own, any).await? {
+            return Err(AppError::Forbidden);
+        }
+        Ok(())
+    }
+
+    pub async fn get_role_permissions(state: &AppState) -> Result<PermissionsResponse, AppError> {
+        let mut roles = Vec::new();
+        for role in Role::iter() {
+            let permissions = Self::permissions(state, std::slice::from_ref(&role)).await?;
+            roles.push(RolePermissionsDto { role, permissions });
+        }
+
+        Ok(PermissionsResponse {
+            permissions: Permission::iter().collect(),
+            roles,
+        })
+    }
+
+    /// Replaces the permissions of a role. Admins can't lose `role:manage`,
+    /// otherwise nobody could restore permissions through the API.
+    pub async fn set_role_permissions(
+        state: &AppState,
+        role: Role,
+        payload: UpdateRolePermissionsRequest,
+    ) -> Result<RolePermissionsDto, AppError> {
+        let mut permissions: Vec<Permission> = Vec::new();
+        for permission in payload.permissions {
+            if !permissions.contains(&permission) {
+                permissions.push(permission);
+            }
+        }
+
+        if role == Role::Admin && !permissions.contains(&Permission::RoleManage) {
+            return Err(AppError::Conflict(
+                "Нельзя отозвать у администратора право role:manage".to_string(),
+            ));
+        }
+
+        let txn = state.conn.begin().await?;
+
+        RolePermission::delete_many()
+            .filter(role_permission::Column::Role.eq(role.clone()))
+            .exec(&txn)
+            .await?;
+
+        if !permissions.is_empty() {
+            RolePermission::insert_many(permissions.iter().map(|permission| {
+                role_permission::ActiveModel {
+                    role: Set(role.clone()),
+                    permission: Set(*permission),
+                }
+            }))
+            .exec(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        let mut cached = MAPPING.write().await;
+        GENERATION.fetch_add(1, Ordering::AcqRel);
+        *cached = None;
+
+        Ok(RolePermissionsDto { role, permissions })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(rows: &[(Role, Permission)]) -> Vec<role_permission::Model> {
+        rows.iter()
+            .map(|(role, permission)| role_permission::Model {
+                role: role.clone(),
+                permission: *permission,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn permissions_are_merged_across_roles() {
+        let mapping = mapping(&[
+            (Role::Initiator, Permission::IdeaUpdateOwn),
+            (Role::Initiator, Permission::CompanyManageOwn),
+            (Role::Expert, Permission::IdeaApprove),
+            (Role::Expert, Permission::IdeaUpdateOwn),
+            (Role::Admin, Permission::RoleManage),
+        ]);
+
+        assert_eq!(
+            granted_permissions(&mapping, &[Role::Initiator, Role::Expert]),
+            vec![
+                Permission::IdeaUpdateOwn,
+                Permission::CompanyManageOwn,
+                Permission::IdeaApprove
+            ]
+        );
+        assert!(granted_permissions(&mapping, &[Role::Member]).is_empty());
+    }
+
+    #[test]
+    fn permission_is_granted_only_through_listed_roles() {
+        let mapping = mapping(&[
+            (Role::Expert, Permission::IdeaApprove),
+            (Role::Admin, Permission::RoleManage),
+        ]);
+
+        assert!(is_granted(
+            &mapping,
+            &[Role::Initiator, Role::Expert],
+            Permission::IdeaApprove
+        ));
+        assert!(!is_granted(
+            &mapping,
+            &[Role::Initiator],
+            Permission::IdeaApprove
+        ));
+        assert!(!is_granted(
+            &mapping,
+            &[Role::Expert],
+            Permission::RoleManage
+        ));
+        assert!(!is_granted(&[], &[Role::Admin], Permission::RoleManage));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::{
+    AppState,
+    dtos::comment::{CommentDto, CreateCommentRequest, UpdateCommentRequest},
+    error::AppError,
+    services::{authorizer::Authorizer, idea::IdeaService},
+    utils::{security::Claims, smtp::send_comment_notification},
+};
+use chrono::Local;
+use entity::{
+    idea, idea_comment,
+    permission::Permission,
+    prelude::{Idea, IdeaComment, Users},
+};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, IntoActiveModel, JoinType,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, prelude::Uuid,
+};
+use validator::Validate;
+
+pub struct CommentService;
+
+impl CommentService {
+    pub async fn get_all(
+        state: &AppState,
+        idea_id: Uuid,
+        claims: &Claims,
+    ) -> Result<Vec<CommentDto>, AppError> {
+        Self::find_accessible_idea(state, idea_id, claims).await?;
+
+        let mut comments: Vec<CommentDto> = IdeaComment::find()
+            .join(JoinType::InnerJoin, idea_comment::Relation::Author.def())
+            .filter(idea_comment::Column::IdeaId.eq(idea_id))
+            .order_by_asc(idea_comment::Column::CreatedAt)
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        // Deleted comments stay in the list so that replies keep their place in the thread.
+        for comment in comments.iter_mut().filter(|c| c.deleted_at.is_some()) {
+            comment.text.clear();
+        }
+
+        Ok(comments)
+    }
+
+    pub async fn create(
+        state: &AppState,
+        idea_id: Uuid,
+        payload: CreateCommentRequest,
+        claims: &Claims,
+    ) -> Result<CommentDto, AppError> {
+        payload.validate()?;
+
+        let idea = Self::find_accessible_idea(state, idea_id, claims).await?;
+
+        if let Some(parent_id) = payload.parent_id {
+            IdeaComment::find_by_id(parent_id)
+                .filter(idea_comment::Column::IdeaId.eq(idea_id))
+                .filter(idea_comment::Column::DeletedAt.is_null())
+                .one(&state.conn)
+                .await?
+                .ok_or(AppError::NotFound)?;
+        }
+
+        let comment = idea_comment::ActiveModel {
+            idea_id: Set(idea_id),
+            author_id: Set(claims.sub),
+            parent_id: Set(payload.parent_id),
+            text: Set(payload.text),
+            ..Default::default()
+        }
+        .insert(&state.conn)
+        .await?;
+
+        if idea.initiator_id != claims.sub {
+            Self::notify_initiator(state, idea, claims).await?;
+        }
+
+        Self::get_one(state, comment.id).await
+    }
+
+    pub async fn update(
+        state: &AppState,
+        idea_id: Uuid,
+        comment_id: Uuid,
+        payload: UpdateCommentRequest,
+        claims: &Claims,
+    ) -> Result<CommentDto, AppError> {
+        payload.validate()?;
+
+        Self::find_accessible_idea(state, idea_id, claims).await?;
+
+        let comment = Self::find_comment(state, idea_id, comment_id).await?;
+
+        if comment.author_id != claims.sub {
+            return Err(AppError::Forbidden);
+        }
+
+        let mut comment = comment.into_active_model();
+
+        comment.text = Set(payload.text);
+        comment.edited_at = Set(Some(Local::now().into()));
+
+        let comment = comment.update(&state.conn).await?;
+
+        Self::get_one(state, comment.id).await
+    }
+
+    pub async fn delete(
+        state: &AppState,
+        idea_id: Uuid,
+        comment_id: Uuid,
+        claims: &Claims,
+    ) -> Result<(), AppError> {
+        Self::find_accessible_idea(state, idea_id, claims).await?;
+
+        let comment = Self::find_comment(state, idea_id, comment_id).await?;
+
+        if comment.author_id != claims.sub {
+            Authorizer::require(state, claims, Permission::CommentDeleteAny).await?;
+        }
+
+        let mut comment = comment.into_active_model();
+
+        comment.deleted_at = Set(Some(Local::now().into()));
+
+        comment.update(&state.conn).await?;
+
+        Ok(())
+    }
+
+    async fn get_one(state: &AppState, comment_id: Uuid) -> Result<CommentDto, AppError> {
+        IdeaComment::find_by_id(comment_id)
+            .join(JoinType::InnerJoin, idea_comment::Relation::Author.def())
+            .into_partial_model()
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    async fn find_comment(
+        state: &AppState,
+        idea_id: Uuid,
+        comment_id: Uuid,
+    ) -> Result<idea_comment::Model, AppError> {
+        IdeaComment::find_by_id(comment_id)
+            .filter(idea_comment::Column::IdeaId.eq(idea_id))
+            .filter(idea_comment::Column::DeletedAt.is_null())
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    async fn find_accessible_idea(
+        state: &AppState,
+        idea_id: Uuid,
+        claims: &Claims,
+    ) -> Result<idea::Model, AppError> {
+        let idea = Idea::find_by_id(idea_id)
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        IdeaService::check_access(state, &idea, claims).await?;
+
+        Ok(idea)
+    }
+
+    async fn notify_initiator(
+        state: &AppState,
+        idea: idea::Model,
+        claims: &Claims,
+    ) -> Result<(), AppError> {
+        let initiator = Users::find_by_id(idea.initiator_id)
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let first_name = claims.first_name.clone();
+        let last_name = claims.last_name.clone();
+
+        // The comment is already saved, so a failed email must not fail the request.
+        tokio::spawn(async move {
+            let result = send_comment_notification(
+                idea.id.to_string(),
+                idea.name,
+                first_name,
+                last_name,
+                initiator.email,
+            )
+            .await;
+
+            if let Err(e) = result {
+                tracing::error!("Failed to send notification: {}", e);
+            }
+        });
+
+        Ok(())
+    }
+}
@@ -5,9 +5,12 @@ use crate::{
         profile::UserDto,
     },
     error::AppError,
+    services::authorizer::Authorizer,
+    utils::security::Claims,
 };
 use entity::{
     company, company_member,
+    permission::Permission,
     prelude::{Company, CompanyMember, Users},
     users,
 };
@@ -139,22 +142,34 @@ impl CompanyService {
         Ok(company)
     }
 
+    /// The owner may change the company with `company:manage:own`.
     pub async fn update(
         state: &AppState,
         payload: UpdateCompanyRequest,
+        claims: &Claims,
     ) -> Result<CompanyResponse, AppError> {
         let txn = state.conn.begin().await?;
 
-        let mut company = Company::find_by_id(payload.id)
+        let company = Company::find_by_id(payload.id)
             .one(&txn)
             .await?
-            .ok_or(AppError::NotFound)?
-            .into_active_model();
+            .ok_or(AppError::NotFound)?;
 
-        if let Some(name) = payload.name {
-            if !name.is_empty() {
-                company.name = Set(name);
-            }
+        Authorizer::require_owned(
+            state,
+            claims,
+            company.owner_id,
+            Permission::CompanyManageOwn,
+            Permission::CompanyManageAny,
+        )
+        .await?;
+
+        let mut company = company.into_active_model();
+
+        if let Some(name) = payload.name
+            && !name.is_empty()
+        {
+            company.name = Set(name);
         }
 
         if let Some(owner_id) = payload.owner_id {
@@ -203,7 +218,21 @@ impl CompanyService {
 
         Ok(company)
     }
-    pub async fn delete(state: &AppState, id: Uuid) -> Result<(), AppError> {
+    pub async fn delete(state: &AppState, id: Uuid, claims: &Claims) -> Result<(), AppError> {
+        let company = Company::find_by_id(id)
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        Authorizer::require_owned(
+            state,
+            claims,
+            company.owner_id,
+            Permission::CompanyManageOwn,
+            Permission::CompanyManageAny,
+        )
+        .await?;
+
         Company::delete_by_id(id).exec(&state.conn).await?;
         Ok(())
     }
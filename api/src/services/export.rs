@@ -0,0 +1,741 @@
+use crate::{
+    AppState,
+    dtos::{idea::IdeaFilterParams, user::UserFilterParams},
+    error::AppError,
+    services::{authorizer::Authorizer, idea::IdeaService, user::UserService},
+    utils::{
+        security::Claims,
+        spreadsheet::{Cell, CsvWriter, SpreadsheetFormat, XlsxWriter},
+    },
+};
+use axum::{
+    body::{Body, Bytes},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::Local;
+use entity::{
+    company, company_member, group, group_member, idea, idea_skill,
+    permission::Permission,
+    prelude::{Company, CompanyMember, GroupMember, Idea, IdeaSkill, Users},
+    skill, users,
+};
+use futures::{StreamExt, TryStreamExt};
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, JoinType, ModelTrait, Order,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Value, prelude::Uuid,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::fs::File;
+use tokio_util::io::ReaderStream;
+
+/// How many rows are read from the database per query.
+const EXPORT_BATCH_SIZE: u64 = 500;
+
+const USER_COLUMNS: &[&str] = &[
+    "id",
+    "email",
+    "last_name",
+    "first_name",
+    "study_group",
+    "telephone",
+    "roles",
+    "groups",
+    "is_deleted",
+    "created_at",
+];
+
+/// Columns exported only with `user:manage:any`, as user search hides them too.
+const USER_CONTACT_COLUMNS: &[&str] = &["email", "study_group", "telephone"];
+
+const IDEA_COLUMNS: &[&str] = &[
+    "id",
+    "name",
+    "status",
+    "rating",
+    "pre_assessment",
+    "budget",
+    "initiator",
+    "initiator_email",
+    "skills",
+    "customer",
+    "created_at",
+    "modified_at",
+];
+
+const COMPANY_COLUMNS: &[&str] = &[
+    "id",
+    "name",
+    "owner",
+    "owner_email",
+    "members",
+    "created_at",
+];
+
+/// What is exported. Filters are the same as for the list endpoints.
+pub enum ExportSource {
+    Users(UserFilterParams),
+    Ideas(IdeaFilterParams),
+    Companies,
+}
+
+impl ExportSource {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Users(_) => "users",
+            Self::Ideas(_) => "ideas",
+            Self::Companies => "companies",
+        }
+    }
+
+    fn columns(&self, access: &ExportAccess) -> Vec<&'static str> {
+        match self {
+            Self::Users(_) if !access.contacts => without_contacts(USER_COLUMNS.to_vec()),
+            Self::Users(_) => USER_COLUMNS.to_vec(),
+            Self::Ideas(_) => IDEA_COLUMNS.to_vec(),
+            Self::Companies => COMPANY_COLUMNS.to_vec(),
+        }
+    }
+
+    /// The rows following `after`, or the first rows without it. An empty
+    /// batch means the export is complete.
+    async fn batch(
+        &self,
+        db: &DatabaseConnection,
+        access: &ExportAccess,
+        after: Option<&Cursor>,
+    ) -> Result<Batch, AppError> {
+        match self {
+            Self::Users(filters) => user_rows(db, filters, access.contacts, after).await,
+            Self::Ideas(filters) => idea_rows(db, filters, access.viewer, after).await,
+            Self::Companies => company_rows(db, after).await,
+        }
+    }
+}
+
+/// Sort key and id of the last exported row.
+///
+/// Batches are read by keyset rather than by OFFSET: rows added or deleted
+/// while the export runs don't shift the next batch, so nothing is skipped
+/// or exported twice.
+#[derive(Debug)]
+struct Cursor {
+    key: Value,
+    id: Uuid,
+}
+
+impl Cursor {
+    fn of<M: ModelTrait>(model: &M, column: <M::Entity as EntityTrait>::Column, id: Uuid) -> Self {
+        Self {
+            key: model.get(column),
+            id,
+        }
+    }
+
+    /// Rows after the cursor in the order `column` (nulls last), then id ascending.
+    fn next<C: ColumnTrait>(&self, column: C, id_column: C, order: Order) -> Condition {
+        let later_id = id_column.gt(self.id);
+
+        if self.key == self.key.as_null() {
+            return Condition::all().add(column.is_null()).add(later_id);
+        }
+
+        let beyond = match order {
+            Order::Desc => column.lt(self.key.clone()),
+            _ => column.gt(self.key.clone()),
+        };
+
+        Condition::any()
+            .add(beyond)
+            .add(
+                Condition::all()
+                    .add(column.eq(self.key.clone()))
+                    .add(later_id),
+            )
+            .add(column.is_null())
+    }
+}
+
+struct Batch {
+    rows: Vec<Vec<Cell>>,
+    last: Option<Cursor>,
+}
+
+/// What the caller may see, resolved once per export.
+struct ExportAccess {
+    /// Ideas are limited as in `GET /idea`, see `IdeaService::viewer`.
+    viewer: Option<Uuid>,
+    /// Whether `USER_CONTACT_COLUMNS` are exported.
+    contacts: bool,
+}
+
+/// Drops `USER_CONTACT_COLUMNS` from a header or row in `USER_COLUMNS` order.
+fn without_contacts<T>(values: Vec<T>) -> Vec<T> {
+    USER_COLUMNS
+        .iter()
+        .zip(values)
+        .filter(|(column, _)| !USER_CONTACT_COLUMNS.contains(column))
+        .map(|(_, value)| value)
+        .collect()
+}
+
+/// Variant name as used in the API: `ProjectOffice`, `OnApproval`.
+fn api_name<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn date(value: &sea_orm::prelude::DateTimeWithTimeZone) -> Cell {
+    Cell::Text(
+        value
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+    )
+}
+
+fn full_name(user: &users::Model) -> String {
+    format!("{} {}", user.last_name, user.first_name)
+}
+
+/// Groups `(id, value)` pairs by `id`.
+fn group_values(pairs: Vec<(Uuid, String)>) -> HashMap<Uuid, Vec<String>> {
+    let mut grouped: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for (id, value) in pairs {
+        grouped.entry(id).or_default().push(value);
+    }
+    grouped
+}
+
+async fn user_rows(
+    db: &DatabaseConnection,
+    filters: &UserFilterParams,
+    contacts: bool,
+    after: Option<&Cursor>,
+) -> Result<Batch, AppError> {
+    let (column, _) = UserService::sort_column(filters.sort_by);
+    let mut query = UserService::apply_filters(Users::find(), filters);
+    if let Some(cursor) = after {
+        query = query.filter(cursor.next(column, users::Column::Id, filters.sort_order.into()));
+    }
+
+    let users = query.limit(EXPORT_BATCH_SIZE).all(db).await?;
+    let last = users.last().map(|user| Cursor::of(user, column, user.id));
+
+    let ids: Vec<Uuid> = users.iter().map(|user| user.id).collect();
+
+    let groups = group_values(
+        GroupMember::find()
+            .select_only()
+            .column(group_member::Column::UserId)
+            .column(group::Column::Name)
+            .join(JoinType::InnerJoin, group_member::Relation::Group.def())
+            .filter(group_member::Column::UserId.is_in(ids))
+            .order_by_asc(group::Column::Name)
+            .into_tuple()
+            .all(db)
+            .await?,
+    );
+
+    let rows = users
+        .into_iter()
+        .map(|user| {
+            let row = vec![
+                Cell::Text(user.id.to_string()),
+                Cell::Text(user.email),
+                Cell::Text(user.last_name),
+                Cell::Text(user.first_name),
+                user.study_group.into(),
+                user.telephone.into(),
+                Cell::Text(
+                    user.roles
+                        .iter()
+                        .map(api_name)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+                Cell::Text(
+                    groups
+                        .get(&user.id)
+                        .map(|groups| groups.join(", "))
+                        .unwrap_or_default(),
+                ),
+                Cell::Text(user.is_deleted.to_string()),
+                date(&user.created_at),
+            ];
+            if contacts { row } else { without_contacts(row) }
+        })
+        .collect();
+
+    Ok(Batch { rows, last })
+}
+
+async fn idea_rows(
+    db: &DatabaseConnection,
+    filters: &IdeaFilterParams,
+    viewer: Option<Uuid>,
+    after: Option<&Cursor>,
+) -> Result<Batch, AppError> {
+    let (column, _) = IdeaService::sort_column(filters.sort_by);
+    let mut query = IdeaService::apply_filters(Idea::find(), filters, viewer);
+    if let Some(cursor) = after {
+        query = query.filter(cursor.next(column, idea::Column::Id, filters.sort_order.into()));
+    }
+
+    let ideas = query.limit(EXPORT_BATCH_SIZE).all(db).await?;
+    let last = ideas.last().map(|idea| Cursor::of(idea, column, idea.id));
+
+    let idea_ids: Vec<Uuid> = ideas.iter().map(|idea| idea.id).collect();
+    let initiator_ids: Vec<Uuid> = ideas.iter().map(|idea| idea.initiator_id).collect();
+
+    let initiators: HashMap<Uuid, users::Model> = Users::find()
+        .filter(users::Column::Id.is_in(initiator_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect();
+
+    let skills = group_values(
+        IdeaSkill::find()
+            .select_only()
+            .column(idea_skill::Column::IdeaId)
+            .column(skill::Column::Name)
+            .join(JoinType::InnerJoin, idea_skill::Relation::Skill.def())
+            .filter(idea_skill::Column::IdeaId.is_in(idea_ids))
+            .order_by_asc(skill::Column::Name)
+            .into_tuple()
+            .all(db)
+            .await?,
+    );
+
+    let rows = ideas
+        .into_iter()
+        .map(|idea: idea::Model| {
+            let initiator = initiators.get(&idea.initiator_id);
+            vec![
+                Cell::Text(idea.id.to_string()),
+                Cell::Text(idea.name),
+                Cell::Text(api_name(&idea.status)),
+                idea.rating.into(),
+                idea.pre_assessment.into(),
+                idea.budget.into(),
+                initiator.map(full_name).into(),
+                initiator.map(|user| user.email.clone()).into(),
+                Cell::Text(
+                    skills
+                        .get(&idea.id)
+                        .map(|skills| skills.join(", "))
+                        .unwrap_or_default(),
+                ),
+                idea.customer.into(),
+                date(&idea.created_at),
+                date(&idea.modified_at),
+            ]
+        })
+        .collect();
+
+    Ok(Batch { rows, last })
+}
+
+async fn company_rows(db: &DatabaseConnection, after: Option<&Cursor>) -> Result<Batch, AppError> {
+    let mut query = Company::find()
+        .order_by_asc(company::Column::Name)
+        .order_by_asc(company::Column::Id);
+    if let Some(cursor) = after {
+        query = query.filter(cursor.next(company::Column::Name, company::Column::Id, Order::Asc));
+    }
+
+    let companies = query.limit(EXPORT_BATCH_SIZE).all(db).await?;
+    let last = companies
+        .last()
+        .map(|company| Cursor::of(company, company::Column::Name, company.id));
+
+    let company_ids: Vec<Uuid> = companies.iter().map(|company| company.id).collect();
+    let owner_ids: Vec<Uuid> = companies.iter().map(|company| company.owner_id).collect();
+
+    let owners: HashMap<Uuid, users::Model> = Users::find()
+        .filter(users::Column::Id.is_in(owner_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect();
+
+    let members: Vec<(Uuid, String, String, String)> = CompanyMember::find()
+        .select_only()
+        .column(company_member::Column::CompanyId)
+        .column(users::Column::LastName)
+        .column(users::Column::FirstName)
+        .column(users::Column::Email)
+        .join(JoinType::InnerJoin, company_member::Relation::Users.def())
+        .filter(company_member::Column::CompanyId.is_in(company_ids))
+        .order_by_asc(users::Column::LastName)
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let members = group_values(
+        members
+            .into_iter()
+            .map(|(company_id, last_name, first_name, email)| {
+                (
+                    company_id,
+                    format!("{} {} <{}>", last_name, first_name, email),
+                )
+            })
+            .collect(),
+    );
+
+    let rows = companies
+        .into_iter()
+        .map(|company| {
+            let owner = owners.get(&company.owner_id);
+            vec![
+                Cell::Text(company.id.to_string()),
+                Cell::Text(company.name),
+                owner.map(full_name).into(),
+                owner.map(|user| user.email.clone()).into(),
+                Cell::Text(
+                    members
+                        .get(&company.id)
+                        .map(|members| members.join(", "))
+                        .unwrap_or_default(),
+                ),
+                date(&company.created_at),
+            ]
+        })
+        .collect();
+
+    Ok(Batch { rows, last })
+}
+
+struct ExportStream {
+    db: DatabaseConnection,
+    source: ExportSource,
+    access: ExportAccess,
+    writer: Option<CsvWriter>,
+    after: Option<Cursor>,
+}
+
+/// Runs blocking spreadsheet work off the async runtime.
+async fn blocking<T, F>(work: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(work).await.map_err(|e| {
+        tracing::error!("Export task failed: {:?}", e);
+        AppError::InternalServerError
+    })?
+}
+
+pub struct ExportService;
+
+impl ExportService {
+    /// Exports rows read in batches of `EXPORT_BATCH_SIZE`, so only one batch
+    /// is held in memory at a time.
+    pub async fn export(
+        state: &AppState,
+        claims: &Claims,
+        source: ExportSource,
+        format: SpreadsheetFormat,
+    ) -> Result<Response, AppError> {
+        if let ExportSource::Users(filters) = &source
+            && filters.is_deleted
+        {
+            Authorizer::require(state, claims, Permission::UserManageAny).await?;
+        }
+
+        let file_name = format!(
+            "{}_{}.{}",
+            source.name(),
+            Local::now().format("%Y%m%d_%H%M%S"),
+            format.extension()
+        );
+
+        let access = ExportAccess {
+            viewer: IdeaService::viewer(state, claims).await?,
+            contacts: Authorizer::can(state, claims, Permission::UserManageAny).await?,
+        };
+
+        let db = state.conn.clone();
+        let body = match format {
+            SpreadsheetFormat::Csv => Self::csv_body(db, source, access).await?,
+            SpreadsheetFormat::Xlsx => Self::xlsx_body(db, source, access).await?,
+        };
+
+        Ok((
+            [
+                (header::CONTENT_TYPE, format.content_type().to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", file_name),
+                ),
+            ],
+            body,
+        )
+            .into_response())
+    }
+
+    /// Streams CSV batch by batch. The first batch is read before the response
+    /// is sent, so most failures still produce an error status; later ones can
+    /// only cut the file short and are logged.
+    async fn csv_body(
+        db: DatabaseConnection,
+        source: ExportSource,
+        access: ExportAccess,
+    ) -> Result<Body, AppError> {
+        let mut writer = CsvWriter::new(&source.columns(&access))?;
+
+        let batch = source.batch(&db, &access, None).await?;
+        for row in batch.rows {
+            writer.write_row(row)?;
+        }
+        let first_chunk = writer.take_chunk()?;
+
+        let export = ExportStream {
+            db,
+            writer: batch.last.is_some().then_some(writer),
+            source,
+            access,
+            after: batch.last,
+        };
+
+        let rest = futures::stream::try_unfold(export, |mut export| async move {
+            let Some(writer) = export.writer.as_mut() else {
+                return Ok(None);
+            };
+
+            let batch = export
+                .source
+                .batch(&export.db, &export.access, export.after.as_ref())
+                .await?;
+            if batch.last.is_none() {
+                export.writer = None;
+            } else {
+                for row in batch.rows {
+                    writer.write_row(row)?;
+                }
+                export.after = batch.last;
+            }
+
+            let chunk = match export.writer.as_mut() {
+                Some(writer) => writer.take_chunk()?,
+                None => Vec::new(),
+            };
+
+            Ok::<_, AppError>(Some((Bytes::from(chunk), export)))
+        })
+        .inspect_err(|e| tracing::error!("Export failed after the response was sent: {:?}", e));
+
+        let stream = futures::stream::once(async { Ok::<_, AppError>(Bytes::from(first_chunk)) })
+            .chain(rest);
+
+        Ok(Body::from_stream(stream))
+    }
+
+    /// XLSX is a ZIP archive with its directory at the end, so the file is
+    /// assembled completely before the response is sent and any failure comes
+    /// back as an error status. Rows are still read batch by batch and kept in
+    /// temporary files; the finished file is streamed from disk.
+    async fn xlsx_body(
+        db: DatabaseConnection,
+        source: ExportSource,
+        access: ExportAccess,
+    ) -> Result<Body, AppError> {
+        let columns = source.columns(&access);
+        let mut writer = blocking(move || XlsxWriter::new(&columns)).await?;
+
+        let mut after = None;
+        loop {
+            let batch = source.batch(&db, &access, after.as_ref()).await?;
+            if batch.last.is_none() {
+                break;
+            }
+
+            let rows = batch.rows;
+            writer = blocking(move || {
+                for row in rows {
+                    writer.write_row(row)?;
+                }
+                Ok(writer)
+            })
+            .await?;
+            after = batch.last;
+        }
+
+        let file = blocking(move || writer.finish()).await?;
+
+        Ok(Body::from_stream(ReaderStream::new(File::from_std(file))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dtos::{common::SortOrder, user::UserSortField};
+    use crate::utils::{
+        spreadsheet::read_rows,
+        testing::{TestUsers, claims_for, test_state, unique_email},
+    };
+    use entity::{idea_status::IdeaStatus, role::Role};
+    use sea_orm::{ActiveModelTrait, ActiveValue::Set, IntoActiveModel};
+
+    /// Runs the export and returns its rows, header first.
+    async fn export_csv(
+        state: &AppState,
+        claims: &Claims,
+        source: ExportSource,
+    ) -> Vec<Vec<String>> {
+        let response = ExportService::export(state, claims, source, SpreadsheetFormat::Csv)
+            .await
+            .unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        read_rows(SpreadsheetFormat::Csv, &bytes)
+            .unwrap()
+            .into_iter()
+            .map(|row| row.cells)
+            .collect()
+    }
+
+    #[test]
+    fn contact_columns_are_dropped_by_name() {
+        let columns = without_contacts(USER_COLUMNS.to_vec());
+
+        assert_eq!(
+            columns.len(),
+            USER_COLUMNS.len() - USER_CONTACT_COLUMNS.len()
+        );
+        for column in USER_CONTACT_COLUMNS {
+            assert!(USER_COLUMNS.contains(column));
+            assert!(!columns.contains(column));
+        }
+        assert_eq!(
+            columns,
+            [
+                "id",
+                "last_name",
+                "first_name",
+                "roles",
+                "groups",
+                "is_deleted",
+                "created_at"
+            ]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn cursor_continues_after_every_row() {
+        let state = test_state().await;
+        let mut users = TestUsers::new(&state);
+        let marker = format!("cursor-{}", Uuid::new_v4());
+        for (last_name, study_group) in
+            [("b", None), ("a", Some("A")), ("b", Some("B")), ("a", None)]
+        {
+            let user = users
+                .create(&unique_email(&marker), vec![Role::Member])
+                .await;
+            let mut user = user.into_active_model();
+            user.last_name = Set(last_name.to_string());
+            user.study_group = Set(study_group.map(str::to_string));
+            user.update(&state.conn).await.unwrap();
+        }
+
+        for sort_by in [UserSortField::StudyGroup, UserSortField::LastName] {
+            for sort_order in [SortOrder::Asc, SortOrder::Desc] {
+                let filters = UserFilterParams {
+                    search: Some(marker.clone()),
+                    sort_by,
+                    sort_order,
+                    ..Default::default()
+                };
+                let (column, _) = UserService::sort_column(sort_by);
+                let query = || UserService::apply_filters(Users::find(), &filters);
+                let all = query().all(&state.conn).await.unwrap();
+                assert_eq!(all.len(), 4);
+
+                for (index, user) in all.iter().enumerate() {
+                    let cursor = Cursor::of(user, column, user.id);
+                    let rest = query()
+                        .filter(cursor.next(column, users::Column::Id, sort_order.into()))
+                        .all(&state.conn)
+                        .await
+                        .unwrap();
+                    assert_eq!(rest, all[index + 1..]);
+                }
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn user_export_shows_contacts_only_to_managers() {
+        let state = test_state().await;
+        let mut users = TestUsers::new(&state);
+        let office = users
+            .create(&unique_email("office"), vec![Role::ProjectOffice])
+            .await;
+        let admin = users
+            .create(&unique_email("admin"), vec![Role::Admin])
+            .await;
+        let exported = users
+            .create(&unique_email("exported"), vec![Role::Member])
+            .await;
+
+        let filters = || UserFilterParams {
+            search: Some(exported.email.clone()),
+            ..Default::default()
+        };
+
+        let rows = export_csv(&state, &claims_for(&office), ExportSource::Users(filters())).await;
+        assert_eq!(rows[0], without_contacts(USER_COLUMNS.to_vec()));
+        assert_eq!(rows.len(), 2);
+        assert!(!rows[1].contains(&exported.email));
+
+        let rows = export_csv(&state, &claims_for(&admin), ExportSource::Users(filters())).await;
+        assert_eq!(rows[0], USER_COLUMNS);
+        assert_eq!(rows[1][1], exported.email);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn idea_export_hides_drafts_of_other_initiators() {
+        let state = test_state().await;
+        let mut users = TestUsers::new(&state);
+        let office = users
+            .create(&unique_email("office"), vec![Role::ProjectOffice])
+            .await;
+        let initiator = users
+            .create(&unique_email("initiator"), vec![Role::Initiator])
+            .await;
+
+        let marker = format!("export-{}", Uuid::new_v4());
+        let mut ideas = Vec::new();
+        for status in [IdeaStatus::New, IdeaStatus::OnMarket] {
+            let idea = idea::ActiveModel {
+                initiator_id: Set(initiator.id),
+                name: Set(marker.clone()),
+                status: Set(status),
+                ..Default::default()
+            }
+            .insert(&state.conn)
+            .await
+            .unwrap();
+            ideas.push(idea.id.to_string());
+        }
+
+        let filters = IdeaFilterParams {
+            search: Some(marker),
+            ..Default::default()
+        };
+        let rows = export_csv(&state, &claims_for(&office), ExportSource::Ideas(filters)).await;
+
+        let ids: Vec<&str> = rows[1..].iter().map(|row| row[0].as_str()).collect();
+        assert_eq!(ids, [ideas[1].as_str()]);
+    }
+}
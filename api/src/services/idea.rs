@@ -1,136 +1,530 @@
-use sea_orm::{
-    ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, QueryFilter, QuerySelect, Set,
-    TransactionTrait,
-};
-use uuid::Uuid;
-
 use crate::{
-    dtos::idea::{
-        CreateIdeaRequest, IdeaResponse, IdeaSkillRequest, UpdateIdeaRequest,
-        UpdateIdeaStatusRequest,
+    AppState,
+    dtos::{
+        common::{PagedResponse, PaginationParams},
+        group::GroupDto,
+        idea::{
+            CreateIdeaRequest, IdeaFilterParams, IdeaResponse, IdeaSkillRequest, IdeaSortField,
+            IdeaStatusHistoryDto, UpdateIdeaRequest, UpdateIdeaStatusRequest,
+        },
+        profile::UserDto,
+        skill::SkillDto,
     },
-    dtos::skill::SkillDto,
     error::AppError,
-    services::{group::GroupService, profile::ProfileService},
-    AppState,
+    services::authorizer::Authorizer,
+    utils::security::Claims,
 };
+use chrono::Local;
 use entity::{
-    company, group,
-    idea::{self, IdeaStatus},
-    idea_checked, idea_skill, skill, users,
+    group, group_member, idea, idea_checked, idea_skill,
+    idea_status::IdeaStatus,
+    idea_status_history,
+    permission::Permission,
+    prelude::{Group, GroupMember, Idea, IdeaChecked, IdeaSkill, IdeaStatusHistory, Skill, Users},
+    skill, users,
+};
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait, Condition, ConnectionTrait, EntityTrait, IntoActiveModel, JoinType, Order,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select, TransactionTrait,
+    prelude::Uuid,
+    sea_query::{self, NullOrdering},
 };
+use std::collections::{HashMap, HashSet};
+use validator::Validate;
+
+/// Members of which of the idea's groups may make a status change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reviewers {
+    /// The permission alone is enough.
+    Anyone,
+    Experts,
+    ProjectOffice,
+    ExpertsOrProjectOffice,
+}
+
+/// Allowed idea status changes, the permission required for each of them and
+/// the groups the actor must belong to. `OnEditing` is the return path used to
+/// send an idea back to its initiator.
+const STATUS_TRANSITIONS: &[(IdeaStatus, IdeaStatus, Permission, Reviewers)] = &[
+    (
+        IdeaStatus::New,
+        IdeaStatus::OnApproval,
+        Permission::IdeaSubmitAny,
+        Reviewers::Anyone,
+    ),
+    (
+        IdeaStatus::OnEditing,
+        IdeaStatus::OnApproval,
+        Permission::IdeaSubmitAny,
+        Reviewers::Anyone,
+    ),
+    (
+        IdeaStatus::OnApproval,
+        IdeaStatus::OnConfirmation,
+        Permission::IdeaApprove,
+        Reviewers::Experts,
+    ),
+    (
+        IdeaStatus::OnApproval,
+        IdeaStatus::OnEditing,
+        Permission::IdeaReturnApproval,
+        Reviewers::ExpertsOrProjectOffice,
+    ),
+    (
+        IdeaStatus::OnConfirmation,
+        IdeaStatus::Confirmed,
+        Permission::IdeaConfirm,
+        Reviewers::ProjectOffice,
+    ),
+    (
+        IdeaStatus::OnConfirmation,
+        IdeaStatus::OnEditing,
+        Permission::IdeaReturnConfirmation,
+        Reviewers::ProjectOffice,
+    ),
+    (
+        IdeaStatus::Confirmed,
+        IdeaStatus::OnMarket,
+        Permission::IdeaPublish,
+        Reviewers::ProjectOffice,
+    ),
+];
 
 pub struct IdeaService;
 
 impl IdeaService {
-    async fn idea_model_to_response(
+    fn find_transition(
+        from: &IdeaStatus,
+        to: &IdeaStatus,
+    ) -> Result<(Permission, Reviewers), AppError> {
+        STATUS_TRANSITIONS
+            .iter()
+            .find(|(edge_from, edge_to, _, _)| edge_from == from && edge_to == to)
+            .map(|(_, _, permission, reviewers)| (*permission, *reviewers))
+            .ok_or_else(|| AppError::InvalidStatusTransition {
+                from: from.clone(),
+                to: to.clone(),
+            })
+    }
+
+    /// Groups of `idea` whose members count as `reviewers`.
+    fn reviewer_groups(idea: &idea::Model, reviewers: Reviewers) -> Vec<Uuid> {
+        let (experts, project_office) = match reviewers {
+            Reviewers::Anyone => (None, None),
+            Reviewers::Experts => (idea.group_expert_id, None),
+            Reviewers::ProjectOffice => (None, idea.group_project_office_id),
+            Reviewers::ExpertsOrProjectOffice => {
+                (idea.group_expert_id, idea.group_project_office_id)
+            }
+        };
+        [experts, project_office].into_iter().flatten().collect()
+    }
+
+    /// Checks that `claims` may move `idea` to `to`: the transition must exist,
+    /// the active role must grant its permission, and the actor must be in the
+    /// responsible group of the idea unless they hold `idea:update:any`.
+    pub async fn check_transition(
         state: &AppState,
-        model: idea::Model,
-    ) -> Result<IdeaResponse, AppError> {
-        let initiator = ProfileService::get_user_dto_by_id(state, model.initiator_id).await?;
+        claims: &Claims,
+        idea: &idea::Model,
+        to: &IdeaStatus,
+    ) -> Result<(), AppError> {
+        let (permission, reviewers) = Self::find_transition(&idea.status, to)?;
+        Authorizer::require_active(state, claims, permission).await?;
+
+        if reviewers == Reviewers::Anyone
+            || Authorizer::can_active(state, claims, Permission::IdeaUpdateAny).await?
+        {
+            return Ok(());
+        }
+
+        let is_reviewer = GroupMember::find()
+            .filter(group_member::Column::GroupId.is_in(Self::reviewer_groups(idea, reviewers)))
+            .filter(group_member::Column::UserId.eq(claims.sub))
+            .count(&state.conn)
+            .await?
+            > 0;
+
+        if !is_reviewer {
+            return Err(AppError::Forbidden);
+        }
+        Ok(())
+    }
+
+    fn check_editable_by_initiator(idea: &idea::Model) -> Result<(), AppError> {
+        if !matches!(idea.status, IdeaStatus::New | IdeaStatus::OnEditing) {
+            return Err(AppError::Conflict(
+                "Идею можно редактировать только в статусе NEW или ON_EDITING".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Initiators edit their ideas only while they are drafts or sent back;
+    /// `idea:update:any` lifts both the ownership and the status restriction.
+    async fn check_editable(
+        state: &AppState,
+        idea: &idea::Model,
+        claims: &Claims,
+    ) -> Result<(), AppError> {
+        if Authorizer::can_active(state, claims, Permission::IdeaUpdateAny).await? {
+            return Ok(());
+        }
 
-        let experts = if let Some(group_id) = model.group_expert_id {
-            Some(GroupService::get_one(state, group_id).await?)
+        Authorizer::require_owned(
+            state,
+            claims,
+            idea.initiator_id,
+            Permission::IdeaUpdateOwn,
+            Permission::IdeaUpdateAny,
+        )
+        .await?;
+
+        Self::check_editable_by_initiator(idea)
+    }
+
+    /// Ideas `user_id` may open without `idea:read:any`: their own ideas, ideas
+    /// on the market and ideas assigned to their expert or project office
+    /// groups. Both the list and `check_access` filter by it; idea search
+    /// repeats it in SQL.
+    fn visible_to(user_id: Uuid) -> Condition {
+        let member_groups = sea_query::Query::select()
+            .column(group_member::Column::GroupId)
+            .from(group_member::Entity)
+            .and_where(group_member::Column::UserId.eq(user_id))
+            .to_owned();
+
+        Condition::any()
+            .add(idea::Column::InitiatorId.eq(user_id))
+            .add(idea::Column::Status.eq(IdeaStatus::OnMarket))
+            .add(idea::Column::GroupExpertId.in_subquery(member_groups.clone()))
+            .add(idea::Column::GroupProjectOfficeId.in_subquery(member_groups))
+    }
+
+    /// The user whose visible ideas the caller is limited to, or `None` when
+    /// the caller holds `idea:read:any`. Pass it to `apply_filters`.
+    pub async fn viewer(state: &AppState, claims: &Claims) -> Result<Option<Uuid>, AppError> {
+        if Authorizer::can(state, claims, Permission::IdeaReadAny).await? {
+            Ok(None)
         } else {
-            None
+            Ok(Some(claims.sub))
+        }
+    }
+
+    /// Grants access to the ideas listed by `visible_to`, and to every idea for
+    /// holders of `idea:read:any`.
+    pub async fn check_access(
+        state: &AppState,
+        idea: &idea::Model,
+        claims: &Claims,
+    ) -> Result<(), AppError> {
+        let Some(user_id) = Self::viewer(state, claims).await? else {
+            return Ok(());
         };
 
-        let project_office = if let Some(group_id) = model.group_project_office_id {
-            Some(GroupService::get_one(state, group_id).await?)
-        } else {
-            None
+        let visible = Idea::find_by_id(idea.id)
+            .filter(Self::visible_to(user_id))
+            .count(&state.conn)
+            .await?
+            > 0;
+
+        if !visible {
+            return Err(AppError::Forbidden);
+        }
+        Ok(())
+    }
+
+    /// Builds responses for a page of ideas with a fixed number of queries: the
+    /// groups, their members, the users (initiators and members) and the
+    /// checked flags are each loaded in one batch.
+    async fn models_to_responses(
+        state: &AppState,
+        models: Vec<idea::Model>,
+        user_id: Uuid,
+    ) -> Result<Vec<IdeaResponse>, AppError> {
+        if models.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<Uuid> = models.iter().map(|idea| idea.id).collect();
+
+        let group_ids: HashSet<Uuid> = models
+            .iter()
+            .flat_map(|idea| [idea.group_expert_id, idea.group_project_office_id])
+            .flatten()
+            .collect();
+
+        let members: Vec<(Uuid, Uuid)> = GroupMember::find()
+            .select_only()
+            .column(group_member::Column::GroupId)
+            .column(group_member::Column::UserId)
+            .filter(group_member::Column::GroupId.is_in(group_ids.clone()))
+            .into_tuple()
+            .all(&state.conn)
+            .await?;
+
+        let user_ids: HashSet<Uuid> = models
+            .iter()
+            .map(|idea| idea.initiator_id)
+            .chain(members.iter().map(|(_, user_id)| *user_id))
+            .collect();
+
+        let users: HashMap<Uuid, UserDto> = Users::find()
+            .filter(users::Column::Id.is_in(user_ids))
+            .into_partial_model::<UserDto>()
+            .all(&state.conn)
+            .await?
+            .into_iter()
+            .map(|user| (user.id, user))
+            .collect();
+
+        let mut groups: HashMap<Uuid, GroupDto> = Group::find()
+            .filter(group::Column::Id.is_in(group_ids))
+            .into_partial_model::<GroupDto>()
+            .all(&state.conn)
+            .await?
+            .into_iter()
+            .map(|group| (group.id, group))
+            .collect();
+
+        for (group_id, member_id) in members {
+            if let (Some(group), Some(user)) = (groups.get_mut(&group_id), users.get(&member_id)) {
+                group.members.push(user.clone());
+            }
+        }
+
+        let checked: HashSet<Uuid> = IdeaChecked::find()
+            .select_only()
+            .column(idea_checked::Column::IdeaId)
+            .filter(idea_checked::Column::UserId.eq(user_id))
+            .filter(idea_checked::Column::IdeaId.is_in(ids))
+            .into_tuple::<Uuid>()
+            .all(&state.conn)
+            .await?
+            .into_iter()
+            .collect();
+
+        models
+            .into_iter()
+            .map(|model| {
+                let initiator = users
+                    .get(&model.initiator_id)
+                    .cloned()
+                    .ok_or(AppError::NotFound)?;
+
+                Ok(IdeaResponse {
+                    id: model.id,
+                    initiator,
+                    name: model.name,
+                    experts: model
+                        .group_expert_id
+                        .and_then(|group_id| groups.get(&group_id).cloned()),
+                    project_office: model
+                        .group_project_office_id
+                        .and_then(|group_id| groups.get(&group_id).cloned()),
+                    is_checked: checked.contains(&model.id),
+                    status: model.status,
+                    created_at: model.created_at,
+                    modified_at: model.modified_at,
+                    is_active: model.is_active,
+                    problem: model.problem,
+                    solution: model.solution,
+                    result: model.result,
+                    customer: model.customer,
+                    contact_person: model.contact_person,
+                    description: model.description,
+                    suitability: model.suitability,
+                    budget: model.budget,
+                    pre_assessment: model.pre_assessment,
+                    rating: model.rating,
+                    max_team_size: model.max_team_size,
+                    min_team_size: model.min_team_size,
+                })
+            })
+            .collect()
+    }
+
+    async fn model_to_response(
+        state: &AppState,
+        model: idea::Model,
+        user_id: Uuid,
+    ) -> Result<IdeaResponse, AppError> {
+        Self::models_to_responses(state, vec![model], user_id)
+            .await?
+            .pop()
+            .ok_or(AppError::NotFound)
+    }
+
+    /// Column behind a sort field and whether it is nullable; nulls sort last.
+    pub fn sort_column(field: IdeaSortField) -> (idea::Column, bool) {
+        match field {
+            IdeaSortField::CreatedAt => (idea::Column::CreatedAt, false),
+            IdeaSortField::Rating => (idea::Column::Rating, true),
+            IdeaSortField::Budget => (idea::Column::Budget, true),
+        }
+    }
+
+    /// Applies the list filters and sorting. With `viewer` set, only the ideas
+    /// visible to that user are kept.
+    pub fn apply_filters(
+        mut query: Select<Idea>,
+        filters: &IdeaFilterParams,
+        viewer: Option<Uuid>,
+    ) -> Select<Idea> {
+        if let Some(user_id) = viewer {
+            query = query.filter(Self::visible_to(user_id));
+        }
+        if !filters.status.is_empty() {
+            query = query.filter(idea::Column::Status.is_in(filters.status.clone()));
+        }
+        if let Some(initiator_id) = filters.initiator_id {
+            query = query.filter(idea::Column::InitiatorId.eq(initiator_id));
+        }
+        if let Some(group_id) = filters.group_expert_id {
+            query = query.filter(idea::Column::GroupExpertId.eq(group_id));
+        }
+        if !filters.skill_ids.is_empty() {
+            query = query.filter(
+                idea::Column::Id.in_subquery(
+                    sea_query::Query::select()
+                        .column(idea_skill::Column::IdeaId)
+                        .from(idea_skill::Entity)
+                        .and_where(idea_skill::Column::SkillId.is_in(filters.skill_ids.clone()))
+                        .to_owned(),
+                ),
+            );
+        }
+        if let Some(created_from) = filters.created_from {
+            query = query.filter(idea::Column::CreatedAt.gte(created_from));
+        }
+        if let Some(created_to) = filters.created_to {
+            query = query.filter(idea::Column::CreatedAt.lte(created_to));
+        }
+        if let Some(search) = filters.search.as_deref().map(str::trim)
+            && !search.is_empty()
+        {
+            let pattern = format!(
+                "%{}%",
+                search
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            );
+            query = query.filter(
+                Condition::any()
+                    .add(idea::Column::Name.ilike(&pattern))
+                    .add(idea::Column::Problem.ilike(&pattern))
+                    .add(idea::Column::Solution.ilike(&pattern)),
+            );
+        }
+
+        let order: Order = filters.sort_order.into();
+        query = match Self::sort_column(filters.sort_by) {
+            (column, true) => query.order_by_with_nulls(column, order, NullOrdering::Last),
+            (column, false) => query.order_by(column, order),
         };
 
-        Ok(IdeaResponse {
-            id: model.id,
-            initiator,
-            name: model.name,
-            experts,
-            project_office,
-            is_checked: false, // TODO: Implement is_checked
-            status: model.status,
-            created_at: model.created_at,
-            modified_at: model.modified_at,
-            is_active: model.is_active,
-            problem: model.problem,
-            solution: model.solution,
-            result: model.result,
-            customer: model.customer,
-            contact_person: model.contact_person,
-            description: model.description,
-            suitability: model.suitability,
-            budget: model.budget,
-            pre_assessment: model.pre_assessment,
-            rating: model.rating,
-            max_team_size: model.max_team_size,
-            min_team_size: model.min_team_size,
-        })
+        // Tie-breaker so that pages stay stable between requests.
+        query.order_by_asc(idea::Column::Id)
     }
 
     pub async fn get_idea(
         state: &AppState,
         idea_id: Uuid,
-        user_id: Uuid,
+        claims: &Claims,
     ) -> Result<IdeaResponse, AppError> {
-        let idea = idea::Entity::find_by_id(idea_id)
-            .one(&state.db)
+        let user_id = claims.sub;
+
+        let idea = Idea::find_by_id(idea_id)
+            .one(&state.conn)
             .await?
-            .ok_or_else(|| AppError::NotFound("Idea not found".to_owned()))?;
+            .ok_or(AppError::NotFound)?;
 
-        let is_checked = idea_checked::Entity::find()
-            .filter(
-                idea_checked::Column::IdeaId
-                    .eq(idea_id)
-                    .and(idea_checked::Column::UserId.eq(user_id)),
-            )
-            .one(&state.db)
+        Self::check_access(state, &idea, claims).await?;
+
+        let is_checked = IdeaChecked::find_by_id((idea_id, user_id))
+            .one(&state.conn)
             .await?
             .is_some();
 
         if !is_checked {
-            let checked = idea_checked::ActiveModel {
+            idea_checked::ActiveModel {
                 idea_id: Set(idea_id),
                 user_id: Set(user_id),
-            };
-            checked.insert(&state.db).await?;
+            }
+            .insert(&state.conn)
+            .await?;
         }
 
-        let mut response = Self::idea_model_to_response(state, idea).await?;
-        response.is_checked = true;
-        Ok(response)
+        Self::model_to_response(state, idea, user_id).await
     }
 
-    pub async fn get_all(state: &AppState) -> Result<Vec<IdeaResponse>, AppError> {
-        let ideas = idea::Entity::find().all(&state.db).await?;
-        let mut responses = Vec::new();
-        for idea in ideas {
-            responses.push(Self::idea_model_to_response(state, idea).await?);
-        }
-        Ok(responses)
+    /// Lists ideas page by page. Without `idea:read:any` the list is limited to
+    /// the ideas the caller could open through `get_idea`.
+    pub async fn get_all(
+        state: &AppState,
+        claims: &Claims,
+        filters: IdeaFilterParams,
+        pagination: PaginationParams,
+    ) -> Result<PagedResponse<IdeaResponse>, AppError> {
+        pagination.validate()?;
+
+        let viewer = Self::viewer(state, claims).await?;
+
+        let paginator = Self::apply_filters(Idea::find(), &filters, viewer)
+            .paginate(&state.conn, pagination.page_size);
+
+        let total = paginator.num_items().await?;
+        let ideas = paginator.fetch_page(pagination.page).await?;
+
+        Ok(PagedResponse {
+            items: Self::models_to_responses(state, ideas, claims.sub).await?,
+            total,
+            page: pagination.page,
+            page_size: pagination.page_size,
+        })
     }
 
     pub async fn get_list_by_initiator(
         state: &AppState,
         user_id: Uuid,
     ) -> Result<Vec<IdeaResponse>, AppError> {
-        let ideas = idea::Entity::find()
+        let ideas = Idea::find()
             .filter(idea::Column::InitiatorId.eq(user_id))
-            .all(&state.db)
+            .order_by_desc(idea::Column::CreatedAt)
+            .all(&state.conn)
             .await?;
-        let mut responses = Vec::new();
-        for idea in ideas {
-            responses.push(Self::idea_model_to_response(state, idea).await?);
-        }
-        Ok(responses)
+
+        Self::models_to_responses(state, ideas, user_id).await
     }
 
+    /// Creates an idea in `New`. With `submit` the idea is sent to approval in
+    /// the same transaction, so a failed submit leaves no draft behind.
     pub async fn create(
         state: &AppState,
         payload: CreateIdeaRequest,
-        initiator_id: Uuid,
+        claims: &Claims,
+        submit: bool,
     ) -> Result<IdeaResponse, AppError> {
+        payload.validate()?;
+
+        if submit {
+            Authorizer::require_owned(
+                state,
+                claims,
+                claims.sub,
+                Permission::IdeaSubmitOwn,
+                Permission::IdeaSubmitAny,
+            )
+            .await?;
+        }
+
+        let txn = state.conn.begin().await?;
+
         let idea = idea::ActiveModel {
-            initiator_id: Set(initiator_id),
+            initiator_id: Set(claims.sub),
             name: Set(payload.name),
             group_expert_id: Set(payload.group_expert_id),
             group_project_office_id: Set(payload.group_project_office_id),
@@ -143,27 +537,45 @@ impl IdeaService {
             description: Set(payload.description),
             suitability: Set(payload.suitability),
             budget: Set(payload.budget),
-            max_team_size: Set(payload.max_team_size),
-            min_team_size: Set(payload.min_team_size),
+            max_team_size: Set(Some(payload.max_team_size)),
+            min_team_size: Set(Some(payload.min_team_size)),
             ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        let idea = if submit {
+            Self::change_status(&txn, idea, IdeaStatus::OnApproval, Some(claims), None).await?
+        } else {
+            idea
         };
 
-        let idea = idea.insert(&state.db).await?;
-        Self::idea_model_to_response(state, idea).await
+        txn.commit().await?;
+
+        Self::model_to_response(state, idea, claims.sub).await
     }
 
-    pub async fn update_by_initiator(
+    pub async fn update(
         state: &AppState,
         payload: UpdateIdeaRequest,
-        initiator_id: Uuid,
+        claims: &Claims,
     ) -> Result<IdeaResponse, AppError> {
-        let idea = idea::Entity::find_by_id(payload.id)
-            .filter(idea::Column::InitiatorId.eq(initiator_id))
-            .one(&state.db)
+        payload.validate()?;
+
+        let txn = state.conn.begin().await?;
+
+        // Locked so that the status `check_editable` sees can't change before
+        // the edit is saved.
+        let idea = Idea::find_by_id(payload.id)
+            .lock_exclusive()
+            .one(&txn)
             .await?
-            .ok_or_else(|| AppError::NotFound("Idea not found or you don't have access".to_owned()))?;
+            .ok_or(AppError::NotFound)?;
+
+        Self::check_editable(state, &idea, claims).await?;
+
+        let mut idea = idea.into_active_model();
 
-        let mut idea: idea::ActiveModel = idea.into();
         idea.name = Set(payload.name);
         idea.group_expert_id = Set(payload.group_expert_id);
         idea.group_project_office_id = Set(payload.group_project_office_id);
@@ -175,116 +587,172 @@ impl IdeaService {
         idea.description = Set(payload.description);
         idea.suitability = Set(payload.suitability);
         idea.budget = Set(payload.budget);
-        idea.max_team_size = Set(payload.max_team_size);
-        idea.min_team_size = Set(payload.min_team_size);
+        idea.max_team_size = Set(Some(payload.max_team_size));
+        idea.min_team_size = Set(Some(payload.min_team_size));
+        idea.modified_at = Set(Local::now().into());
 
-        let idea = idea.update(&state.db).await?;
-        Self::idea_model_to_response(state, idea).await
+        let idea = idea.update(&txn).await?;
+        txn.commit().await?;
+
+        Self::model_to_response(state, idea, claims.sub).await
     }
 
-    pub async fn update_by_admin(
-        state: &AppState,
-        payload: UpdateIdeaRequest,
-    ) -> Result<IdeaResponse, AppError> {
-        let idea = idea::Entity::find_by_id(payload.id)
-            .one(&state.db)
+    pub async fn delete(state: &AppState, id: Uuid, claims: &Claims) -> Result<(), AppError> {
+        let idea = Idea::find_by_id(id)
+            .one(&state.conn)
             .await?
-            .ok_or_else(|| AppError::NotFound("Idea not found".to_owned()))?;
+            .ok_or(AppError::NotFound)?;
+
+        Authorizer::require_owned(
+            state,
+            claims,
+            idea.initiator_id,
+            Permission::IdeaDeleteOwn,
+            Permission::IdeaDeleteAny,
+        )
+        .await?;
+
+        Idea::delete_by_id(id).exec(&state.conn).await?;
+        Ok(())
+    }
 
-        let mut idea: idea::ActiveModel = idea.into();
-        idea.name = Set(payload.name);
-        idea.group_expert_id = Set(payload.group_expert_id);
-        idea.group_project_office_id = Set(payload.group_project_office_id);
-        idea.problem = Set(payload.problem);
-        idea.solution = Set(payload.solution);
-        idea.result = Set(payload.result);
-        idea.customer = Set(payload.customer);
-        idea.contact_person = Set(payload.contact_person);
-        idea.description = Set(payload.description);
-        idea.suitability = Set(payload.suitability);
-        idea.budget = Set(payload.budget);
-        idea.max_team_size = Set(payload.max_team_size);
-        idea.min_team_size = Set(payload.min_team_size);
+    /// Sends an idea to approval on behalf of its initiator.
+    pub async fn submit(state: &AppState, id: Uuid, claims: &Claims) -> Result<(), AppError> {
+        let txn = state.conn.begin().await?;
 
-        let idea = idea.update(&state.db).await?;
-        Self::idea_model_to_response(state, idea).await
-    }
+        // Locked for the same reason as in `update_status`: the history must
+        // record the status the idea actually had.
+        let idea = Idea::find_by_id(id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(AppError::NotFound)?;
 
-    pub async fn delete_by_initiator(
-        state: &AppState,
-        id: Uuid,
-        initiator_id: Uuid,
-    ) -> Result<(), AppError> {
-        let res = idea::Entity::delete_by_id(id)
-            .filter(idea::Column::InitiatorId.eq(initiator_id))
-            .exec(&state.db)
-            .await?;
+        Self::find_transition(&idea.status, &IdeaStatus::OnApproval)?;
 
-        if res.rows_affected == 0 {
-            return Err(AppError::NotFound(
-                "Idea not found or you don't have access".to_owned(),
-            ));
-        }
-        Ok(())
-    }
+        Authorizer::require_owned(
+            state,
+            claims,
+            idea.initiator_id,
+            Permission::IdeaSubmitOwn,
+            Permission::IdeaSubmitAny,
+        )
+        .await?;
+
+        Self::change_status(&txn, idea, IdeaStatus::OnApproval, Some(claims), None).await?;
+        txn.commit().await?;
 
-    pub async fn delete_by_admin(state: &AppState, id: Uuid) -> Result<(), AppError> {
-        let res = idea::Entity::delete_by_id(id).exec(&state.db).await?;
-        if res.rows_affected == 0 {
-            return Err(AppError::NotFound("Idea not found".to_owned()));
-        }
         Ok(())
     }
 
-    pub async fn update_status_by_initiator(
+    pub async fn update_status(
         state: &AppState,
         id: Uuid,
-        initiator_id: Uuid,
+        payload: UpdateIdeaStatusRequest,
+        claims: &Claims,
     ) -> Result<(), AppError> {
-        let idea = idea::Entity::find_by_id(id)
-            .filter(idea::Column::InitiatorId.eq(initiator_id))
-            .one(&state.db)
+        let txn = state.conn.begin().await?;
+
+        // The row lock makes concurrent status changes wait for each other, so
+        // the transition is always checked against the current status.
+        let idea = Idea::find_by_id(id)
+            .lock_exclusive()
+            .one(&txn)
             .await?
-            .ok_or_else(|| AppError::NotFound("Idea not found or you don't have access".to_owned()))?;
+            .ok_or(AppError::NotFound)?;
+
+        Self::check_transition(state, claims, &idea, &payload.status).await?;
+
+        Self::change_status(&txn, idea, payload.status, Some(claims), payload.comment).await?;
+        txn.commit().await?;
 
-        let mut idea: idea::ActiveModel = idea.into();
-        idea.status = Set(IdeaStatus::OnApproval);
-        idea.update(&state.db).await?;
         Ok(())
     }
 
-    pub async fn update_status(
+    /// Updates the idea status and appends the change to `idea_status_history`,
+    /// recording the actor and their active role.
+    /// Callers are expected to run it inside a transaction, with the idea row
+    /// locked since it was read.
+    pub async fn change_status<C: ConnectionTrait>(
+        db: &C,
+        idea: idea::Model,
+        new_status: IdeaStatus,
+        actor: Option<&Claims>,
+        comment: Option<String>,
+    ) -> Result<idea::Model, AppError> {
+        let history = idea_status_history::ActiveModel {
+            idea_id: Set(idea.id),
+            actor_id: Set(actor.map(|claims| claims.sub)),
+            actor_role: Set(actor.and_then(|claims| claims.active_role.clone())),
+            previous_status: Set(idea.status.clone()),
+            new_status: Set(new_status.clone()),
+            comment: Set(comment.filter(|comment| !comment.trim().is_empty())),
+            ..Default::default()
+        };
+
+        let mut idea = idea.into_active_model();
+
+        idea.status = Set(new_status);
+        idea.modified_at = Set(Local::now().into());
+
+        let idea = idea.update(db).await?;
+        history.insert(db).await?;
+
+        Ok(idea)
+    }
+
+    pub async fn get_status_history(
         state: &AppState,
-        id: Uuid,
-        status: UpdateIdeaStatusRequest,
-    ) -> Result<(), AppError> {
-        let idea = idea::Entity::find_by_id(id)
-            .one(&state.db)
+        idea_id: Uuid,
+        claims: &Claims,
+    ) -> Result<Vec<IdeaStatusHistoryDto>, AppError> {
+        let idea = Idea::find_by_id(idea_id)
+            .one(&state.conn)
             .await?
-            .ok_or_else(|| AppError::NotFound("Idea not found".to_owned()))?;
+            .ok_or(AppError::NotFound)?;
 
-        let mut idea: idea::ActiveModel = idea.into();
-        idea.status = Set(status.status);
-        idea.update(&state.db).await?;
-        Ok(())
+        Self::check_access(state, &idea, claims).await?;
+
+        let history = IdeaStatusHistory::find()
+            .join(
+                JoinType::LeftJoin,
+                idea_status_history::Relation::Actor.def(),
+            )
+            .filter(idea_status_history::Column::IdeaId.eq(idea_id))
+            .order_by_asc(idea_status_history::Column::CreatedAt)
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        Ok(history)
     }
 
     pub async fn get_idea_skills(
         state: &AppState,
         idea_id: Uuid,
+        claims: &Claims,
     ) -> Result<Vec<SkillDto>, AppError> {
-        let idea = idea::Entity::find_by_id(idea_id)
-            .one(&state.db)
+        let idea = Idea::find_by_id(idea_id)
+            .one(&state.conn)
             .await?
-            .ok_or_else(|| AppError::NotFound("Idea not found".to_owned()))?;
+            .ok_or(AppError::NotFound)?;
 
-        let skills = idea
-            .find_related(skill::Entity)
-            .all(&state.db)
-            .await?
-            .into_iter()
-            .map(|model| SkillDto::from(model))
-            .collect();
+        Self::check_access(state, &idea, claims).await?;
+
+        let skills = Skill::find()
+            .filter(
+                skill::Column::Id.in_subquery(
+                    sea_query::Query::select()
+                        .column(idea_skill::Column::SkillId)
+                        .from(idea_skill::Entity)
+                        .and_where(idea_skill::Column::IdeaId.eq(idea_id))
+                        .to_owned(),
+                ),
+            )
+            .filter(skill::Column::DeletedAt.is_null())
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
 
         Ok(skills)
     }
@@ -292,19 +760,25 @@ impl IdeaService {
     pub async fn update_idea_skills(
         state: &AppState,
         payload: IdeaSkillRequest,
-        user_id: Uuid,
-        is_admin: bool,
+        claims: &Claims,
     ) -> Result<(), AppError> {
-        let idea = idea::Entity::find_by_id(payload.idea_id)
-            .one(&state.db)
+        let txn = state.conn.begin().await?;
+
+        // Locked for the same reason as in `update`.
+        let idea = Idea::find_by_id(payload.idea_id)
+            .lock_exclusive()
+            .one(&txn)
             .await?
-            .ok_or_else(|| AppError::NotFound("Idea not found".to_owned()))?;
+            .ok_or(AppError::NotFound)?;
 
-        if idea.initiator_id != user_id && !is_admin {
-            return Err(AppError::Forbidden);
-        }
+        Self::check_editable(state, &idea, claims).await?;
+
+        IdeaSkill::delete_many()
+            .filter(idea_skill::Column::IdeaId.eq(payload.idea_id))
+            .exec(&txn)
+            .await?;
 
-        let skills_to_add: Vec<idea_skill::ActiveModel> = payload
+        let skills: Vec<idea_skill::ActiveModel> = payload
             .skills
             .iter()
             .map(|skill| idea_skill::ActiveModel {
@@ -313,23 +787,199 @@ impl IdeaService {
             })
             .collect();
 
-        state.db.transaction::<_, (), AppError>(|txn| {
-            Box::pin(async move {
-                idea_skill::Entity::delete_many()
-                    .filter(idea_skill::Column::IdeaId.eq(payload.idea_id))
-                    .exec(txn)
-                    .await?;
-
-                if !skills_to_add.is_empty() {
-                    idea_skill::Entity::insert_many(skills_to_add)
-                        .exec(txn)
-                        .await?;
-                }
+        if !skills.is_empty() {
+            IdeaSkill::insert_many(skills).exec(&txn).await?;
+        }
 
-                Ok(())
-            })
-        }).await?;
+        txn.commit().await?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::{TestUsers, claims_for, test_state, unique_email};
+    use entity::role::Role;
+
+    async fn insert_idea(
+        state: &AppState,
+        initiator_id: Uuid,
+        name: &str,
+        status: IdeaStatus,
+    ) -> idea::Model {
+        idea::ActiveModel {
+            initiator_id: Set(initiator_id),
+            name: Set(name.to_string()),
+            status: Set(status),
+            ..Default::default()
+        }
+        .insert(&state.conn)
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn status_transitions_follow_the_workflow() {
+        use IdeaStatus::*;
+
+        let allowed = [
+            (
+                New,
+                OnApproval,
+                Permission::IdeaSubmitAny,
+                Reviewers::Anyone,
+            ),
+            (
+                OnEditing,
+                OnApproval,
+                Permission::IdeaSubmitAny,
+                Reviewers::Anyone,
+            ),
+            (
+                OnApproval,
+                OnConfirmation,
+                Permission::IdeaApprove,
+                Reviewers::Experts,
+            ),
+            (
+                OnApproval,
+                OnEditing,
+                Permission::IdeaReturnApproval,
+                Reviewers::ExpertsOrProjectOffice,
+            ),
+            (
+                OnConfirmation,
+                Confirmed,
+                Permission::IdeaConfirm,
+                Reviewers::ProjectOffice,
+            ),
+            (
+                OnConfirmation,
+                OnEditing,
+                Permission::IdeaReturnConfirmation,
+                Reviewers::ProjectOffice,
+            ),
+            (
+                Confirmed,
+                OnMarket,
+                Permission::IdeaPublish,
+                Reviewers::ProjectOffice,
+            ),
+        ];
+        for (from, to, permission, reviewers) in &allowed {
+            assert_eq!(
+                IdeaService::find_transition(from, to).unwrap(),
+                (*permission, *reviewers)
+            );
+        }
+
+        let statuses = [
+            New,
+            OnEditing,
+            OnApproval,
+            OnConfirmation,
+            Confirmed,
+            OnMarket,
+        ];
+        for from in &statuses {
+            for to in &statuses {
+                if allowed.iter().any(|(f, t, _, _)| f == from && t == to) {
+                    continue;
+                }
+                assert!(matches!(
+                    IdeaService::find_transition(from, to),
+                    Err(AppError::InvalidStatusTransition { .. })
+                ));
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn only_reviewers_of_the_idea_change_its_status() {
+        let state = test_state().await;
+        let mut users = TestUsers::new(&state);
+        let initiator = users
+            .create(&unique_email("initiator"), vec![Role::Initiator])
+            .await;
+        let member = users
+            .create(&unique_email("expert"), vec![Role::Expert])
+            .await;
+        let outsider = users
+            .create(&unique_email("expert"), vec![Role::Expert])
+            .await;
+        let admin = users
+            .create(&unique_email("admin"), vec![Role::Admin])
+            .await;
+        let experts = users.create_group(vec![Role::Expert], &[member.id]).await;
+
+        let mut idea = insert_idea(&state, initiator.id, "review", IdeaStatus::OnApproval).await;
+        let mut active = idea.clone().into_active_model();
+        active.group_expert_id = Set(Some(experts.id));
+        idea = active.update(&state.conn).await.unwrap();
+
+        let approve = |user| {
+            let claims = claims_for(user);
+            let (state, idea) = (&state, &idea);
+            async move {
+                IdeaService::check_transition(state, &claims, idea, &IdeaStatus::OnConfirmation)
+                    .await
+            }
+        };
+
+        approve(&member).await.unwrap();
+        approve(&admin).await.unwrap();
+        for user in [&outsider, &initiator] {
+            assert!(matches!(approve(user).await, Err(AppError::Forbidden)));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn list_hides_drafts_of_other_initiators() {
+        let state = test_state().await;
+        let mut users = TestUsers::new(&state);
+        let viewer = users
+            .create(&unique_email("viewer"), vec![Role::Initiator])
+            .await;
+        let other = users
+            .create(&unique_email("initiator"), vec![Role::Initiator])
+            .await;
+
+        // Narrows the list down to the ideas of this run.
+        let marker = format!("visibility-{}", Uuid::new_v4());
+        let own = insert_idea(&state, viewer.id, &marker, IdeaStatus::New).await;
+        let draft = insert_idea(&state, other.id, &marker, IdeaStatus::New).await;
+        let published = insert_idea(&state, other.id, &marker, IdeaStatus::OnMarket).await;
+
+        let filters = IdeaFilterParams {
+            search: Some(marker),
+            ..Default::default()
+        };
+        let pagination = PaginationParams {
+            page: 0,
+            page_size: 100,
+        };
+        let page = IdeaService::get_all(&state, &claims_for(&viewer), filters, pagination)
+            .await
+            .unwrap();
+
+        let ids: HashSet<Uuid> = page.items.iter().map(|idea| idea.id).collect();
+        assert_eq!(page.total, 2);
+        assert!(ids.contains(&own.id));
+        assert!(ids.contains(&published.id));
+        assert!(!ids.contains(&draft.id));
+
+        // Opening an idea follows the same rule as the list.
+        let claims = claims_for(&viewer);
+        for idea in [&own, &published] {
+            IdeaService::check_access(&state, idea, &claims)
+                .await
+                .unwrap();
+        }
+        assert!(matches!(
+            IdeaService::check_access(&state, &draft, &claims).await,
+            Err(AppError::Forbidden)
+        ));
+    }
+}
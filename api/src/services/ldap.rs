@@ -0,0 +1,248 @@
+use crate::{
+    AppState,
+    config::{GLOBAL_CONFIG, LdapConfig},
+    error::AppError,
+    services::session::SessionService,
+    utils::security::{generate_password, hash_password},
+};
+use entity::{
+    role::Role,
+    users::{self, Entity as User},
+};
+use ldap3::{LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry, ldap_escape};
+use sea_orm::{ActiveModelTrait, ActiveValue::Set, IntoActiveModel};
+use std::time::Duration;
+
+const LDAP_TIMEOUT_SECONDS: u64 = 5;
+
+/// User attributes read from the directory after a successful bind.
+struct DirectoryUser {
+    first_name: Option<String>,
+    last_name: Option<String>,
+    study_group: Option<String>,
+    roles: Vec<Role>,
+}
+
+enum DirectoryBind {
+    Bound(DirectoryUser),
+    NotFound,
+    WrongPassword,
+}
+
+pub enum LdapLogin {
+    Authenticated(Box<users::Model>),
+    /// The login fails without checking the local password.
+    Rejected,
+    /// Not a directory account: the local password is checked.
+    Local,
+}
+
+pub struct LdapService;
+
+impl LdapService {
+    /// Checks the password by binding to the directory and syncs the local
+    /// user. A wrong password for a directory user is final. The local password
+    /// is only checked for accounts that never came from the directory, when the
+    /// user is not in the directory or it is unreachable. The admin from
+    /// `ADMIN_USERNAME` always logs in with the local password.
+    pub async fn login(
+        state: &AppState,
+        email: &str,
+        password: &str,
+    ) -> Result<LdapLogin, AppError> {
+        let Some(config) = GLOBAL_CONFIG.ldap.as_ref() else {
+            return Ok(LdapLogin::Local);
+        };
+
+        if email.eq_ignore_ascii_case(&GLOBAL_CONFIG.admin_username) {
+            return Ok(LdapLogin::Local);
+        }
+
+        match Self::authenticate(config, email, password).await {
+            Ok(DirectoryBind::Bound(directory_user)) => {
+                let user = Self::sync_user(state, config, email, directory_user).await?;
+                Ok(user.map_or(LdapLogin::Rejected, |user| {
+                    LdapLogin::Authenticated(Box::new(user))
+                }))
+            }
+            Ok(DirectoryBind::WrongPassword) => Ok(LdapLogin::Rejected),
+            Ok(DirectoryBind::NotFound) => Self::local_login(state, email).await,
+            Err(e) => {
+                tracing::error!("LDAP source error: {:?}", e);
+                Self::local_login(state, email).await
+            }
+        }
+    }
+
+    /// A user disabled in or removed from the directory must not keep logging in
+    /// with a local password left from before.
+    async fn local_login(state: &AppState, email: &str) -> Result<LdapLogin, AppError> {
+        let is_directory_user = User::find_by_email(email.to_string())
+            .one(&state.conn)
+            .await?
+            .is_some_and(|user| user.is_directory_user);
+
+        if is_directory_user {
+            Ok(LdapLogin::Rejected)
+        } else {
+            Ok(LdapLogin::Local)
+        }
+    }
+
+    async fn authenticate(
+        config: &LdapConfig,
+        email: &str,
+        password: &str,
+    ) -> Result<DirectoryBind, LdapError> {
+        // Servers treat a bind with an empty password as anonymous and accept it.
+        if password.is_empty() {
+            return Ok(DirectoryBind::WrongPassword);
+        }
+
+        let settings =
+            LdapConnSettings::new().set_conn_timeout(Duration::from_secs(LDAP_TIMEOUT_SECONDS));
+        let (conn, mut ldap) = LdapConnAsync::with_settings(settings, &config.url).await?;
+        ldap3::drive!(conn);
+
+        if let (Some(bind_dn), Some(bind_password)) = (&config.bind_dn, &config.bind_password) {
+            ldap.simple_bind(bind_dn, bind_password).await?.success()?;
+        }
+
+        let filter = config.user_filter.replace("{email}", &ldap_escape(email));
+        let attributes = [
+            config.first_name_attr.as_str(),
+            config.last_name_attr.as_str(),
+            config.study_group_attr.as_str(),
+            config.group_attr.as_str(),
+        ];
+
+        let (mut entries, _) = ldap
+            .search(&config.base_dn, Scope::Subtree, &filter, attributes)
+            .await?
+            .success()?;
+
+        if entries.len() != 1 {
+            ldap.unbind().await?;
+            return Ok(DirectoryBind::NotFound);
+        }
+
+        let entry = SearchEntry::construct(entries.remove(0));
+
+        let is_bound = ldap.simple_bind(&entry.dn, password).await?.rc == 0;
+        ldap.unbind().await?;
+
+        if !is_bound {
+            return Ok(DirectoryBind::WrongPassword);
+        }
+
+        let attribute = |name: &str| {
+            entry
+                .attrs
+                .get(name)
+                .and_then(|values| values.first())
+                .filter(|value| !value.is_empty())
+                .cloned()
+        };
+
+        let groups = entry
+            .attrs
+            .get(&config.group_attr)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut roles: Vec<Role> = Vec::new();
+        for (group, role) in &config.group_roles {
+            if groups.iter().any(|dn| dn.eq_ignore_ascii_case(group)) && !roles.contains(role) {
+                roles.push(role.clone());
+            }
+        }
+
+        Ok(DirectoryBind::Bound(DirectoryUser {
+            first_name: attribute(&config.first_name_attr),
+            last_name: attribute(&config.last_name_attr),
+            study_group: attribute(&config.study_group_attr),
+            roles,
+        }))
+    }
+
+    /// Copies the name, surname and study group from the directory into `users`
+    /// and marks the account as a directory one. Roles are replaced with the
+    /// directory group roles if `LDAP_GROUP_ROLES` is set.
+    async fn sync_user(
+        state: &AppState,
+        config: &LdapConfig,
+        email: &str,
+        directory_user: DirectoryUser,
+    ) -> Result<Option<users::Model>, AppError> {
+        let sync_roles = !config.group_roles.is_empty();
+
+        let user = User::find_by_email(email.to_string())
+            .one(&state.conn)
+            .await?;
+
+        let Some(user) = user else {
+            let user = users::ActiveModel {
+                email: Set(email.to_string()),
+                password: Set(hash_password(&generate_password())?),
+                first_name: Set(directory_user.first_name.unwrap_or_default()),
+                last_name: Set(directory_user.last_name.unwrap_or_default()),
+                study_group: Set(directory_user.study_group),
+                roles: Set(directory_user.roles),
+                is_directory_user: Set(true),
+                ..Default::default()
+            }
+            .insert(&state.conn)
+            .await?;
+
+            return Ok(Some(user));
+        };
+
+        if user.is_deleted {
+            return Ok(None);
+        }
+
+        let roles_changed = sync_roles
+            && (user.roles.len() != directory_user.roles.len()
+                || directory_user
+                    .roles
+                    .iter()
+                    .any(|role| !user.roles.contains(role)));
+        let user_id = user.id;
+
+        let mut active_user = user.clone().into_active_model();
+
+        if let Some(first_name) = directory_user
+            .first_name
+            .filter(|first_name| *first_name != user.first_name)
+        {
+            active_user.first_name = Set(first_name);
+        }
+        if let Some(last_name) = directory_user
+            .last_name
+            .filter(|last_name| *last_name != user.last_name)
+        {
+            active_user.last_name = Set(last_name);
+        }
+        if directory_user.study_group.is_some() && directory_user.study_group != user.study_group {
+            active_user.study_group = Set(directory_user.study_group);
+        }
+        if roles_changed {
+            active_user.roles = Set(directory_user.roles);
+        }
+        if !user.is_directory_user {
+            active_user.is_directory_user = Set(true);
+        }
+
+        if !active_user.is_changed() {
+            return Ok(Some(user));
+        }
+
+        let user = active_user.update(&state.conn).await?;
+
+        if roles_changed {
+            SessionService::invalidate_tokens(state, user_id).await?;
+        }
+
+        Ok(Some(user))
+    }
+}
@@ -0,0 +1,551 @@
+use crate::{
+    AppState,
+    dtos::market::{
+        ApplyToIdeaRequest, CreateMarketRequest, MarketDto, MarketIdeaDto, MarketIdeaRequestDto,
+        PublishIdeaRequest, UpdateMarketRequest,
+    },
+    error::AppError,
+    services::{authorizer::Authorizer, idea::IdeaService, team::TeamService},
+    utils::security::Claims,
+};
+use chrono::Local;
+use entity::{
+    idea,
+    idea_status::IdeaStatus,
+    market, market_idea, market_idea_request,
+    market_idea_status::MarketIdeaStatus,
+    market_request_status::MarketRequestStatus,
+    market_status::MarketStatus,
+    permission::Permission,
+    prelude::{Idea, Market, MarketIdea, MarketIdeaRequest, Team, TeamMember},
+    team_member,
+};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, IntoActiveModel,
+    JoinType, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+    TransactionTrait, prelude::Uuid, sea_query::Expr,
+};
+use validator::Validate;
+
+/// A market moves forward only: `New` → `Active` → `Done`, and `New` may be
+/// finished without ever opening.
+const STATUS_TRANSITIONS: &[(MarketStatus, MarketStatus)] = &[
+    (MarketStatus::New, MarketStatus::Active),
+    (MarketStatus::New, MarketStatus::Done),
+    (MarketStatus::Active, MarketStatus::Done),
+];
+
+pub struct MarketService;
+
+impl MarketService {
+    pub async fn get_all(state: &AppState) -> Result<Vec<MarketDto>, AppError> {
+        let markets = Market::find()
+            .order_by_desc(market::Column::StartDate)
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        Ok(markets)
+    }
+
+    pub async fn get_one(state: &AppState, id: Uuid) -> Result<MarketDto, AppError> {
+        Market::find_by_id(id)
+            .into_partial_model()
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    pub async fn create(
+        state: &AppState,
+        payload: CreateMarketRequest,
+    ) -> Result<MarketDto, AppError> {
+        payload.validate()?;
+
+        if payload.finish_date < payload.start_date {
+            return Err(AppError::Custom(
+                "Дата окончания биржи не может быть раньше даты начала".to_string(),
+            ));
+        }
+
+        let market = market::ActiveModel {
+            name: Set(payload.name),
+            start_date: Set(payload.start_date),
+            finish_date: Set(payload.finish_date),
+            status: Set(MarketStatus::New),
+            ..Default::default()
+        }
+        .insert(&state.conn)
+        .await?;
+
+        Self::get_one(state, market.id).await
+    }
+
+    pub async fn update(
+        state: &AppState,
+        payload: UpdateMarketRequest,
+    ) -> Result<MarketDto, AppError> {
+        let txn = state.conn.begin().await?;
+
+        let market = Market::find_by_id(payload.id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let start_date = payload.start_date.unwrap_or(market.start_date);
+        let finish_date = payload.finish_date.unwrap_or(market.finish_date);
+
+        if finish_date < start_date {
+            return Err(AppError::Custom(
+                "Дата окончания биржи не может быть раньше даты начала".to_string(),
+            ));
+        }
+
+        if let Some(status) = &payload.status {
+            Self::check_status_transition(&market.status, status)?;
+        }
+
+        let mut market = market.into_active_model();
+
+        if let Some(name) = payload.name
+            && !name.is_empty()
+        {
+            market.name = Set(name);
+        }
+
+        market.start_date = Set(start_date);
+        market.finish_date = Set(finish_date);
+
+        if let Some(status) = payload.status {
+            market.status = Set(status);
+        }
+
+        let market = market.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Self::get_one(state, market.id).await
+    }
+
+    fn check_status_transition(from: &MarketStatus, to: &MarketStatus) -> Result<(), AppError> {
+        if from == to || STATUS_TRANSITIONS.contains(&(from.clone(), to.clone())) {
+            return Ok(());
+        }
+
+        Err(AppError::Conflict(format!(
+            "Нельзя перевести биржу из статуса {:?} в статус {:?}",
+            from, to
+        )))
+    }
+
+    pub async fn get_ideas(
+        state: &AppState,
+        market_id: Uuid,
+    ) -> Result<Vec<MarketIdeaDto>, AppError> {
+        Self::find_market(&state.conn, market_id).await?;
+
+        let ideas = MarketIdea::find()
+            .join(JoinType::InnerJoin, market_idea::Relation::Idea.def())
+            .filter(market_idea::Column::MarketId.eq(market_id))
+            .order_by_asc(market_idea::Column::CreatedAt)
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        Ok(ideas)
+    }
+
+    /// Puts a `Confirmed` idea on the market and moves it to `OnMarket`.
+    pub async fn publish_idea(
+        state: &AppState,
+        market_id: Uuid,
+        payload: PublishIdeaRequest,
+        claims: &Claims,
+    ) -> Result<MarketIdeaDto, AppError> {
+        let txn = state.conn.begin().await?;
+
+        let market = Self::lock_market(&txn, market_id).await?;
+
+        if market.status == MarketStatus::Done {
+            return Err(AppError::Conflict("Биржа уже завершена".to_string()));
+        }
+
+        let idea = Idea::find_by_id(payload.idea_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        IdeaService::check_transition(state, claims, &idea, &IdeaStatus::OnMarket).await?;
+
+        let market_idea = market_idea::ActiveModel {
+            market_id: Set(market_id),
+            idea_id: Set(idea.id),
+            status: Set(MarketIdeaStatus::RecruitmentOpen),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        IdeaService::change_status(&txn, idea, IdeaStatus::OnMarket, Some(claims), None).await?;
+
+        txn.commit().await?;
+
+        Self::get_market_idea(state, market_idea.id).await
+    }
+
+    /// Submits a team's request for an idea. Only the owner or a leader of the
+    /// team may apply, and the team size has to fit the idea's limits.
+    pub async fn apply(
+        state: &AppState,
+        market_idea_id: Uuid,
+        payload: ApplyToIdeaRequest,
+        claims: &Claims,
+    ) -> Result<MarketIdeaRequestDto, AppError> {
+        payload.validate()?;
+
+        let txn = state.conn.begin().await?;
+
+        let market_idea = Self::find_open_market_idea(&txn, market_idea_id).await?;
+
+        let market = Self::lock_market(&txn, market_idea.market_id).await?;
+
+        if market.status != MarketStatus::Active {
+            return Err(AppError::Conflict(
+                "Подавать заявки можно только на активной бирже".to_string(),
+            ));
+        }
+
+        let team = Team::find_by_id(payload.team_id)
+            .one(&txn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        TeamService::check_manager(state, &team, claims).await?;
+
+        let idea = Self::find_idea(&txn, market_idea.idea_id).await?;
+
+        Self::check_team_size(&txn, &idea, team.id).await?;
+
+        let is_pending = MarketIdeaRequest::find()
+            .filter(market_idea_request::Column::MarketIdeaId.eq(market_idea_id))
+            .filter(market_idea_request::Column::TeamId.eq(team.id))
+            .filter(market_idea_request::Column::Status.eq(MarketRequestStatus::New))
+            .one(&txn)
+            .await?
+            .is_some();
+
+        if is_pending {
+            return Err(AppError::Conflict(
+                "Команда уже подала заявку на эту идею".to_string(),
+            ));
+        }
+
+        let request = market_idea_request::ActiveModel {
+            market_idea_id: Set(market_idea_id),
+            team_id: Set(team.id),
+            letter: Set(payload.letter),
+            status: Set(MarketRequestStatus::New),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Self::get_request(state, request.id).await
+    }
+
+    /// Requests for an idea are visible to its initiator, the project office and admins.
+    pub async fn get_idea_requests(
+        state: &AppState,
+        market_idea_id: Uuid,
+        claims: &Claims,
+    ) -> Result<Vec<MarketIdeaRequestDto>, AppError> {
+        let market_idea = MarketIdea::find_by_id(market_idea_id)
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let idea = Self::find_idea(&state.conn, market_idea.idea_id).await?;
+
+        if idea.initiator_id != claims.sub {
+            Authorizer::require(state, claims, Permission::MarketRequestReadAny).await?;
+        }
+
+        let requests = MarketIdeaRequest::find()
+            .join(
+                JoinType::InnerJoin,
+                market_idea_request::Relation::Team.def(),
+            )
+            .filter(market_idea_request::Column::MarketIdeaId.eq(market_idea_id))
+            .order_by_asc(market_idea_request::Column::CreatedAt)
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        Ok(requests)
+    }
+
+    pub async fn get_team_requests(
+        state: &AppState,
+        team_id: Uuid,
+        claims: &Claims,
+    ) -> Result<Vec<MarketIdeaRequestDto>, AppError> {
+        TeamService::check_access(state, team_id, claims).await?;
+
+        let requests = MarketIdeaRequest::find()
+            .join(
+                JoinType::InnerJoin,
+                market_idea_request::Relation::Team.def(),
+            )
+            .filter(market_idea_request::Column::TeamId.eq(team_id))
+            .order_by_desc(market_idea_request::Column::CreatedAt)
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        Ok(requests)
+    }
+
+    /// Accepts the request, declines every other pending request for the same
+    /// idea and closes recruitment for it.
+    pub async fn accept_request(
+        state: &AppState,
+        request_id: Uuid,
+        claims: &Claims,
+    ) -> Result<MarketIdeaDto, AppError> {
+        let txn = state.conn.begin().await?;
+
+        let (request, market_idea) =
+            Self::find_request_for_initiator(state, &txn, request_id, claims).await?;
+
+        let idea = Self::find_idea(&txn, market_idea.idea_id).await?;
+
+        Self::check_team_size(&txn, &idea, request.team_id).await?;
+
+        let now = Local::now();
+
+        // Both updates are guarded by the status read above: if a concurrent
+        // accept or decline got there first, no rows match and nothing is committed.
+        let closed = MarketIdea::update_many()
+            .col_expr(market_idea::Column::TeamId, Expr::value(request.team_id))
+            .col_expr(
+                market_idea::Column::Status,
+                Expr::value(MarketIdeaStatus::RecruitmentClosed),
+            )
+            .filter(market_idea::Column::Id.eq(market_idea.id))
+            .filter(market_idea::Column::Status.eq(MarketIdeaStatus::RecruitmentOpen))
+            .exec(&txn)
+            .await?;
+
+        if closed.rows_affected == 0 {
+            return Err(AppError::Conflict(
+                "Набор команды на идею уже закрыт".to_string(),
+            ));
+        }
+
+        Self::respond(&txn, request.id, MarketRequestStatus::Accepted).await?;
+
+        MarketIdeaRequest::update_many()
+            .col_expr(
+                market_idea_request::Column::Status,
+                Expr::value(MarketRequestStatus::Declined),
+            )
+            .col_expr(
+                market_idea_request::Column::RespondedAt,
+                Expr::value(now.fixed_offset()),
+            )
+            .filter(market_idea_request::Column::MarketIdeaId.eq(market_idea.id))
+            .filter(market_idea_request::Column::Status.eq(MarketRequestStatus::New))
+            .filter(market_idea_request::Column::Id.ne(request.id))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Self::get_market_idea(state, market_idea.id).await
+    }
+
+    pub async fn decline_request(
+        state: &AppState,
+        request_id: Uuid,
+        claims: &Claims,
+    ) -> Result<(), AppError> {
+        let (request, _) =
+            Self::find_request_for_initiator(state, &state.conn, request_id, claims).await?;
+
+        Self::respond(&state.conn, request.id, MarketRequestStatus::Declined).await
+    }
+
+    /// Sets the response status of a request that is still `New`.
+    async fn respond<C: ConnectionTrait>(
+        db: &C,
+        request_id: Uuid,
+        status: MarketRequestStatus,
+    ) -> Result<(), AppError> {
+        let result = MarketIdeaRequest::update_many()
+            .col_expr(market_idea_request::Column::Status, Expr::value(status))
+            .col_expr(
+                market_idea_request::Column::RespondedAt,
+                Expr::value(Local::now().fixed_offset()),
+            )
+            .filter(market_idea_request::Column::Id.eq(request_id))
+            .filter(market_idea_request::Column::Status.eq(MarketRequestStatus::New))
+            .exec(db)
+            .await?;
+
+        if result.rows_affected == 0 {
+            return Err(AppError::Conflict("Заявка уже обработана".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn get_market_idea(state: &AppState, id: Uuid) -> Result<MarketIdeaDto, AppError> {
+        MarketIdea::find_by_id(id)
+            .join(JoinType::InnerJoin, market_idea::Relation::Idea.def())
+            .into_partial_model()
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    async fn get_request(state: &AppState, id: Uuid) -> Result<MarketIdeaRequestDto, AppError> {
+        MarketIdeaRequest::find_by_id(id)
+            .join(
+                JoinType::InnerJoin,
+                market_idea_request::Relation::Team.def(),
+            )
+            .into_partial_model()
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    async fn find_market<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<market::Model, AppError> {
+        Market::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    /// Keeps a share lock on the market until the transaction ends, so it can't
+    /// be finished while an idea or a request is being added to it.
+    async fn lock_market<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<market::Model, AppError> {
+        Market::find_by_id(id)
+            .lock_shared()
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    async fn find_idea<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<idea::Model, AppError> {
+        Idea::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    /// The row stays locked until the transaction ends: requests for the idea
+    /// are added and answered one at a time.
+    async fn find_open_market_idea<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+    ) -> Result<market_idea::Model, AppError> {
+        let market_idea = MarketIdea::find_by_id(id)
+            .lock_exclusive()
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        if market_idea.status != MarketIdeaStatus::RecruitmentOpen {
+            return Err(AppError::Conflict(
+                "Набор команды на идею уже закрыт".to_string(),
+            ));
+        }
+
+        Ok(market_idea)
+    }
+
+    /// Loads a pending request together with its market idea and checks that the
+    /// caller is the idea's initiator or holds `market_request:manage:any`.
+    async fn find_request_for_initiator<C: ConnectionTrait>(
+        state: &AppState,
+        db: &C,
+        request_id: Uuid,
+        claims: &Claims,
+    ) -> Result<(market_idea_request::Model, market_idea::Model), AppError> {
+        let request = MarketIdeaRequest::find_by_id(request_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        if request.status != MarketRequestStatus::New {
+            return Err(AppError::Conflict("Заявка уже обработана".to_string()));
+        }
+
+        let market_idea = Self::find_open_market_idea(db, request.market_idea_id).await?;
+
+        let idea = Self::find_idea(db, market_idea.idea_id).await?;
+
+        if idea.initiator_id != claims.sub {
+            Authorizer::require(state, claims, Permission::MarketRequestManageAny).await?;
+        }
+
+        Ok((request, market_idea))
+    }
+
+    async fn check_team_size<C: ConnectionTrait>(
+        db: &C,
+        idea: &idea::Model,
+        team_id: Uuid,
+    ) -> Result<(), AppError> {
+        let size = TeamMember::find()
+            .filter(team_member::Column::TeamId.eq(team_id))
+            .count(db)
+            .await? as i64;
+
+        if let Some(min) = idea.min_team_size
+            && size < i64::from(min)
+        {
+            return Err(AppError::Conflict(format!(
+                "В команде должно быть не меньше {} участников",
+                min
+            )));
+        }
+
+        if let Some(max) = idea.max_team_size
+            && size > i64::from(max)
+        {
+            return Err(AppError::Conflict(format!(
+                "В команде должно быть не больше {} участников",
+                max
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markets_only_move_forward() {
+        use MarketStatus::*;
+
+        for (from, to) in [(New, Active), (New, Done), (Active, Done), (Active, Active)] {
+            assert!(MarketService::check_status_transition(&from, &to).is_ok());
+        }
+
+        for (from, to) in [(Active, New), (Done, Active), (Done, New)] {
+            assert!(matches!(
+                MarketService::check_status_transition(&from, &to),
+                Err(AppError::Conflict(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{
+    AppState,
+    dtos::{
+        matching::{IdeaMatchResponse, SkillTypeCoverage, TeamMatchDto, UserMatchDto},
+        profile::UserDto,
+        skill::SkillDto,
+        team::TeamShortDto,
+    },
+    error::AppError,
+    services::idea::IdeaService,
+    utils::security::Claims,
+};
+use entity::{
+    idea_skill,
+    prelude::{Idea, Skill, Team, TeamMember, UserSkill, Users},
+    skill,
+    skill_type::SkillType,
+    team, team_member, user_skill, users,
+};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QuerySelect, prelude::Uuid, sea_query};
+use std::collections::HashMap;
+
+/// How many users and teams are returned for an idea.
+const MATCH_LIMIT: usize = 20;
+
+/// Matched skills of a candidate with the best proficiency level for each of them.
+type SkillLevels = HashMap<Uuid, i16>;
+
+pub struct MatchingService;
+
+impl MatchingService {
+    /// Ranks users and teams by how many of the idea's skills they cover.
+    /// Candidates with equal coverage are ordered by the sum of their levels
+    /// in the matched skills. A team covers every skill any of its members has.
+    pub async fn match_idea(
+        state: &AppState,
+        idea_id: Uuid,
+        claims: &Claims,
+    ) -> Result<IdeaMatchResponse, AppError> {
+        let idea = Idea::find_by_id(idea_id)
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        IdeaService::check_access(state, &idea, claims).await?;
+
+        let required_skills: Vec<SkillDto> = Skill::find()
+            .filter(
+                skill::Column::Id.in_subquery(
+                    sea_query::Query::select()
+                        .column(idea_skill::Column::SkillId)
+                        .from(idea_skill::Entity)
+                        .and_where(idea_skill::Column::IdeaId.eq(idea_id))
+                        .to_owned(),
+                ),
+            )
+            .filter(skill::Column::DeletedAt.is_null())
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        if required_skills.is_empty() {
+            return Ok(IdeaMatchResponse {
+                required_skills,
+                users: Vec::new(),
+                teams: Vec::new(),
+            });
+        }
+
+        let required_ids: Vec<Uuid> = required_skills.iter().map(|skill| skill.id).collect();
+
+        let user_skills: Vec<(Uuid, Uuid, i16)> = UserSkill::find()
+            .select_only()
+            .column(user_skill::Column::UserId)
+            .column(user_skill::Column::SkillId)
+            .column(user_skill::Column::Level)
+            .filter(user_skill::Column::SkillId.is_in(required_ids))
+            .filter(
+                user_skill::Column::UserId.in_subquery(
+                    sea_query::Query::select()
+                        .column(users::Column::Id)
+                        .from(users::Entity)
+                        .and_where(users::Column::IsDeleted.eq(false))
+                        .to_owned(),
+                ),
+            )
+            .into_tuple()
+            .all(&state.conn)
+            .await?;
+
+        let mut by_user: HashMap<Uuid, SkillLevels> = HashMap::new();
+        for (user_id, skill_id, level) in user_skills {
+            by_user.entry(user_id).or_default().insert(skill_id, level);
+        }
+
+        let memberships: Vec<(Uuid, Uuid)> = TeamMember::find()
+            .select_only()
+            .column(team_member::Column::TeamId)
+            .column(team_member::Column::UserId)
+            .filter(team_member::Column::UserId.is_in(by_user.keys().copied()))
+            .into_tuple()
+            .all(&state.conn)
+            .await?;
+
+        let mut by_team: HashMap<Uuid, SkillLevels> = HashMap::new();
+        for (team_id, user_id) in memberships {
+            let team_skills = by_team.entry(team_id).or_default();
+            for (skill_id, level) in &by_user[&user_id] {
+                let best = team_skills.entry(*skill_id).or_default();
+                *best = (*best).max(*level);
+            }
+        }
+
+        let top_users = Self::rank(&by_user);
+        let top_teams = Self::rank(&by_team);
+
+        let mut user_dtos: HashMap<Uuid, UserDto> = Users::find()
+            .filter(users::Column::Id.is_in(top_users.iter().copied()))
+            .into_partial_model::<UserDto>()
+            .all(&state.conn)
+            .await?
+            .into_iter()
+            .map(|user| (user.id, user))
+            .collect();
+
+        let mut team_dtos: HashMap<Uuid, TeamShortDto> = Team::find()
+            .filter(team::Column::Id.is_in(top_teams.iter().copied()))
+            .into_partial_model::<TeamShortDto>()
+            .all(&state.conn)
+            .await?
+            .into_iter()
+            .map(|team| (team.id, team))
+            .collect();
+
+        let users = top_users
+            .into_iter()
+            .filter_map(|user_id| {
+                let user = user_dtos.remove(&user_id)?;
+                let (matched_skills, coverage, percentage) =
+                    Self::coverage(&required_skills, &by_user[&user_id]);
+
+                Some(UserMatchDto {
+                    user,
+                    matched_skills,
+                    coverage,
+                    percentage,
+                })
+            })
+            .collect();
+
+        let teams = top_teams
+            .into_iter()
+            .filter_map(|team_id| {
+                let team = team_dtos.remove(&team_id)?;
+                let (matched_skills, coverage, percentage) =
+                    Self::coverage(&required_skills, &by_team[&team_id]);
+
+                Some(TeamMatchDto {
+                    team,
+                    matched_skills,
+                    coverage,
+                    percentage,
+                })
+            })
+            .collect();
+
+        Ok(IdeaMatchResponse {
+            required_skills,
+            users,
+            teams,
+        })
+    }
+
+    fn rank(candidates: &HashMap<Uuid, SkillLevels>) -> Vec<Uuid> {
+        let mut ranked: Vec<(Uuid, usize, i64)> = candidates
+            .iter()
+            .map(|(id, skills)| {
+                let levels = skills.values().map(|level| i64::from(*level)).sum();
+                (*id, skills.len(), levels)
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0)));
+
+        ranked
+            .into_iter()
+            .take(MATCH_LIMIT)
+            .map(|(id, _, _)| id)
+            .collect()
+    }
+
+    fn coverage(
+        required: &[SkillDto],
+        matched: &SkillLevels,
+    ) -> (Vec<Uuid>, Vec<SkillTypeCoverage>, f64) {
+        let mut types: Vec<SkillType> = Vec::new();
+        for skill in required {
+            if !types.contains(&skill.skill_type) {
+                types.push(skill.skill_type.clone());
+            }
+        }
+
+        let coverage = types
+            .into_iter()
+            .map(|skill_type| {
+                let of_type: Vec<&SkillDto> = required
+                    .iter()
+                    .filter(|skill| skill.skill_type == skill_type)
+                    .collect();
+                let matched_count = of_type
+                    .iter()
+                    .filter(|skill| matched.contains_key(&skill.id))
+                    .count();
+
+                SkillTypeCoverage {
+                    skill_type,
+                    required: of_type.len(),
+                    matched: matched_count,
+                    percentage: Self::percentage(matched_count, of_type.len()),
+                }
+            })
+            .collect();
+
+        let matched_skills: Vec<Uuid> = required
+            .iter()
+            .map(|skill| skill.id)
+            .filter(|id| matched.contains_key(id))
+            .collect();
+
+        let percentage = Self::percentage(matched_skills.len(), required.len());
+
+        (matched_skills, coverage, percentage)
+    }
+
+    fn percentage(matched: usize, required: usize) -> f64 {
+        (matched as f64 / required as f64 * 1000.0).round() / 10.0
+    }
+}
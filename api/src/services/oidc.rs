@@ -0,0 +1,714 @@
+use crate::{
+    AppState,
+    config::{GLOBAL_CONFIG, OidcConfig},
+    dtos::auth::OidcCallbackParams,
+    error::AppError,
+    utils::security::hash_password,
+};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use aws_lc_rs::digest::{SHA256, digest};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::Local;
+use entity::{
+    invitation::{self, Entity as Invitation},
+    users::{self, Entity as User},
+};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header, jwk::JwkSet};
+use redis::AsyncCommands;
+use reqwest::{Client, Url};
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait, EntityTrait, ExprTrait, IntoActiveModel, QueryFilter, TransactionTrait,
+    sea_query::{Expr, Func},
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::LazyLock};
+use tokio::sync::OnceCell;
+
+const OIDC_STATE_PREFIX: &str = "oidc_state";
+const OIDC_STATE_TTL_SECONDS: u64 = 10 * 60;
+const OIDC_SCOPES: &str = "openid email profile";
+
+static HTTP_CLIENT: LazyLock<Client> = LazyLock::new(Client::new);
+
+/// The provider's discovery document is fetched once per process lifetime.
+static PROVIDER: OnceCell<ProviderMetadata> = OnceCell::const_new();
+
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    #[serde(default)]
+    id_token_signing_alg_values_supported: Vec<String>,
+}
+
+/// Data kept in Redis between the redirect to the provider and the callback.
+#[derive(Serialize, Deserialize)]
+struct AuthorizationRequest {
+    nonce: String,
+    code_verifier: String,
+}
+
+#[derive(Deserialize)]
+struct TokenEndpointResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    email: Option<String>,
+    email_verified: Option<bool>,
+    nonce: Option<String>,
+    given_name: Option<String>,
+    family_name: Option<String>,
+}
+
+fn config() -> Result<&'static OidcConfig, AppError> {
+    GLOBAL_CONFIG.oidc.as_ref().ok_or(AppError::NotFound)
+}
+
+fn random_string() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// PKCE `S256` challenge (RFC 7636, section 4.2).
+fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(digest(&SHA256, code_verifier.as_bytes()))
+}
+
+/// Only a verified email identifies a user, and the administrator only signs
+/// in with the local password.
+fn login_email(claims: &IdTokenClaims, admin_username: &str) -> Result<String, AppError> {
+    let email = claims
+        .email
+        .as_deref()
+        .filter(|_| claims.email_verified == Some(true))
+        .ok_or(AppError::WrongCredentials)?
+        .to_lowercase();
+
+    if email.eq_ignore_ascii_case(admin_username) {
+        return Err(AppError::WrongCredentials);
+    }
+
+    Ok(email)
+}
+
+async fn provider(config: &OidcConfig) -> Result<&'static ProviderMetadata, AppError> {
+    PROVIDER
+        .get_or_try_init(|| async {
+            let metadata = HTTP_CLIENT
+                .get(format!(
+                    "{}/.well-known/openid-configuration",
+                    config.issuer_url
+                ))
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<ProviderMetadata>()
+                .await?;
+
+            Ok::<_, AppError>(metadata)
+        })
+        .await
+}
+
+pub struct OidcService;
+
+impl OidcService {
+    /// Starts a provider login: stores `state`, `nonce` and the PKCE
+    /// `code_verifier` and returns the authorization page URL.
+    pub async fn authorization_url(state: &AppState) -> Result<String, AppError> {
+        let config = config()?;
+        let provider = provider(config).await?;
+
+        Self::start_login(state, config, provider).await
+    }
+
+    /// Exchanges the authorization code for an `id_token`, verifies it and finds
+    /// the user with the same email. If there is no such user yet, accepts an
+    /// active invitation for that email.
+    pub async fn callback(
+        state: &AppState,
+        params: OidcCallbackParams,
+    ) -> Result<users::Model, AppError> {
+        let config = config()?;
+        let provider = provider(config).await?;
+
+        Self::finish_login(state, config, provider, params).await
+    }
+
+    async fn start_login(
+        state: &AppState,
+        config: &OidcConfig,
+        provider: &ProviderMetadata,
+    ) -> Result<String, AppError> {
+        let csrf_state = random_string();
+        let request = AuthorizationRequest {
+            nonce: random_string(),
+            code_verifier: random_string(),
+        };
+        let code_challenge = code_challenge(&request.code_verifier);
+
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let _: () = redis_con
+            .set_ex(
+                format!("{}:{}", OIDC_STATE_PREFIX, csrf_state),
+                serde_json::to_string(&request).map_err(|_| AppError::InternalServerError)?,
+                OIDC_STATE_TTL_SECONDS,
+            )
+            .await?;
+
+        let url = Url::parse_with_params(
+            &provider.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", config.client_id.as_str()),
+                ("redirect_uri", config.redirect_url.as_str()),
+                ("scope", OIDC_SCOPES),
+                ("state", csrf_state.as_str()),
+                ("nonce", request.nonce.as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|_| AppError::InternalServerError)?;
+
+        Ok(url.into())
+    }
+
+    async fn finish_login(
+        state: &AppState,
+        config: &OidcConfig,
+        provider: &ProviderMetadata,
+        params: OidcCallbackParams,
+    ) -> Result<users::Model, AppError> {
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let request: Option<String> = redis_con
+            .get_del(format!("{}:{}", OIDC_STATE_PREFIX, params.state))
+            .await?;
+
+        let request: AuthorizationRequest = request
+            .and_then(|request| serde_json::from_str(&request).ok())
+            .ok_or(AppError::InvalidToken)?;
+
+        if let Some(error) = params.error {
+            tracing::warn!("OIDC provider returned an error: {}", error);
+            return Err(AppError::WrongCredentials);
+        }
+
+        let code = params.code.ok_or(AppError::BadRequest)?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("redirect_uri", config.redirect_url.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("code_verifier", request.code_verifier.as_str()),
+        ];
+        if let Some(client_secret) = &config.client_secret {
+            form.push(("client_secret", client_secret.as_str()));
+        }
+
+        let response = HTTP_CLIENT
+            .post(&provider.token_endpoint)
+            .form(&form)
+            .send()
+            .await?;
+
+        if response.status().is_client_error() {
+            return Err(AppError::WrongCredentials);
+        }
+
+        let response = response
+            .error_for_status()?
+            .json::<TokenEndpointResponse>()
+            .await?;
+
+        let claims = Self::verify_id_token(config, provider, &response.id_token).await?;
+
+        if claims.nonce.as_deref() != Some(request.nonce.as_str()) {
+            return Err(AppError::InvalidToken);
+        }
+
+        let user = Self::find_user(state, config, claims).await?;
+
+        Ok(user)
+    }
+
+    async fn verify_id_token(
+        config: &OidcConfig,
+        provider: &ProviderMetadata,
+        id_token: &str,
+    ) -> Result<IdTokenClaims, AppError> {
+        let jwks = HTTP_CLIENT
+            .get(&provider.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await?;
+
+        Self::decode_id_token(config, provider, &jwks, id_token)
+    }
+
+    fn decode_id_token(
+        config: &OidcConfig,
+        provider: &ProviderMetadata,
+        jwks: &JwkSet,
+        id_token: &str,
+    ) -> Result<IdTokenClaims, AppError> {
+        let header = decode_header(id_token).map_err(|_| AppError::InvalidToken)?;
+
+        let jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            None => jwks.keys.first(),
+        }
+        .ok_or(AppError::InvalidToken)?;
+
+        // The header is controlled by whoever issued the token, so the accepted
+        // algorithms come from the key itself or, if it doesn't declare one, from
+        // the provider's discovery document.
+        let algorithms: Vec<Algorithm> = match jwk.common.key_algorithm {
+            Some(alg) => {
+                vec![Algorithm::from_str(&alg.to_string()).map_err(|_| AppError::InvalidToken)?]
+            }
+            None => provider
+                .id_token_signing_alg_values_supported
+                .iter()
+                .filter_map(|alg| Algorithm::from_str(alg).ok())
+                .collect(),
+        };
+
+        if !algorithms.contains(&header.alg) {
+            return Err(AppError::InvalidToken);
+        }
+
+        let decoding_key = DecodingKey::from_jwk(jwk).map_err(|_| AppError::InvalidToken)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.algorithms = algorithms;
+        validation.set_issuer(&[&provider.issuer]);
+        validation.set_audience(&[&config.client_id]);
+
+        let token_data = decode::<IdTokenClaims>(id_token, &decoding_key, &validation)
+            .map_err(|_| AppError::InvalidToken)?;
+
+        Ok(token_data.claims)
+    }
+
+    async fn find_user(
+        state: &AppState,
+        config: &OidcConfig,
+        claims: IdTokenClaims,
+    ) -> Result<users::Model, AppError> {
+        let email = login_email(&claims, &GLOBAL_CONFIG.admin_username)?;
+
+        if let Some(user) = User::find_by_email(email.clone()).one(&state.conn).await? {
+            if user.is_deleted {
+                return Err(AppError::WrongCredentials);
+            }
+
+            tracing::info!("OIDC login: {} -> {}", claims.sub, user.id);
+            return Ok(user);
+        }
+
+        if !config.auto_accept_invitations {
+            return Err(AppError::WrongCredentials);
+        }
+
+        let txn = state.conn.begin().await?;
+
+        let invitation = Invitation::find()
+            .filter(Expr::expr(Func::lower(Expr::col(invitation::Column::Email))).eq(&email))
+            .filter(invitation::Column::ExpiryDate.gt(Local::now()))
+            .one(&txn)
+            .await?
+            .ok_or(AppError::WrongCredentials)?;
+
+        let user = users::ActiveModel {
+            email: Set(email),
+            password: Set(hash_password(&random_string())?),
+            roles: Set(invitation.roles.clone()),
+            first_name: Set(claims.given_name.unwrap_or_default()),
+            last_name: Set(claims.family_name.unwrap_or_default()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        let mut invitation = invitation.into_active_model();
+        invitation.expiry_date = Set(Local::now().into());
+        invitation.update(&txn).await?;
+
+        txn.commit().await?;
+
+        tracing::info!(
+            "OIDC login: {} -> {}, invitation accepted",
+            claims.sub,
+            user.id
+        );
+
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{
+        startup::create_admin,
+        testing::{TestUsers, test_state, unique_email},
+    };
+    use aws_lc_rs::signature::{ECDSA_P256_SHA256_FIXED_SIGNING, EcdsaKeyPair, KeyPair};
+    use axum::{Form, Json, Router, extract::State, http::StatusCode, routing::post};
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use serde_json::{Value, json};
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    const KID: &str = "test-key";
+    const CLIENT_ID: &str = "hits";
+
+    /// A P-256 key pair: the private half signs test tokens, the public half is
+    /// served as a JWK.
+    fn signing_key(alg: Option<&str>) -> (EncodingKey, JwkSet) {
+        let pair = EcdsaKeyPair::generate(&ECDSA_P256_SHA256_FIXED_SIGNING).unwrap();
+        // Uncompressed point: 0x04 || x || y.
+        let point = pair.public_key().as_ref();
+
+        let mut jwk = json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "kid": KID,
+            "use": "sig",
+            "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+            "y": URL_SAFE_NO_PAD.encode(&point[33..65]),
+        });
+        if let Some(alg) = alg {
+            jwk["alg"] = json!(alg);
+        }
+
+        let encoding_key = EncodingKey::from_ec_der(pair.to_pkcs8v1().unwrap().as_ref());
+        let jwks = serde_json::from_value(json!({ "keys": [jwk] })).unwrap();
+
+        (encoding_key, jwks)
+    }
+
+    fn test_config(issuer: &str) -> (OidcConfig, ProviderMetadata) {
+        let config = OidcConfig {
+            issuer_url: issuer.to_string(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: None,
+            redirect_url: "http://localhost/api/auth/oidc/callback".to_string(),
+            auto_accept_invitations: false,
+        };
+        let provider = ProviderMetadata {
+            issuer: issuer.to_string(),
+            authorization_endpoint: format!("{}/authorize", issuer),
+            token_endpoint: format!("{}/token", issuer),
+            jwks_uri: format!("{}/jwks", issuer),
+            id_token_signing_alg_values_supported: vec!["ES256".to_string()],
+        };
+
+        (config, provider)
+    }
+
+    fn id_token_claims(issuer: &str, email: &str, email_verified: bool, nonce: &str) -> Value {
+        json!({
+            "iss": issuer,
+            "aud": CLIENT_ID,
+            "exp": Local::now().timestamp() + 300,
+            "sub": "provider-subject",
+            "email": email,
+            "email_verified": email_verified,
+            "nonce": nonce,
+            "given_name": "Иван",
+            "family_name": "Иванов",
+        })
+    }
+
+    fn sign(alg: Algorithm, key: &EncodingKey, claims: &Value) -> String {
+        let mut header = Header::new(alg);
+        header.kid = Some(KID.to_string());
+        encode(&header, claims, key).unwrap()
+    }
+
+    fn claims_with(email: Option<&str>, email_verified: Option<bool>) -> IdTokenClaims {
+        IdTokenClaims {
+            sub: "provider-subject".to_string(),
+            email: email.map(str::to_string),
+            email_verified,
+            nonce: None,
+            given_name: None,
+            family_name: None,
+        }
+    }
+
+    #[test]
+    fn code_challenge_matches_rfc_7636() {
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn login_email_requires_verified_email() {
+        let admin = "admin@example.com";
+
+        assert_eq!(
+            login_email(&claims_with(Some("User@Example.com"), Some(true)), admin).unwrap(),
+            "user@example.com"
+        );
+        for claims in [
+            claims_with(Some("user@example.com"), Some(false)),
+            claims_with(Some("user@example.com"), None),
+            claims_with(None, Some(true)),
+        ] {
+            assert!(matches!(
+                login_email(&claims, admin),
+                Err(AppError::WrongCredentials)
+            ));
+        }
+    }
+
+    #[test]
+    fn login_email_excludes_admin() {
+        assert!(matches!(
+            login_email(
+                &claims_with(Some("Admin@Example.com"), Some(true)),
+                "admin@example.com"
+            ),
+            Err(AppError::WrongCredentials)
+        ));
+    }
+
+    #[test]
+    fn id_token_algorithm_is_pinned_by_the_key() {
+        let issuer = "https://id.example.com";
+        let (config, provider) = test_config(issuer);
+        let (key, jwks) = signing_key(Some("ES256"));
+        let claims = id_token_claims(issuer, "user@example.com", true, "nonce");
+
+        let token = sign(Algorithm::ES256, &key, &claims);
+        let decoded = OidcService::decode_id_token(&config, &provider, &jwks, &token).unwrap();
+        assert_eq!(decoded.email.as_deref(), Some("user@example.com"));
+
+        // The header names an algorithm the key isn't meant for.
+        let forged = sign(
+            Algorithm::HS256,
+            &EncodingKey::from_secret(b"secret"),
+            &claims,
+        );
+        assert!(matches!(
+            OidcService::decode_id_token(&config, &provider, &jwks, &forged),
+            Err(AppError::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn id_token_algorithm_falls_back_to_discovery() {
+        let issuer = "https://id.example.com";
+        let (config, mut provider) = test_config(issuer);
+        let (key, jwks) = signing_key(None);
+        let token = sign(
+            Algorithm::ES256,
+            &key,
+            &id_token_claims(issuer, "user@example.com", true, "nonce"),
+        );
+
+        assert!(OidcService::decode_id_token(&config, &provider, &jwks, &token).is_ok());
+
+        provider.id_token_signing_alg_values_supported = vec!["RS256".to_string()];
+        assert!(matches!(
+            OidcService::decode_id_token(&config, &provider, &jwks, &token),
+            Err(AppError::InvalidToken)
+        ));
+
+        provider.id_token_signing_alg_values_supported.clear();
+        assert!(matches!(
+            OidcService::decode_id_token(&config, &provider, &jwks, &token),
+            Err(AppError::InvalidToken)
+        ));
+    }
+
+    /// What the mock provider expects at the token endpoint for the pending login.
+    struct Grant {
+        code_challenge: String,
+        nonce: String,
+        email: String,
+        email_verified: bool,
+    }
+
+    #[derive(Clone)]
+    struct MockProvider {
+        issuer: String,
+        key: EncodingKey,
+        jwks: Arc<JwkSet>,
+        grant: Arc<Mutex<Option<Grant>>>,
+    }
+
+    async fn token_endpoint(
+        State(mock): State<MockProvider>,
+        Form(form): Form<HashMap<String, String>>,
+    ) -> Result<Json<Value>, StatusCode> {
+        let grant = mock
+            .grant
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        let code_verifier = form.get("code_verifier").ok_or(StatusCode::BAD_REQUEST)?;
+
+        if form.get("code").map(String::as_str) != Some("code")
+            || code_challenge(code_verifier) != grant.code_challenge
+        {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let claims = id_token_claims(
+            &mock.issuer,
+            &grant.email,
+            grant.email_verified,
+            &grant.nonce,
+        );
+
+        Ok(Json(json!({
+            "id_token": sign(Algorithm::ES256, &mock.key, &claims),
+            "token_type": "Bearer",
+        })))
+    }
+
+    async fn jwks_endpoint(State(mock): State<MockProvider>) -> Json<JwkSet> {
+        Json(mock.jwks.as_ref().clone())
+    }
+
+    async fn start_mock_provider() -> MockProvider {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let (key, jwks) = signing_key(Some("ES256"));
+        let mock = MockProvider {
+            issuer: format!("http://{}", listener.local_addr().unwrap()),
+            key,
+            jwks: Arc::new(jwks),
+            grant: Arc::new(Mutex::new(None)),
+        };
+
+        let app = Router::new()
+            .route("/token", post(token_endpoint))
+            .route("/jwks", axum::routing::get(jwks_endpoint))
+            .with_state(mock.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        mock
+    }
+
+    /// Starts a login and returns the query parameters of the authorization URL.
+    async fn start_login(
+        state: &AppState,
+        config: &OidcConfig,
+        provider: &ProviderMetadata,
+    ) -> HashMap<String, String> {
+        let url = OidcService::start_login(state, config, provider)
+            .await
+            .unwrap();
+        let url = Url::parse(&url).unwrap();
+        assert_eq!(url.path(), "/authorize");
+
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert_eq!(params["code_challenge_method"], "S256");
+        assert_eq!(params["client_id"], CLIENT_ID);
+        params
+    }
+
+    fn callback_params(csrf_state: &str) -> OidcCallbackParams {
+        OidcCallbackParams {
+            code: Some("code".to_string()),
+            state: csrf_state.to_string(),
+            error: None,
+        }
+    }
+
+    /// Plays the provider's part of a login for `email` and returns the result
+    /// of the callback together with the `state` it used.
+    async fn login(
+        state: &AppState,
+        mock: &MockProvider,
+        email: &str,
+        email_verified: bool,
+    ) -> (String, Result<users::Model, AppError>) {
+        let (config, provider) = test_config(&mock.issuer);
+        let params = start_login(state, &config, &provider).await;
+
+        *mock.grant.lock().unwrap() = Some(Grant {
+            code_challenge: params["code_challenge"].clone(),
+            nonce: params["nonce"].clone(),
+            email: email.to_string(),
+            email_verified,
+        });
+
+        let callback = callback_params(&params["state"]);
+        let result = OidcService::finish_login(state, &config, &provider, callback).await;
+
+        (params["state"].clone(), result)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "needs PostgreSQL and Redis; reads the server environment (.env)"]
+    async fn login_round_trip() {
+        let state = test_state().await;
+        let mock = start_mock_provider().await;
+        let (config, provider) = test_config(&mock.issuer);
+
+        let mut users = TestUsers::new(&state);
+        let email = unique_email("oidc");
+        let user = users.create(&email, vec![]).await;
+
+        let (csrf_state, result) = login(&state, &mock, &email.to_uppercase(), true).await;
+        assert_eq!(result.unwrap().id, user.id);
+
+        // `state` is single-use.
+        let replay =
+            OidcService::finish_login(&state, &config, &provider, callback_params(&csrf_state))
+                .await;
+        assert!(matches!(replay, Err(AppError::InvalidToken)));
+
+        // The token endpoint only accepts the verifier behind the challenge
+        // that was sent to the authorization page.
+        let params = start_login(&state, &config, &provider).await;
+        *mock.grant.lock().unwrap() = Some(Grant {
+            code_challenge: code_challenge("another verifier"),
+            nonce: params["nonce"].clone(),
+            email: email.clone(),
+            email_verified: true,
+        });
+        let callback = callback_params(&params["state"]);
+        assert!(matches!(
+            OidcService::finish_login(&state, &config, &provider, callback).await,
+            Err(AppError::WrongCredentials)
+        ));
+
+        let (_, unverified) = login(&state, &mock, &email, false).await;
+        assert!(matches!(unverified, Err(AppError::WrongCredentials)));
+
+        // The administrator's account exists, but a provider login can't reach it.
+        create_admin(state.conn.clone()).await.unwrap();
+        let (_, admin) = login(&state, &mock, &GLOBAL_CONFIG.admin_username, true).await;
+        assert!(matches!(admin, Err(AppError::WrongCredentials)));
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     config::GLOBAL_CONFIG,
     dtos::{
         auth::{EmailResetPayload, PasswordResetPayload},
-        profile::ProfileUpdatePayload,
+        profile::{ProfileUpdatePayload, UpdateUserSkillsRequest, UserSkillDto},
     },
     error::AppError,
     utils::{
@@ -15,6 +15,8 @@ use argon2::password_hash::rand_core::{OsRng, RngCore};
 use axum::body::Bytes;
 use chrono::{Duration, Local};
 use entity::{
+    skill::{self, Entity as Skill},
+    user_skill::{self, Entity as UserSkill},
     users::Entity as User,
     verification_code::{self, Entity as VerificationCode},
 };
@@ -22,12 +24,15 @@ use image::ImageFormat;
 use sea_orm::{
     ActiveModelTrait,
     ActiveValue::Set,
-    ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, TransactionTrait,
+    ColumnTrait, EntityTrait, ExprTrait, IntoActiveModel, JoinType, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, RelationTrait, TransactionTrait,
     prelude::{Expr, Uuid},
 };
 use std::path::PathBuf;
 use validator::Validate;
 
+const MAX_CODE_ATTEMPTS: i16 = 3;
+
 pub struct ProfileService;
 
 impl ProfileService {
@@ -63,6 +68,76 @@ impl ProfileService {
 
         Ok(())
     }
+    pub async fn get_skills(
+        state: &AppState,
+        user_id: Uuid,
+    ) -> Result<Vec<UserSkillDto>, AppError> {
+        let skills = UserSkill::find()
+            .join(JoinType::InnerJoin, user_skill::Relation::Skill.def())
+            .filter(user_skill::Column::UserId.eq(user_id))
+            .filter(skill::Column::DeletedAt.is_null())
+            .order_by_desc(user_skill::Column::Level)
+            .order_by_asc(skill::Column::Name)
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        Ok(skills)
+    }
+
+    /// Replaces the user's skills with the given list.
+    pub async fn update_skills(
+        state: &AppState,
+        payload: UpdateUserSkillsRequest,
+        user_id: Uuid,
+    ) -> Result<Vec<UserSkillDto>, AppError> {
+        payload.validate()?;
+
+        let mut skill_ids: Vec<Uuid> = payload.skills.iter().map(|s| s.skill_id).collect();
+        skill_ids.sort();
+        skill_ids.dedup();
+
+        if skill_ids.len() != payload.skills.len() {
+            return Err(AppError::Custom(
+                "Навыки в списке не должны повторяться".to_string(),
+            ));
+        }
+
+        let existing = Skill::find()
+            .filter(skill::Column::Id.is_in(skill_ids.clone()))
+            .filter(skill::Column::DeletedAt.is_null())
+            .count(&state.conn)
+            .await?;
+
+        if existing != skill_ids.len() as u64 {
+            return Err(AppError::NotFound);
+        }
+
+        let txn = state.conn.begin().await?;
+
+        UserSkill::delete_many()
+            .filter(user_skill::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        let skills: Vec<user_skill::ActiveModel> = payload
+            .skills
+            .into_iter()
+            .map(|skill| user_skill::ActiveModel {
+                user_id: Set(user_id),
+                skill_id: Set(skill.skill_id),
+                level: Set(skill.level),
+            })
+            .collect();
+
+        if !skills.is_empty() {
+            UserSkill::insert_many(skills).exec(&txn).await?;
+        }
+
+        txn.commit().await?;
+
+        Self::get_skills(state, user_id).await
+    }
     pub async fn confirm_email_change(
         state: &AppState,
         claims: Claims,
@@ -80,7 +155,7 @@ impl ProfileService {
             .one(&state.conn)
             .await?;
 
-        if let Some(_) = user {
+        if user.is_some() {
             return Err(AppError::Custom(
                 "Пользователь с такой почтой уже существует!".to_string(),
             ));
@@ -188,7 +263,17 @@ impl ProfileService {
         if Local::now() > verification_code.expiry_date {
             return Err(AppError::Custom("Время запроса истекло".to_string()));
         }
-        if verification_code.wrong_tries >= 3 {
+        let reserved = VerificationCode::update_many()
+            .col_expr(
+                verification_code::Column::WrongTries,
+                Expr::col(verification_code::Column::WrongTries).add(1),
+            )
+            .filter(verification_code::Column::Id.eq(verification_code.id))
+            .filter(verification_code::Column::WrongTries.lt(MAX_CODE_ATTEMPTS))
+            .exec(&state.conn)
+            .await?;
+
+        if reserved.rows_affected == 0 {
             return Err(AppError::Custom(
                 "Превышено максимальное количество попыток".to_string(),
             ));
@@ -226,13 +311,6 @@ impl ProfileService {
 
             txn.commit().await?;
         } else {
-            let wrong_tries = verification_code.wrong_tries + 1;
-            let mut verification_code = verification_code.into_active_model();
-
-            verification_code.wrong_tries = Set(wrong_tries);
-
-            verification_code.update(&state.conn).await?;
-
             return Err(AppError::Custom("Ошибка, попробуйте еще раз".to_string()));
         }
         Ok(())
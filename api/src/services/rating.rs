@@ -0,0 +1,221 @@
+use crate::{
+    AppState,
+    dtos::rating::{RatingDto, RatingRequest},
+    error::AppError,
+    services::idea::IdeaService,
+    utils::security::Claims,
+};
+use chrono::Local;
+use entity::{
+    group_member, idea,
+    idea_status::IdeaStatus,
+    prelude::{GroupMember, Idea, Rating},
+    rating,
+};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, IntoActiveModel,
+    JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait, TransactionTrait, prelude::Uuid,
+};
+use validator::Validate;
+
+pub struct RatingService;
+
+impl RatingService {
+    pub async fn get_idea_ratings(
+        state: &AppState,
+        idea_id: Uuid,
+    ) -> Result<Vec<RatingDto>, AppError> {
+        let ratings = Rating::find()
+            .join(JoinType::InnerJoin, rating::Relation::Expert.def())
+            .filter(rating::Column::IdeaId.eq(idea_id))
+            .order_by_asc(rating::Column::CreatedAt)
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        Ok(ratings)
+    }
+
+    pub async fn get_my_rating(
+        state: &AppState,
+        idea_id: Uuid,
+        expert_id: Uuid,
+    ) -> Result<RatingDto, AppError> {
+        Rating::find()
+            .join(JoinType::InnerJoin, rating::Relation::Expert.def())
+            .filter(rating::Column::IdeaId.eq(idea_id))
+            .filter(rating::Column::ExpertId.eq(expert_id))
+            .into_partial_model()
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    pub async fn save(
+        state: &AppState,
+        payload: RatingRequest,
+        expert_id: Uuid,
+    ) -> Result<RatingDto, AppError> {
+        payload.validate()?;
+
+        let txn = state.conn.begin().await?;
+
+        Self::find_idea_for_expert(&txn, payload.idea_id, expert_id).await?;
+        Self::upsert(&txn, &payload, expert_id, false).await?;
+
+        txn.commit().await?;
+
+        Self::get_my_rating(state, payload.idea_id, expert_id).await
+    }
+
+    /// Confirms the expert's rating. Every confirmation refreshes `idea.pre_assessment`,
+    /// and once the whole expert group has confirmed, the aggregate is written to
+    /// `idea.rating` and the idea moves to `OnConfirmation`.
+    pub async fn confirm(
+        state: &AppState,
+        payload: RatingRequest,
+        claims: &Claims,
+    ) -> Result<RatingDto, AppError> {
+        payload.validate()?;
+
+        let expert_id = claims.sub;
+
+        let txn = state.conn.begin().await?;
+
+        let idea = Self::find_idea_for_expert(&txn, payload.idea_id, expert_id).await?;
+        Self::upsert(&txn, &payload, expert_id, true).await?;
+
+        let experts: Vec<Uuid> = GroupMember::find()
+            .select_only()
+            .column(group_member::Column::UserId)
+            .filter(group_member::Column::GroupId.eq(idea.group_expert_id))
+            .into_tuple()
+            .all(&txn)
+            .await?;
+
+        let confirmed: Vec<f64> = Rating::find()
+            .select_only()
+            .column(rating::Column::Rating)
+            .filter(rating::Column::IdeaId.eq(idea.id))
+            .filter(rating::Column::IsConfirmed.eq(true))
+            .filter(rating::Column::ExpertId.is_in(experts.clone()))
+            .into_tuple::<Option<f64>>()
+            .all(&txn)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let aggregate = confirmed.iter().sum::<f64>() / confirmed.len() as f64;
+        let is_complete = confirmed.len() >= experts.len();
+
+        let mut idea = idea.into_active_model();
+
+        idea.pre_assessment = Set(Some(aggregate));
+        if is_complete {
+            idea.rating = Set(Some(aggregate));
+        }
+
+        let idea = idea.update(&txn).await?;
+
+        if is_complete {
+            IdeaService::change_status(&txn, idea, IdeaStatus::OnConfirmation, Some(claims), None)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Self::get_my_rating(state, payload.idea_id, expert_id).await
+    }
+
+    async fn find_idea_for_expert<C: ConnectionTrait>(
+        db: &C,
+        idea_id: Uuid,
+        expert_id: Uuid,
+    ) -> Result<idea::Model, AppError> {
+        // The row lock serializes ratings of the same idea: otherwise the last two
+        // experts confirming at once would not see each other's rating and the
+        // idea would never leave `OnApproval`.
+        let idea = Idea::find_by_id(idea_id)
+            .lock_exclusive()
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        if idea.status != IdeaStatus::OnApproval {
+            return Err(AppError::Conflict(
+                "Оценивать можно только идеи в статусе ON_APPROVAL".to_string(),
+            ));
+        }
+
+        let group_id = idea.group_expert_id.ok_or(AppError::Conflict(
+            "Для идеи не назначена группа экспертов".to_string(),
+        ))?;
+
+        GroupMember::find_by_id((group_id, expert_id))
+            .one(db)
+            .await?
+            .ok_or(AppError::Forbidden)?;
+
+        Ok(idea)
+    }
+
+    async fn upsert<C: ConnectionTrait>(
+        db: &C,
+        payload: &RatingRequest,
+        expert_id: Uuid,
+        confirm: bool,
+    ) -> Result<(), AppError> {
+        let existing = Rating::find()
+            .filter(rating::Column::IdeaId.eq(payload.idea_id))
+            .filter(rating::Column::ExpertId.eq(expert_id))
+            .one(db)
+            .await?;
+
+        if existing.as_ref().is_some_and(|rating| rating.is_confirmed) {
+            return Err(AppError::Conflict("Оценка уже подтверждена".to_string()));
+        }
+
+        let criteria = [
+            payload.market_value,
+            payload.originality,
+            payload.technical_realizability,
+            payload.suitability,
+            payload.budget,
+        ];
+
+        let value = criteria
+            .iter()
+            .copied()
+            .collect::<Option<Vec<i16>>>()
+            .map(|values| values.iter().map(|v| f64::from(*v)).sum::<f64>() / values.len() as f64);
+
+        if confirm && value.is_none() {
+            return Err(AppError::Custom(
+                "Для подтверждения необходимо заполнить все критерии".to_string(),
+            ));
+        }
+
+        let mut rating = match existing {
+            Some(rating) => rating.into_active_model(),
+            None => rating::ActiveModel {
+                idea_id: Set(payload.idea_id),
+                expert_id: Set(expert_id),
+                ..Default::default()
+            },
+        };
+
+        rating.market_value = Set(payload.market_value);
+        rating.originality = Set(payload.originality);
+        rating.technical_realizability = Set(payload.technical_realizability);
+        rating.suitability = Set(payload.suitability);
+        rating.budget = Set(payload.budget);
+        rating.rating = Set(value);
+        rating.is_confirmed = Set(confirm);
+        rating.updated_at = Set(Local::now().into());
+
+        rating.save(db).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{
+    AppState,
+    dtos::search::{IdeaSearchHit, SearchParams, SearchResponse, SkillSearchHit, UserSearchHit},
+    error::AppError,
+    services::authorizer::Authorizer,
+    utils::security::Claims,
+};
+use entity::permission::Permission;
+use sea_orm::{DbBackend, FromQueryResult, Statement};
+use validator::Validate;
+
+const DEFAULT_LIMIT: u64 = 10;
+
+/// Both dictionaries are queried so that Russian and English words are
+/// stemmed the same way they were when the `search_vector` columns were built.
+const TS_QUERY: &str =
+    "(websearch_to_tsquery('russian', $1) || websearch_to_tsquery('english', $1))";
+
+/// `ts_headline` marks hits with control characters that don't occur in the
+/// indexed text; they become `<mark>` tags once the headline is HTML-escaped.
+const MARK_START: char = '\u{2}';
+const MARK_END: char = '\u{3}';
+const HEADLINE_OPTIONS: &str = "StartSel=\u{2}, StopSel=\u{3}, MaxFragments=2, MaxWords=20";
+
+/// Only holders of `user:manage:any` see and search by email and study group;
+/// everyone else finds users by name through `users.name_search_vector`.
+const USER_NAME_TEXT: &str = "u.first_name || ' ' || u.last_name";
+const USER_FULL_TEXT: &str =
+    "u.first_name || ' ' || u.last_name || ' ' || u.email || ' ' || coalesce(u.study_group, '')";
+
+/// Clients render headlines as HTML, so the source text is escaped and the
+/// only markup left is `<mark>`. Escaping happens after `ts_headline`, so a
+/// hit can't end up inside an entity such as `&lt;`.
+fn highlight(headline: &str) -> String {
+    let mut result = String::with_capacity(headline.len());
+    for c in headline.chars() {
+        match c {
+            MARK_START => result.push_str("<mark>"),
+            MARK_END => result.push_str("</mark>"),
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+pub struct SearchService;
+
+impl SearchService {
+    /// Searches ideas, users and skills. Ideas are limited to the ones the caller
+    /// can work with: holders of `idea:read:any` see everything, everyone else
+    /// sees their own ideas, ideas on the market and ideas assigned to their
+    /// expert or project office groups, as in `IdeaService::visible_to`.
+    pub async fn search(
+        state: &AppState,
+        params: SearchParams,
+        claims: &Claims,
+    ) -> Result<SearchResponse, AppError> {
+        params.validate()?;
+
+        let query = params.q.trim().to_string();
+        let limit = params.limit.unwrap_or(DEFAULT_LIMIT) as i64;
+        let can_read_ideas = Authorizer::can(state, claims, Permission::IdeaReadAny).await?;
+        let can_manage_users = Authorizer::can(state, claims, Permission::UserManageAny).await?;
+        let can_manage_skills = Authorizer::can(state, claims, Permission::SkillManageAny).await?;
+
+        let idea_text = "coalesce(i.name, '') || ' ' || coalesce(i.problem, '') || ' ' || \
+             coalesce(i.solution, '') || ' ' || coalesce(i.description, '')";
+
+        let (user_text, user_vector, user_contacts) = if can_manage_users {
+            (USER_FULL_TEXT, "u.search_vector", "u.email, u.study_group")
+        } else {
+            (
+                USER_NAME_TEXT,
+                "u.name_search_vector",
+                "NULL::text AS email, NULL::text AS study_group",
+            )
+        };
+
+        let mut ideas = IdeaSearchHit::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"
+                SELECT i.id, i.name, i.status,
+                    ts_rank(i.search_vector, {TS_QUERY}) AS rank,
+                    ts_headline('russian', {idea_text}, {TS_QUERY}, '{HEADLINE_OPTIONS}') AS headline
+                FROM idea i
+                WHERE i.search_vector @@ {TS_QUERY}
+                    AND ($2 OR i.initiator_id = $3 OR i.status = 'ON_MARKET'
+                        OR i.group_expert_id IN (SELECT group_id FROM group_member WHERE user_id = $3)
+                        OR i.group_project_office_id IN (SELECT group_id FROM group_member WHERE user_id = $3))
+                ORDER BY rank DESC
+                LIMIT $4
+                "#
+            ),
+            [
+                query.clone().into(),
+                can_read_ideas.into(),
+                claims.sub.into(),
+                limit.into(),
+            ],
+        ))
+        .all(&state.conn)
+        .await?;
+
+        let mut users = UserSearchHit::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"
+                SELECT u.id, u.first_name, u.last_name, {user_contacts},
+                    ts_rank({user_vector}, {TS_QUERY}) AS rank,
+                    ts_headline('russian', {user_text}, {TS_QUERY}, '{HEADLINE_OPTIONS}') AS headline
+                FROM users u
+                WHERE {user_vector} @@ {TS_QUERY}
+                    AND ($2 OR NOT u.is_deleted)
+                ORDER BY rank DESC
+                LIMIT $3
+                "#
+            ),
+            [query.clone().into(), can_manage_users.into(), limit.into()],
+        ))
+        .all(&state.conn)
+        .await?;
+
+        let mut skills = SkillSearchHit::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"
+                SELECT s.id, s.name, s.skill_type,
+                    ts_rank(s.search_vector, {TS_QUERY}) AS rank,
+                    ts_headline('russian', s.name, {TS_QUERY}, '{HEADLINE_OPTIONS}') AS headline
+                FROM skill s
+                WHERE s.search_vector @@ {TS_QUERY}
+                    AND s.deleted_at IS NULL
+                    AND ($2 OR s.confirmed OR s.creator_id = $3)
+                ORDER BY rank DESC
+                LIMIT $4
+                "#
+            ),
+            [
+                query.into(),
+                can_manage_skills.into(),
+                claims.sub.into(),
+                limit.into(),
+            ],
+        ))
+        .all(&state.conn)
+        .await?;
+
+        for hit in &mut ideas {
+            hit.headline = highlight(&hit.headline);
+        }
+        for hit in &mut users {
+            hit.headline = highlight(&hit.headline);
+        }
+        for hit in &mut skills {
+            hit.headline = highlight(&hit.headline);
+        }
+
+        Ok(SearchResponse {
+            ideas,
+            users,
+            skills,
+        })
+    }
+}
@@ -0,0 +1,453 @@
+use crate::{
+    AppState,
+    dtos::session::SessionDto,
+    error::AppError,
+    services::two_factor::TwoFactorService,
+    utils::security::{
+        Claims, ClientInfo, REFRESH_TOKEN_TTL_DAYS, TokenPair, TokenType, decode_token,
+        generate_tokens,
+    },
+};
+use chrono::{DateTime, Local};
+use entity::{
+    role::Role,
+    users::{self, Entity as User},
+};
+use redis::{AsyncCommands, Script};
+use sea_orm::{
+    ColumnTrait, EntityTrait, ExprTrait, QueryFilter, QuerySelect,
+    prelude::{Expr, Uuid},
+};
+use std::{cmp::Reverse, collections::HashMap, sync::LazyLock};
+
+const SESSION_PREFIX: &str = "session";
+const USER_SESSIONS_PREFIX: &str = "user_sessions";
+const TOKEN_VERSION_PREFIX: &str = "token_version";
+const SESSION_TTL_SECONDS: i64 = REFRESH_TOKEN_TTL_DAYS * 24 * 60 * 60;
+const TOKEN_VERSION_TTL_SECONDS: u64 = 24 * 60 * 60;
+
+/// Atomically replaces the session's current `jti` with a new one.
+/// Returns `1` on successful rotation, `-1` when an old token is reused
+/// (the session is deleted) and `0` if the session has already been revoked.
+static ROTATE_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+        local current = redis.call('HGET', KEYS[1], 'jti')
+        if not current then
+            return 0
+        end
+        if current ~= ARGV[1] then
+            redis.call('DEL', KEYS[1])
+            redis.call('SREM', KEYS[2], ARGV[7])
+            return -1
+        end
+        redis.call('HSET', KEYS[1], 'jti', ARGV[2], 'last_seen_at', ARGV[4], 'ip', ARGV[5], 'user_agent', ARGV[6])
+        redis.call('EXPIRE', KEYS[1], ARGV[3])
+        redis.call('EXPIRE', KEYS[2], ARGV[3])
+        return 1
+        ",
+    )
+});
+
+/// Gives the session a new `jti` without checking the previous one. Returns `0`
+/// if the session has already ended.
+static REISSUE_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+        if redis.call('EXISTS', KEYS[1]) == 0 then
+            return 0
+        end
+        redis.call('HSET', KEYS[1], 'jti', ARGV[1], 'last_seen_at', ARGV[2], 'ip', ARGV[3], 'user_agent', ARGV[4])
+        return 1
+        ",
+    )
+});
+
+/// Deletes every session of the user. The set is read inside the script, so a
+/// session created concurrently is either deleted too or created afterwards.
+static REVOKE_ALL_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+        local sids = redis.call('SMEMBERS', KEYS[1])
+        for _, sid in ipairs(sids) do
+            redis.call('DEL', ARGV[1] .. ':' .. sid)
+        end
+        redis.call('DEL', KEYS[1])
+        return #sids
+        ",
+    )
+});
+
+/// Caches the user's token version unless a newer one is already cached, so a
+/// `validate` that read the version before `invalidate_tokens` cannot put the
+/// old value back.
+static CACHE_VERSION_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+        local cached = tonumber(redis.call('GET', KEYS[1]))
+        if not cached or cached < tonumber(ARGV[1]) then
+            redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[2])
+        end
+        return 1
+        ",
+    )
+});
+
+fn session_key(sid: Uuid) -> String {
+    format!("{}:{}", SESSION_PREFIX, sid)
+}
+
+fn user_sessions_key(user_id: Uuid) -> String {
+    format!("{}:{}", USER_SESSIONS_PREFIX, user_id)
+}
+
+fn token_version_key(user_id: Uuid) -> String {
+    format!("{}:{}", TOKEN_VERSION_PREFIX, user_id)
+}
+
+fn parse_time(value: Option<&String>) -> Option<DateTime<Local>> {
+    value
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|v| v.with_timezone(&Local))
+}
+
+fn non_empty(value: Option<&String>) -> Option<String> {
+    value.filter(|v| !v.is_empty()).cloned()
+}
+
+pub struct SessionService;
+
+impl SessionService {
+    pub async fn create(
+        state: &AppState,
+        user: &users::Model,
+        client: &ClientInfo,
+    ) -> Result<TokenPair, AppError> {
+        let sid = Uuid::new_v4();
+        let jti = Uuid::new_v4();
+        let now = Local::now().to_rfc3339();
+
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let key = session_key(sid);
+        let user_key = user_sessions_key(user.id);
+
+        let _: () = redis::pipe()
+            .atomic()
+            .hset_multiple(
+                &key,
+                &[
+                    ("user_id", user.id.to_string()),
+                    ("jti", jti.to_string()),
+                    ("user_agent", client.user_agent.clone().unwrap_or_default()),
+                    ("ip", client.ip.clone().unwrap_or_default()),
+                    ("created_at", now.clone()),
+                    ("last_seen_at", now),
+                ],
+            )
+            .expire(&key, SESSION_TTL_SECONDS)
+            .sadd(&user_key, sid.to_string())
+            .expire(&user_key, SESSION_TTL_SECONDS)
+            .query_async(&mut redis_con)
+            .await?;
+
+        let user = TwoFactorService::apply_policy(state, user.clone()).await?;
+
+        generate_tokens(&user, sid, jti, None)
+    }
+
+    pub async fn rotate(
+        state: &AppState,
+        refresh_token: &str,
+        client: &ClientInfo,
+    ) -> Result<TokenPair, AppError> {
+        let claims = decode_token(refresh_token, TokenType::Refresh)?;
+
+        let (Some(sid), Some(jti)) = (claims.sid, claims.jti) else {
+            return Err(AppError::InvalidToken);
+        };
+
+        let user = User::find_by_id(claims.sub)
+            .filter(users::Column::IsDeleted.eq(false))
+            .one(&state.conn)
+            .await?;
+
+        let Some(user) = user else {
+            Self::remove(state, claims.sub, sid).await?;
+            return Err(AppError::InvalidToken);
+        };
+
+        let new_jti = Uuid::new_v4();
+
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let result: i32 = ROTATE_SCRIPT
+            .key(session_key(sid))
+            .key(user_sessions_key(claims.sub))
+            .arg(jti.to_string())
+            .arg(new_jti.to_string())
+            .arg(SESSION_TTL_SECONDS)
+            .arg(Local::now().to_rfc3339())
+            .arg(client.ip.clone().unwrap_or_default())
+            .arg(client.user_agent.clone().unwrap_or_default())
+            .arg(sid.to_string())
+            .invoke_async(&mut redis_con)
+            .await?;
+
+        match result {
+            1 => {
+                let user = TwoFactorService::apply_policy(state, user).await?;
+                generate_tokens(&user, sid, new_jti, claims.active_role)
+            }
+            -1 => {
+                tracing::warn!("Refresh token reuse detected, session {} revoked", sid);
+                Err(AppError::InvalidToken)
+            }
+            _ => Err(AppError::InvalidToken),
+        }
+    }
+
+    /// Reissues the current session's tokens with the selected role. `None`
+    /// goes back to acting with all of the user's roles. The session's previous
+    /// refresh token is no longer accepted.
+    pub async fn switch_role(
+        state: &AppState,
+        claims: &Claims,
+        role: Option<Role>,
+        client: &ClientInfo,
+    ) -> Result<TokenPair, AppError> {
+        let sid = claims.sid.ok_or(AppError::Forbidden)?;
+
+        let user = User::find_by_id(claims.sub)
+            .filter(users::Column::IsDeleted.eq(false))
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::InvalidToken)?;
+
+        let user = TwoFactorService::apply_policy(state, user).await?;
+
+        if let Some(role) = &role
+            && !user.roles.contains(role)
+        {
+            return Err(AppError::Forbidden);
+        }
+
+        let jti = Uuid::new_v4();
+
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let result: i32 = REISSUE_SCRIPT
+            .key(session_key(sid))
+            .arg(jti.to_string())
+            .arg(Local::now().to_rfc3339())
+            .arg(client.ip.clone().unwrap_or_default())
+            .arg(client.user_agent.clone().unwrap_or_default())
+            .invoke_async(&mut redis_con)
+            .await?;
+
+        if result == 0 {
+            return Err(AppError::InvalidToken);
+        }
+
+        generate_tokens(&user, sid, jti, role)
+    }
+
+    pub async fn revoke_current(state: &AppState, refresh_token: &str) -> Result<(), AppError> {
+        let Ok(claims) = decode_token(refresh_token, TokenType::Refresh) else {
+            return Ok(());
+        };
+
+        if let Some(sid) = claims.sid {
+            Self::remove(state, claims.sub, sid).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the access token's session has not ended and the user's
+    /// token version has not changed since the token was issued.
+    pub async fn validate(state: &AppState, claims: &Claims) -> Result<(), AppError> {
+        let sid = claims.sid.ok_or(AppError::InvalidToken)?;
+
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let version_key = token_version_key(claims.sub);
+
+        let (exists, version): (bool, Option<i32>) = redis::pipe()
+            .exists(session_key(sid))
+            .get(&version_key)
+            .query_async(&mut redis_con)
+            .await?;
+
+        if !exists {
+            return Err(AppError::InvalidToken);
+        }
+
+        let version = match version {
+            Some(version) => version,
+            None => {
+                let version: i32 = User::find_by_id(claims.sub)
+                    .select_only()
+                    .column(users::Column::TokenVersion)
+                    .into_tuple()
+                    .one(&state.conn)
+                    .await?
+                    .ok_or(AppError::InvalidToken)?;
+
+                let _: i32 = CACHE_VERSION_SCRIPT
+                    .key(&version_key)
+                    .arg(version)
+                    .arg(TOKEN_VERSION_TTL_SECONDS)
+                    .invoke_async(&mut redis_con)
+                    .await?;
+
+                version
+            }
+        };
+
+        if version != claims.ver {
+            return Err(AppError::InvalidToken);
+        }
+
+        Ok(())
+    }
+
+    /// Bumps the user's token version, invalidating every access token
+    /// issued before.
+    pub async fn invalidate_tokens(state: &AppState, user_id: Uuid) -> Result<(), AppError> {
+        let updated = User::update_many()
+            .col_expr(
+                users::Column::TokenVersion,
+                Expr::col(users::Column::TokenVersion).add(1),
+            )
+            .filter(users::Column::Id.eq(user_id))
+            .exec_with_returning(&state.conn)
+            .await?;
+
+        let Some(user) = updated.first() else {
+            return Ok(());
+        };
+
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        // The new version is written rather than deleted: a concurrent `validate`
+        // could otherwise cache the version it read before the update.
+        let _: i32 = CACHE_VERSION_SCRIPT
+            .key(token_version_key(user_id))
+            .arg(user.token_version)
+            .arg(TOKEN_VERSION_TTL_SECONDS)
+            .invoke_async(&mut redis_con)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_all(state: &AppState, claims: &Claims) -> Result<Vec<SessionDto>, AppError> {
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let user_key = user_sessions_key(claims.sub);
+
+        let sids: Vec<String> = redis_con.smembers(&user_key).await?;
+
+        let mut pipe = redis::pipe();
+        for sid in &sids {
+            pipe.hgetall(format!("{}:{}", SESSION_PREFIX, sid));
+        }
+        let entries: Vec<HashMap<String, String>> = pipe.query_async(&mut redis_con).await?;
+
+        let mut sessions = Vec::with_capacity(sids.len());
+        let mut stale = Vec::new();
+
+        for (sid, entry) in sids.into_iter().zip(entries) {
+            let session = Uuid::parse_str(&sid).ok().and_then(|id| {
+                Some(SessionDto {
+                    id,
+                    user_agent: non_empty(entry.get("user_agent")),
+                    ip: non_empty(entry.get("ip")),
+                    created_at: parse_time(entry.get("created_at"))?,
+                    last_seen_at: parse_time(entry.get("last_seen_at"))?,
+                    current: claims.sid == Some(id),
+                })
+            });
+
+            match session {
+                Some(session) => sessions.push(session),
+                None => stale.push(sid),
+            }
+        }
+
+        if !stale.is_empty() {
+            let _: () = redis_con.srem(&user_key, stale).await?;
+        }
+
+        sessions.sort_by_key(|session| Reverse(session.last_seen_at));
+
+        Ok(sessions)
+    }
+
+    pub async fn revoke(state: &AppState, user_id: Uuid, sid: Uuid) -> Result<(), AppError> {
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let is_member: bool = redis_con
+            .sismember(user_sessions_key(user_id), sid.to_string())
+            .await?;
+
+        if !is_member {
+            return Err(AppError::NotFound);
+        }
+
+        Self::remove(state, user_id, sid).await
+    }
+
+    pub async fn revoke_all(state: &AppState, user_id: Uuid) -> Result<(), AppError> {
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let _: i32 = REVOKE_ALL_SCRIPT
+            .key(user_sessions_key(user_id))
+            .arg(SESSION_PREFIX)
+            .invoke_async(&mut redis_con)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn remove(state: &AppState, user_id: Uuid, sid: Uuid) -> Result<(), AppError> {
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let _: () = redis::pipe()
+            .atomic()
+            .del(session_key(sid))
+            .srem(user_sessions_key(user_id), sid.to_string())
+            .query_async(&mut redis_con)
+            .await?;
+
+        Ok(())
+    }
+}
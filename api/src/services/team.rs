@@ -0,0 +1,688 @@
+use crate::{
+    AppState,
+    dtos::{
+        skill::SkillDto,
+        team::{
+            CreateTeamRequest, InviteTeamMemberRequest, TeamInvitationDto, TeamMemberDto,
+            TeamResponse, TeamSkillDto, TransferOwnershipRequest, UpdateTeamMemberRoleRequest,
+            UpdateTeamRequest,
+        },
+    },
+    error::AppError,
+    services::authorizer::Authorizer,
+    utils::{security::Claims, smtp::send_team_invitation},
+};
+use chrono::Local;
+use entity::{
+    permission::Permission,
+    prelude::{Skill, Team, TeamInvitation, TeamMember, UserSkill, Users},
+    role::Role,
+    skill, team, team_invitation,
+    team_invitation_status::TeamInvitationStatus,
+    team_member, user_skill, users,
+};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, Condition, ConnectionTrait, EntityTrait,
+    IntoActiveModel, JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+    TransactionTrait, prelude::Uuid, sea_query,
+};
+use std::collections::HashMap;
+use validator::Validate;
+
+pub struct TeamService;
+
+impl TeamService {
+    pub async fn get_all(state: &AppState) -> Result<Vec<TeamResponse>, AppError> {
+        let teams = Team::find()
+            .join(JoinType::InnerJoin, team::Relation::Owner.def())
+            .order_by_asc(team::Column::Name)
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        Ok(teams)
+    }
+
+    pub async fn get_my(state: &AppState, user_id: Uuid) -> Result<Vec<TeamResponse>, AppError> {
+        let teams = Team::find()
+            .join(JoinType::InnerJoin, team::Relation::Owner.def())
+            .filter(
+                team::Column::Id.in_subquery(
+                    sea_query::Query::select()
+                        .column(team_member::Column::TeamId)
+                        .from(team_member::Entity)
+                        .and_where(team_member::Column::UserId.eq(user_id))
+                        .to_owned(),
+                ),
+            )
+            .order_by_asc(team::Column::Name)
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        Ok(teams)
+    }
+
+    /// A team with its members, for its members and holders of `team:read:any`.
+    pub async fn get_by_id(
+        state: &AppState,
+        id: Uuid,
+        claims: &Claims,
+    ) -> Result<TeamResponse, AppError> {
+        Self::check_access(state, id, claims).await?;
+
+        Self::get_one(state, id).await
+    }
+
+    async fn get_one(state: &AppState, id: Uuid) -> Result<TeamResponse, AppError> {
+        let mut team: TeamResponse = Team::find_by_id(id)
+            .join(JoinType::InnerJoin, team::Relation::Owner.def())
+            .into_partial_model()
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        team.members = TeamMember::find()
+            .join(JoinType::InnerJoin, team_member::Relation::Users.def())
+            .filter(team_member::Column::TeamId.eq(id))
+            .order_by_asc(team_member::Column::JoinedAt)
+            .into_partial_model::<TeamMemberDto>()
+            .all(&state.conn)
+            .await?;
+
+        Ok(team)
+    }
+
+    pub async fn create(
+        state: &AppState,
+        payload: CreateTeamRequest,
+        owner_id: Uuid,
+    ) -> Result<TeamResponse, AppError> {
+        payload.validate()?;
+
+        let txn = state.conn.begin().await?;
+
+        let team = team::ActiveModel {
+            name: Set(payload.name),
+            description: Set(payload.description),
+            owner_id: Set(owner_id),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        team_member::ActiveModel {
+            team_id: Set(team.id),
+            user_id: Set(owner_id),
+            role: Set(Role::TeamOwner),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Self::get_one(state, team.id).await
+    }
+
+    pub async fn update(
+        state: &AppState,
+        payload: UpdateTeamRequest,
+        claims: &Claims,
+    ) -> Result<TeamResponse, AppError> {
+        payload.validate()?;
+
+        let team = Self::find_team(&state.conn, payload.id).await?;
+
+        Self::check_owner(state, &team, claims).await?;
+
+        let mut team = team.into_active_model();
+
+        if let Some(name) = payload.name {
+            team.name = Set(name);
+        }
+
+        if let Some(description) = payload.description {
+            team.description = Set(Some(description).filter(|d| !d.trim().is_empty()));
+        }
+
+        team.updated_at = Set(Local::now().into());
+
+        let team = team.update(&state.conn).await?;
+
+        Self::get_one(state, team.id).await
+    }
+
+    pub async fn delete(state: &AppState, id: Uuid, claims: &Claims) -> Result<(), AppError> {
+        let team = Self::find_team(&state.conn, id).await?;
+
+        Self::check_owner(state, &team, claims).await?;
+
+        Team::delete_by_id(id).exec(&state.conn).await?;
+        Ok(())
+    }
+
+    /// Invites a user by id or by email. An email that does not belong to a
+    /// registered user is stored lowercased and matched against the email in
+    /// the claims when the invitation is accepted.
+    pub async fn invite(
+        state: &AppState,
+        team_id: Uuid,
+        payload: InviteTeamMemberRequest,
+        claims: &Claims,
+    ) -> Result<TeamInvitationDto, AppError> {
+        payload.validate()?;
+
+        let team = Self::find_team(&state.conn, team_id).await?;
+
+        Self::check_manager(state, &team, claims).await?;
+
+        let invitee = match (payload.user_id, payload.email) {
+            (Some(user_id), None) => Users::find_by_id(user_id)
+                .filter(users::Column::IsDeleted.eq(false))
+                .one(&state.conn)
+                .await?
+                .ok_or(AppError::NotFound)?,
+            (None, Some(email)) => {
+                let email = email.to_lowercase();
+                let user = Users::find()
+                    .filter(users::Column::Email.eq(&email))
+                    .filter(users::Column::IsDeleted.eq(false))
+                    .one(&state.conn)
+                    .await?;
+
+                match user {
+                    Some(user) => user,
+                    None => {
+                        return Self::create_invitation(state, &team, None, email, claims).await;
+                    }
+                }
+            }
+            _ => {
+                return Err(AppError::Custom(
+                    "Необходимо указать либо user_id, либо email".to_string(),
+                ));
+            }
+        };
+
+        if Self::find_member(&state.conn, team_id, invitee.id)
+            .await?
+            .is_some()
+        {
+            return Err(AppError::Conflict(
+                "Пользователь уже состоит в команде".to_string(),
+            ));
+        }
+
+        Self::create_invitation(state, &team, Some(invitee.id), invitee.email, claims).await
+    }
+
+    pub async fn get_team_invitations(
+        state: &AppState,
+        team_id: Uuid,
+        claims: &Claims,
+    ) -> Result<Vec<TeamInvitationDto>, AppError> {
+        let team = Self::find_team(&state.conn, team_id).await?;
+
+        Self::check_manager(state, &team, claims).await?;
+
+        let invitations = TeamInvitation::find()
+            .join(JoinType::InnerJoin, team_invitation::Relation::Team.def())
+            .filter(team_invitation::Column::TeamId.eq(team_id))
+            .filter(team_invitation::Column::Status.eq(TeamInvitationStatus::Pending))
+            .order_by_desc(team_invitation::Column::CreatedAt)
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        Ok(invitations)
+    }
+
+    pub async fn get_my_invitations(
+        state: &AppState,
+        claims: &Claims,
+    ) -> Result<Vec<TeamInvitationDto>, AppError> {
+        let invitations = TeamInvitation::find()
+            .join(JoinType::InnerJoin, team_invitation::Relation::Team.def())
+            .filter(Self::invitee_condition(claims))
+            .filter(team_invitation::Column::Status.eq(TeamInvitationStatus::Pending))
+            .order_by_desc(team_invitation::Column::CreatedAt)
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        Ok(invitations)
+    }
+
+    pub async fn accept_invitation(
+        state: &AppState,
+        invitation_id: Uuid,
+        claims: &Claims,
+    ) -> Result<TeamResponse, AppError> {
+        let txn = state.conn.begin().await?;
+
+        let (team, invitation) = Self::lock_my_invitation(&txn, invitation_id, claims).await?;
+        let team_id = team.id;
+
+        if Self::find_member(&txn, team_id, claims.sub)
+            .await?
+            .is_none()
+        {
+            team_member::ActiveModel {
+                team_id: Set(team_id),
+                user_id: Set(claims.sub),
+                role: Set(Role::Member),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        let mut invitation = invitation.into_active_model();
+
+        invitation.user_id = Set(Some(claims.sub));
+        invitation.status = Set(TeamInvitationStatus::Accepted);
+        invitation.responded_at = Set(Some(Local::now().into()));
+
+        invitation.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Self::get_one(state, team_id).await
+    }
+
+    pub async fn decline_invitation(
+        state: &AppState,
+        invitation_id: Uuid,
+        claims: &Claims,
+    ) -> Result<(), AppError> {
+        let txn = state.conn.begin().await?;
+
+        let (_, invitation) = Self::lock_my_invitation(&txn, invitation_id, claims).await?;
+
+        let mut invitation = invitation.into_active_model();
+
+        invitation.user_id = Set(Some(claims.sub));
+        invitation.status = Set(TeamInvitationStatus::Declined);
+        invitation.responded_at = Set(Some(Local::now().into()));
+
+        invitation.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn leave(state: &AppState, team_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let txn = state.conn.begin().await?;
+
+        let team = Self::lock_team(&txn, team_id).await?;
+
+        if team.owner_id == user_id {
+            return Err(AppError::Conflict(
+                "Владелец не может покинуть команду, сначала передайте права другому участнику"
+                    .to_string(),
+            ));
+        }
+
+        let res = TeamMember::delete_by_id((team_id, user_id))
+            .exec(&txn)
+            .await?;
+
+        if res.rows_affected == 0 {
+            return Err(AppError::NotFound);
+        }
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// The owner and admins may remove anyone except the owner, team leaders
+    /// may only remove regular members.
+    pub async fn kick(
+        state: &AppState,
+        team_id: Uuid,
+        user_id: Uuid,
+        claims: &Claims,
+    ) -> Result<(), AppError> {
+        let txn = state.conn.begin().await?;
+
+        let team = Self::lock_team(&txn, team_id).await?;
+
+        if user_id == team.owner_id {
+            return Err(AppError::Conflict(
+                "Нельзя исключить владельца команды".to_string(),
+            ));
+        }
+
+        let member = Self::find_member(&txn, team_id, user_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        if !Self::is_owner(state, &team, claims).await? {
+            let caller = Self::find_member(&txn, team_id, claims.sub).await?;
+
+            let is_leader = caller.is_some_and(|caller| caller.role == Role::TeamLeader);
+            if !is_leader || member.role != Role::Member {
+                return Err(AppError::Forbidden);
+            }
+        }
+
+        TeamMember::delete_by_id((team_id, user_id))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn transfer_ownership(
+        state: &AppState,
+        team_id: Uuid,
+        payload: TransferOwnershipRequest,
+        claims: &Claims,
+    ) -> Result<TeamResponse, AppError> {
+        let txn = state.conn.begin().await?;
+
+        // Ownership is checked against the locked row, so a concurrent transfer
+        // or member removal can't act on an owner that is no longer current.
+        let team = Self::lock_team(&txn, team_id).await?;
+
+        Self::check_owner(state, &team, claims).await?;
+
+        if team.owner_id == payload.user_id {
+            return Err(AppError::Conflict(
+                "Пользователь уже является владельцем команды".to_string(),
+            ));
+        }
+
+        let new_owner = Self::find_member(&txn, team_id, payload.user_id)
+            .await?
+            .ok_or(AppError::Custom(
+                "Права можно передать только участнику команды".to_string(),
+            ))?;
+
+        if let Some(old_owner) = Self::find_member(&txn, team_id, team.owner_id).await? {
+            let mut old_owner = old_owner.into_active_model();
+            old_owner.role = Set(Role::Member);
+            old_owner.update(&txn).await?;
+        }
+
+        let mut new_owner = new_owner.into_active_model();
+        new_owner.role = Set(Role::TeamOwner);
+        new_owner.update(&txn).await?;
+
+        let mut team = team.into_active_model();
+        team.owner_id = Set(payload.user_id);
+        team.updated_at = Set(Local::now().into());
+        team.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Self::get_one(state, team_id).await
+    }
+
+    pub async fn update_member_role(
+        state: &AppState,
+        team_id: Uuid,
+        user_id: Uuid,
+        payload: UpdateTeamMemberRoleRequest,
+        claims: &Claims,
+    ) -> Result<TeamResponse, AppError> {
+        if !matches!(payload.role, Role::TeamLeader | Role::Member) {
+            return Err(AppError::Custom(
+                "Участнику можно назначить только роль TEAM_LEADER или MEMBER".to_string(),
+            ));
+        }
+
+        let txn = state.conn.begin().await?;
+
+        let team = Self::lock_team(&txn, team_id).await?;
+
+        Self::check_owner(state, &team, claims).await?;
+
+        if user_id == team.owner_id {
+            return Err(AppError::Conflict(
+                "Роль владельца меняется через передачу прав".to_string(),
+            ));
+        }
+
+        let mut member = Self::find_member(&txn, team_id, user_id)
+            .await?
+            .ok_or(AppError::NotFound)?
+            .into_active_model();
+
+        member.role = Set(payload.role);
+        member.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Self::get_one(state, team_id).await
+    }
+
+    /// Skills of all team members, most common first.
+    pub async fn get_skill_profile(
+        state: &AppState,
+        team_id: Uuid,
+        claims: &Claims,
+    ) -> Result<Vec<TeamSkillDto>, AppError> {
+        Self::check_access(state, team_id, claims).await?;
+        Self::find_team(&state.conn, team_id).await?;
+
+        let skill_ids: Vec<Uuid> = UserSkill::find()
+            .select_only()
+            .column(user_skill::Column::SkillId)
+            .filter(
+                user_skill::Column::UserId.in_subquery(
+                    sea_query::Query::select()
+                        .column(team_member::Column::UserId)
+                        .from(team_member::Entity)
+                        .and_where(team_member::Column::TeamId.eq(team_id))
+                        .to_owned(),
+                ),
+            )
+            .into_tuple()
+            .all(&state.conn)
+            .await?;
+
+        let mut counts: HashMap<Uuid, usize> = HashMap::new();
+        for skill_id in skill_ids {
+            *counts.entry(skill_id).or_default() += 1;
+        }
+
+        let skills: Vec<SkillDto> = Skill::find()
+            .filter(skill::Column::Id.is_in(counts.keys().copied()))
+            .filter(skill::Column::DeletedAt.is_null())
+            .into_partial_model()
+            .all(&state.conn)
+            .await?;
+
+        let mut profile: Vec<TeamSkillDto> = skills
+            .into_iter()
+            .map(|skill| TeamSkillDto {
+                members_count: counts.get(&skill.id).copied().unwrap_or_default(),
+                skill,
+            })
+            .collect();
+
+        profile.sort_by(|a, b| {
+            b.members_count
+                .cmp(&a.members_count)
+                .then_with(|| a.skill.name.cmp(&b.skill.name))
+        });
+
+        Ok(profile)
+    }
+
+    async fn create_invitation(
+        state: &AppState,
+        team: &team::Model,
+        user_id: Option<Uuid>,
+        email: String,
+        claims: &Claims,
+    ) -> Result<TeamInvitationDto, AppError> {
+        // Invitations are looked up by the lowercased email from the claims.
+        let email = email.to_lowercase();
+
+        let is_pending = TeamInvitation::find()
+            .filter(team_invitation::Column::TeamId.eq(team.id))
+            .filter(team_invitation::Column::Email.eq(&email))
+            .filter(team_invitation::Column::Status.eq(TeamInvitationStatus::Pending))
+            .one(&state.conn)
+            .await?
+            .is_some();
+
+        if is_pending {
+            return Err(AppError::Conflict(
+                "Приглашение этому пользователю уже отправлено".to_string(),
+            ));
+        }
+
+        let invitation = team_invitation::ActiveModel {
+            team_id: Set(team.id),
+            user_id: Set(user_id),
+            email: Set(email.clone()),
+            inviter_id: Set(claims.sub),
+            status: Set(TeamInvitationStatus::Pending),
+            ..Default::default()
+        }
+        .insert(&state.conn)
+        .await?;
+
+        let team_id = team.id.to_string();
+        let team_name = team.name.clone();
+        let first_name = claims.first_name.clone();
+        let last_name = claims.last_name.clone();
+
+        // The invitation is already saved, so a failed email must not fail the request.
+        tokio::spawn(async move {
+            let result =
+                send_team_invitation(team_id, team_name, first_name, last_name, email).await;
+
+            if let Err(e) = result {
+                tracing::error!("Failed to send notification: {}", e);
+            }
+        });
+
+        TeamInvitation::find_by_id(invitation.id)
+            .join(JoinType::InnerJoin, team_invitation::Relation::Team.def())
+            .into_partial_model()
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    fn invitee_condition(claims: &Claims) -> Condition {
+        Condition::any()
+            .add(team_invitation::Column::UserId.eq(claims.sub))
+            .add(team_invitation::Column::Email.eq(claims.email.to_lowercase()))
+    }
+
+    /// Locks the team of a pending invitation addressed to the user and reads
+    /// the invitation under that lock, so two responses to it can't both see
+    /// it pending.
+    async fn lock_my_invitation<C: ConnectionTrait>(
+        db: &C,
+        invitation_id: Uuid,
+        claims: &Claims,
+    ) -> Result<(team::Model, team_invitation::Model), AppError> {
+        let find = || {
+            TeamInvitation::find_by_id(invitation_id)
+                .filter(Self::invitee_condition(claims))
+                .one(db)
+        };
+
+        let team_id = find().await?.ok_or(AppError::NotFound)?.team_id;
+        let team = Self::lock_team(db, team_id).await?;
+        let invitation = find().await?.ok_or(AppError::NotFound)?;
+
+        if invitation.status != TeamInvitationStatus::Pending {
+            return Err(AppError::Conflict("Приглашение уже обработано".to_string()));
+        }
+
+        Ok((team, invitation))
+    }
+
+    async fn find_team<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<team::Model, AppError> {
+        Team::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    /// Like `find_team`, but keeps the row locked until the transaction ends.
+    /// Owner changes, member removals and invitation responses take this lock,
+    /// so each of them sees the owner and members the others left behind.
+    async fn lock_team<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<team::Model, AppError> {
+        Team::find_by_id(id)
+            .lock_exclusive()
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
+    async fn find_member<C: ConnectionTrait>(
+        db: &C,
+        team_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<team_member::Model>, AppError> {
+        Ok(TeamMember::find_by_id((team_id, user_id)).one(db).await?)
+    }
+
+    /// Members of the team and holders of `team:read:any` may view it.
+    pub async fn check_access(
+        state: &AppState,
+        team_id: Uuid,
+        claims: &Claims,
+    ) -> Result<(), AppError> {
+        if Self::find_member(&state.conn, team_id, claims.sub)
+            .await?
+            .is_none()
+        {
+            Authorizer::require(state, claims, Permission::TeamReadAny).await?;
+        }
+        Ok(())
+    }
+
+    async fn is_owner(
+        state: &AppState,
+        team: &team::Model,
+        claims: &Claims,
+    ) -> Result<bool, AppError> {
+        Ok(team.owner_id == claims.sub
+            || Authorizer::can(state, claims, Permission::TeamManageAny).await?)
+    }
+
+    async fn check_owner(
+        state: &AppState,
+        team: &team::Model,
+        claims: &Claims,
+    ) -> Result<(), AppError> {
+        if !Self::is_owner(state, team, claims).await? {
+            return Err(AppError::Forbidden);
+        }
+        Ok(())
+    }
+
+    /// Owners, team leaders and holders of `team:manage:any` may manage invitations.
+    pub async fn check_manager(
+        state: &AppState,
+        team: &team::Model,
+        claims: &Claims,
+    ) -> Result<(), AppError> {
+        if Self::is_owner(state, team, claims).await? {
+            return Ok(());
+        }
+
+        let is_leader = Self::find_member(&state.conn, team.id, claims.sub)
+            .await?
+            .is_some_and(|member| member.role == Role::TeamLeader);
+
+        if !is_leader {
+            return Err(AppError::Forbidden);
+        }
+        Ok(())
+    }
+}
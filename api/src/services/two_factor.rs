@@ -0,0 +1,536 @@
+use crate::{
+    AppState,
+    dtos::two_factor::{
+        RecoveryCodesResponse, TwoFactorChallengeResponse, TwoFactorCodePayload,
+        TwoFactorEnrollmentResponse, TwoFactorLoginPayload, TwoFactorPolicyDto,
+        TwoFactorStatusResponse,
+    },
+    error::AppError,
+    services::session::SessionService,
+    utils::security::{Claims, hash_password, verify_password},
+};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Local;
+use entity::{
+    recovery_code::{self, Entity as RecoveryCode},
+    role::Role,
+    two_factor::{self, Entity as TwoFactor},
+    two_factor_policy::{self, Entity as TwoFactorPolicy},
+    users::{self, Entity as User},
+};
+use redis::AsyncCommands;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, PaginatorTrait,
+    QueryFilter, TransactionTrait, prelude::Expr, prelude::Uuid,
+};
+use std::collections::HashMap;
+use totp_rs::{Builder, Secret, Totp};
+use validator::Validate;
+
+const TOTP_ISSUER: &str = "HITS";
+const TOTP_SECRET_BYTES: usize = 20;
+const TOTP_USED_PREFIX: &str = "totp_used";
+const TOTP_USED_TTL_SECONDS: u64 = 90;
+const RECOVERY_CODES_COUNT: usize = 10;
+const CHALLENGE_PREFIX: &str = "two_factor_challenge";
+const CHALLENGE_TTL_SECONDS: i64 = 5 * 60;
+const MAX_CHALLENGE_ATTEMPTS: i64 = 5;
+
+fn build_totp(secret: &str, account_name: &str) -> Result<Totp, AppError> {
+    let secret = Secret::try_from_base32(secret).map_err(|_| AppError::InternalServerError)?;
+
+    Builder::new()
+        .with_secret(secret)
+        .with_account_name(account_name)
+        .with_issuer(Some(TOTP_ISSUER))
+        .build()
+        .map_err(|_| AppError::InternalServerError)
+}
+
+fn generate_recovery_code() -> String {
+    let mut bytes = [0u8; 5];
+    OsRng.fill_bytes(&mut bytes);
+    let code: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}-{}", &code[..5], &code[5..])
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    code.trim().replace('-', "").to_lowercase()
+}
+
+fn is_totp_code(code: &str) -> bool {
+    code.len() == 6 && code.chars().all(|c| c.is_ascii_digit())
+}
+
+fn challenge_key(id: Uuid) -> String {
+    format!("{}:{}", CHALLENGE_PREFIX, id)
+}
+
+pub struct TwoFactorService;
+
+impl TwoFactorService {
+    pub async fn status(
+        state: &AppState,
+        claims: &Claims,
+    ) -> Result<TwoFactorStatusResponse, AppError> {
+        let user = User::find_by_id(claims.sub)
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let enabled = Self::find_enabled(&state.conn, claims.sub).await?.is_some();
+
+        let recovery_codes_left = RecoveryCode::find()
+            .filter(recovery_code::Column::UserId.eq(claims.sub))
+            .filter(recovery_code::Column::UsedAt.is_null())
+            .count(&state.conn)
+            .await?;
+
+        Ok(TwoFactorStatusResponse {
+            enabled,
+            required: Self::is_required(state, &user.roles).await?,
+            recovery_codes_left,
+        })
+    }
+
+    /// Issues a new secret. 2FA stays disabled until a code confirms it,
+    /// so calling this again simply replaces the secret.
+    pub async fn enroll(
+        state: &AppState,
+        claims: &Claims,
+    ) -> Result<TwoFactorEnrollmentResponse, AppError> {
+        if Self::find_enabled(&state.conn, claims.sub).await?.is_some() {
+            return Err(AppError::Conflict(
+                "Двухфакторная аутентификация уже включена".to_string(),
+            ));
+        }
+
+        let mut bytes = [0u8; TOTP_SECRET_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        let secret = Secret::from(bytes.to_vec()).to_base32();
+
+        let totp = build_totp(&secret, &claims.email)?;
+        let otpauth_uri = totp.to_url().map_err(|_| AppError::InternalServerError)?;
+        let qr_code = totp
+            .to_qr_base64()
+            .map_err(|_| AppError::InternalServerError)?;
+
+        let txn = state.conn.begin().await?;
+
+        TwoFactor::delete_by_id(claims.sub).exec(&txn).await?;
+
+        two_factor::ActiveModel {
+            user_id: Set(claims.sub),
+            secret: Set(secret.clone()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(TwoFactorEnrollmentResponse {
+            secret,
+            otpauth_uri,
+            qr_code: format!("data:image/png;base64,{}", qr_code),
+        })
+    }
+
+    pub async fn confirm(
+        state: &AppState,
+        claims: &Claims,
+        payload: TwoFactorCodePayload,
+    ) -> Result<RecoveryCodesResponse, AppError> {
+        payload.validate()?;
+
+        let two_factor = TwoFactor::find_by_id(claims.sub)
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        if two_factor.enabled_at.is_some() {
+            return Err(AppError::Conflict(
+                "Двухфакторная аутентификация уже включена".to_string(),
+            ));
+        }
+
+        if !is_totp_code(&payload.code)
+            || !Self::verify_totp(state, &two_factor, &payload.code).await?
+        {
+            return Err(AppError::Custom("Неверный код".to_string()));
+        }
+
+        let txn = state.conn.begin().await?;
+
+        TwoFactor::update_many()
+            .col_expr(two_factor::Column::EnabledAt, Expr::value(Local::now()))
+            .filter(two_factor::Column::UserId.eq(claims.sub))
+            .exec(&txn)
+            .await?;
+
+        let recovery_codes = Self::replace_recovery_codes(&txn, claims.sub).await?;
+
+        txn.commit().await?;
+
+        // Roles hidden by the policy until 2FA was enabled come back on token refresh.
+        SessionService::invalidate_tokens(state, claims.sub).await?;
+
+        Ok(RecoveryCodesResponse { recovery_codes })
+    }
+
+    pub async fn regenerate_recovery_codes(
+        state: &AppState,
+        claims: &Claims,
+        payload: TwoFactorCodePayload,
+    ) -> Result<RecoveryCodesResponse, AppError> {
+        payload.validate()?;
+
+        let two_factor = Self::find_enabled(&state.conn, claims.sub)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        if !is_totp_code(&payload.code)
+            || !Self::verify_totp(state, &two_factor, &payload.code).await?
+        {
+            return Err(AppError::Custom("Неверный код".to_string()));
+        }
+
+        let txn = state.conn.begin().await?;
+        let recovery_codes = Self::replace_recovery_codes(&txn, claims.sub).await?;
+        txn.commit().await?;
+
+        Ok(RecoveryCodesResponse { recovery_codes })
+    }
+
+    pub async fn disable(
+        state: &AppState,
+        claims: &Claims,
+        payload: TwoFactorCodePayload,
+    ) -> Result<(), AppError> {
+        payload.validate()?;
+
+        let two_factor = Self::find_enabled(&state.conn, claims.sub)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        let user = User::find_by_id(claims.sub)
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+        if Self::is_required(state, &user.roles).await? {
+            return Err(AppError::Custom(
+                "Двухфакторная аутентификация обязательна для ваших ролей".to_string(),
+            ));
+        }
+
+        if !Self::verify_code(state, &two_factor, &payload.code).await? {
+            return Err(AppError::Custom("Неверный код".to_string()));
+        }
+
+        Self::remove(state, claims.sub).await
+    }
+
+    /// Resets 2FA for a user who lost both the device and the recovery codes.
+    pub async fn reset(state: &AppState, user_id: Uuid) -> Result<(), AppError> {
+        if TwoFactor::find_by_id(user_id)
+            .one(&state.conn)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::NotFound);
+        }
+
+        Self::remove(state, user_id).await?;
+
+        SessionService::invalidate_tokens(state, user_id).await
+    }
+
+    pub async fn get_policy(state: &AppState) -> Result<TwoFactorPolicyDto, AppError> {
+        let roles = TwoFactorPolicy::find()
+            .all(&state.conn)
+            .await?
+            .into_iter()
+            .map(|policy| policy.role)
+            .collect();
+
+        Ok(TwoFactorPolicyDto { roles })
+    }
+
+    pub async fn set_policy(
+        state: &AppState,
+        payload: TwoFactorPolicyDto,
+    ) -> Result<TwoFactorPolicyDto, AppError> {
+        let mut roles: Vec<Role> = Vec::new();
+        for role in payload.roles {
+            if !roles.contains(&role) {
+                roles.push(role);
+            }
+        }
+
+        let txn = state.conn.begin().await?;
+
+        TwoFactorPolicy::delete_many().exec(&txn).await?;
+
+        if !roles.is_empty() {
+            TwoFactorPolicy::insert_many(roles.iter().map(|role| two_factor_policy::ActiveModel {
+                role: Set(role.clone()),
+            }))
+            .exec(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(TwoFactorPolicyDto { roles })
+    }
+
+    /// Drops the roles that the policy requires 2FA for if the user has not
+    /// enabled it yet. Applied whenever tokens are issued.
+    pub async fn apply_policy(
+        state: &AppState,
+        mut user: users::Model,
+    ) -> Result<users::Model, AppError> {
+        let policy_roles: Vec<Role> = TwoFactorPolicy::find()
+            .filter(two_factor_policy::Column::Role.is_in(user.roles.clone()))
+            .all(&state.conn)
+            .await?
+            .into_iter()
+            .map(|policy| policy.role)
+            .collect();
+
+        if policy_roles.is_empty() || Self::find_enabled(&state.conn, user.id).await?.is_some() {
+            return Ok(user);
+        }
+
+        user.roles.retain(|role| !policy_roles.contains(role));
+
+        Ok(user)
+    }
+
+    /// If the user has 2FA enabled, returns a second login step id instead
+    /// of tokens.
+    pub async fn challenge(
+        state: &AppState,
+        user: &users::Model,
+    ) -> Result<Option<TwoFactorChallengeResponse>, AppError> {
+        if Self::find_enabled(&state.conn, user.id).await?.is_none() {
+            return Ok(None);
+        }
+
+        let challenge_id = Uuid::new_v4();
+        let key = challenge_key(challenge_id);
+
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let _: () = redis::pipe()
+            .atomic()
+            .hset_multiple(
+                &key,
+                &[
+                    ("user_id", user.id.to_string()),
+                    ("attempts", "0".to_string()),
+                ],
+            )
+            .expire(&key, CHALLENGE_TTL_SECONDS)
+            .query_async(&mut redis_con)
+            .await?;
+
+        Ok(Some(TwoFactorChallengeResponse {
+            two_factor_required: true,
+            challenge_id,
+        }))
+    }
+
+    /// Second login step: accepts an authenticator code or a recovery code.
+    pub async fn verify_challenge(
+        state: &AppState,
+        payload: TwoFactorLoginPayload,
+    ) -> Result<users::Model, AppError> {
+        payload.validate()?;
+
+        let key = challenge_key(payload.challenge_id);
+
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let (challenge, attempts): (HashMap<String, String>, i64) = redis::pipe()
+            .hgetall(&key)
+            .hincr(&key, "attempts", 1)
+            .query_async(&mut redis_con)
+            .await?;
+
+        let user_id = challenge
+            .get("user_id")
+            .and_then(|user_id| Uuid::parse_str(user_id).ok());
+
+        let Some(user_id) = user_id else {
+            let _: () = redis_con.del(&key).await?;
+            return Err(AppError::InvalidToken);
+        };
+
+        if attempts > MAX_CHALLENGE_ATTEMPTS {
+            let _: () = redis_con.del(&key).await?;
+            return Err(AppError::InvalidToken);
+        }
+
+        let two_factor = Self::find_enabled(&state.conn, user_id)
+            .await?
+            .ok_or(AppError::InvalidToken)?;
+
+        if !Self::verify_code(state, &two_factor, &payload.code).await? {
+            return Err(AppError::WrongCredentials);
+        }
+
+        let _: () = redis_con.del(&key).await?;
+
+        User::find_by_id(user_id)
+            .filter(users::Column::IsDeleted.eq(false))
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::WrongCredentials)
+    }
+
+    async fn find_enabled<C: ConnectionTrait>(
+        conn: &C,
+        user_id: Uuid,
+    ) -> Result<Option<two_factor::Model>, AppError> {
+        Ok(TwoFactor::find_by_id(user_id)
+            .filter(two_factor::Column::EnabledAt.is_not_null())
+            .one(conn)
+            .await?)
+    }
+
+    async fn is_required(state: &AppState, roles: &[Role]) -> Result<bool, AppError> {
+        let count = TwoFactorPolicy::find()
+            .filter(two_factor_policy::Column::Role.is_in(roles.to_vec()))
+            .count(&state.conn)
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    async fn verify_code(
+        state: &AppState,
+        two_factor: &two_factor::Model,
+        code: &str,
+    ) -> Result<bool, AppError> {
+        if is_totp_code(code) {
+            Self::verify_totp(state, two_factor, code).await
+        } else {
+            Self::use_recovery_code(state, two_factor.user_id, code).await
+        }
+    }
+
+    /// Verifies a TOTP code. Each time step is accepted only once.
+    async fn verify_totp(
+        state: &AppState,
+        two_factor: &two_factor::Model,
+        code: &str,
+    ) -> Result<bool, AppError> {
+        let totp = build_totp(&two_factor.secret, "")?;
+
+        let Some(step) = totp.check_current(code) else {
+            return Ok(false);
+        };
+
+        let mut redis_con = state
+            .redis_client
+            .get_multiplexed_async_connection()
+            .await?;
+
+        let is_first_use: bool = redis::cmd("SET")
+            .arg(format!(
+                "{}:{}:{}",
+                TOTP_USED_PREFIX, two_factor.user_id, step
+            ))
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(TOTP_USED_TTL_SECONDS)
+            .query_async::<Option<String>>(&mut redis_con)
+            .await?
+            .is_some();
+
+        Ok(is_first_use)
+    }
+
+    async fn use_recovery_code(
+        state: &AppState,
+        user_id: Uuid,
+        code: &str,
+    ) -> Result<bool, AppError> {
+        let code = normalize_recovery_code(code);
+
+        let recovery_codes = RecoveryCode::find()
+            .filter(recovery_code::Column::UserId.eq(user_id))
+            .filter(recovery_code::Column::UsedAt.is_null())
+            .all(&state.conn)
+            .await?;
+
+        let Some(recovery_code) = recovery_codes
+            .into_iter()
+            .find(|recovery_code| verify_password(&recovery_code.code_hash, &code))
+        else {
+            return Ok(false);
+        };
+
+        let result = RecoveryCode::update_many()
+            .col_expr(recovery_code::Column::UsedAt, Expr::value(Local::now()))
+            .filter(recovery_code::Column::Id.eq(recovery_code.id))
+            .filter(recovery_code::Column::UsedAt.is_null())
+            .exec(&state.conn)
+            .await?;
+
+        Ok(result.rows_affected == 1)
+    }
+
+    async fn replace_recovery_codes<C: ConnectionTrait>(
+        conn: &C,
+        user_id: Uuid,
+    ) -> Result<Vec<String>, AppError> {
+        RecoveryCode::delete_many()
+            .filter(recovery_code::Column::UserId.eq(user_id))
+            .exec(conn)
+            .await?;
+
+        let recovery_codes: Vec<String> = (0..RECOVERY_CODES_COUNT)
+            .map(|_| generate_recovery_code())
+            .collect();
+
+        let models = recovery_codes
+            .iter()
+            .map(|code| {
+                Ok(recovery_code::ActiveModel {
+                    user_id: Set(user_id),
+                    code_hash: Set(hash_password(&normalize_recovery_code(code))?),
+                    ..Default::default()
+                })
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        RecoveryCode::insert_many(models).exec(conn).await?;
+
+        Ok(recovery_codes)
+    }
+
+    async fn remove(state: &AppState, user_id: Uuid) -> Result<(), AppError> {
+        let txn = state.conn.begin().await?;
+
+        RecoveryCode::delete_many()
+            .filter(recovery_code::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        TwoFactor::delete_by_id(user_id).exec(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+}
@@ -1,32 +1,124 @@
 use crate::{
     AppState,
     dtos::{
-        common::PaginationParams,
+        common::{PagedResponse, PaginationParams},
         profile::UserDto,
-        user::{UserCreatePayload, UserUpdatePayload},
+        user::{UserCreatePayload, UserFilterParams, UserSortField, UserUpdatePayload},
     },
     error::AppError,
-    utils::security::hash_password,
+    services::{authorizer::Authorizer, session::SessionService},
+    utils::security::{Claims, hash_password},
+};
+use entity::{
+    permission::Permission,
+    users::{self, Entity as User},
 };
-use entity::users::{self, Entity as User};
 use sea_orm::{
-    ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, IntoActiveModel, PaginatorTrait,
-    QueryFilter, QueryOrder, prelude::Uuid,
+    ActiveEnum, ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait, Condition, EntityTrait, ExprTrait, IntoActiveModel, Order, PaginatorTrait,
+    QueryFilter, QueryOrder, Select,
+    prelude::Uuid,
+    sea_query::{Expr, Func, NullOrdering, extension::postgres::PgBinOper},
 };
 use serde_json::json;
+use validator::Validate;
 
 pub struct UserService;
 
 impl UserService {
-    pub async fn get_all(state: &AppState, pagination: PaginationParams) -> Vec<UserDto> {
-        User::find()
-            .filter(users::Column::IsDeleted.eq(false))
-            .order_by_desc(users::Column::CreatedAt)
+    /// Column behind a sort field and whether it is nullable; nulls sort last.
+    pub fn sort_column(field: UserSortField) -> (users::Column, bool) {
+        match field {
+            UserSortField::CreatedAt => (users::Column::CreatedAt, false),
+            UserSortField::Email => (users::Column::Email, false),
+            UserSortField::LastName => (users::Column::LastName, false),
+            UserSortField::FirstName => (users::Column::FirstName, false),
+            UserSortField::StudyGroup => (users::Column::StudyGroup, true),
+            UserSortField::Telephone => (users::Column::Telephone, true),
+        }
+    }
+
+    pub fn apply_filters(mut query: Select<User>, filters: &UserFilterParams) -> Select<User> {
+        query = query.filter(users::Column::IsDeleted.eq(filters.is_deleted));
+
+        if !filters.roles.is_empty() {
+            let roles: Vec<String> = filters.roles.iter().map(ActiveEnum::to_value).collect();
+            query = query.filter(
+                Expr::col(users::Column::Roles)
+                    .binary(PgBinOper::Overlap, Expr::val(roles).cast_as("varchar[]")),
+            );
+        }
+        if let Some(study_group) = filters.study_group.as_deref().map(str::trim)
+            && !study_group.is_empty()
+        {
+            query = query.filter(
+                Expr::expr(Func::lower(Expr::col(users::Column::StudyGroup)))
+                    .eq(study_group.to_lowercase()),
+            );
+        }
+        if let Some(created_from) = filters.created_from {
+            query = query.filter(users::Column::CreatedAt.gte(created_from));
+        }
+        if let Some(created_to) = filters.created_to {
+            query = query.filter(users::Column::CreatedAt.lte(created_to));
+        }
+        if let Some(search) = filters.search.as_deref().map(str::trim)
+            && !search.is_empty()
+        {
+            let pattern = format!(
+                "%{}%",
+                search
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            );
+            query = query.filter(
+                Condition::any()
+                    .add(users::Column::Email.ilike(&pattern))
+                    .add(users::Column::FirstName.ilike(&pattern))
+                    .add(users::Column::LastName.ilike(&pattern))
+                    .add(users::Column::StudyGroup.ilike(&pattern))
+                    .add(users::Column::Telephone.ilike(&pattern)),
+            );
+        }
+
+        let order: Order = filters.sort_order.into();
+        query = match Self::sort_column(filters.sort_by) {
+            (column, true) => query.order_by_with_nulls(column, order, NullOrdering::Last),
+            (column, false) => query.order_by(column, order),
+        };
+
+        // Tie-breaker so that pages stay stable between requests.
+        query.order_by_asc(users::Column::Id)
+    }
+
+    /// Deleted users are listed only with `user:manage:any`.
+    pub async fn get_all(
+        state: &AppState,
+        claims: &Claims,
+        filters: UserFilterParams,
+        pagination: PaginationParams,
+    ) -> Result<PagedResponse<UserDto>, AppError> {
+        pagination.validate()?;
+
+        if filters.is_deleted {
+            Authorizer::require(state, claims, Permission::UserManageAny).await?;
+        }
+
+        let paginator = Self::apply_filters(User::find(), &filters)
             .into_partial_model()
-            .paginate(&state.conn, pagination.page_size)
-            .fetch_page(pagination.page)
-            .await
-            .unwrap_or_default()
+            .paginate(&state.conn, pagination.page_size);
+
+        let total = paginator.num_items().await?;
+        let users = paginator.fetch_page(pagination.page).await?;
+
+        Ok(PagedResponse {
+            items: users,
+            total,
+            page: pagination.page,
+            page_size: pagination.page_size,
+        })
     }
     pub async fn get_one(state: &AppState, id: Uuid) -> Result<UserDto, AppError> {
         User::find_by_id(id)
@@ -47,6 +139,11 @@ impl UserService {
         Ok(())
     }
     pub async fn update(state: &AppState, payload: UserUpdatePayload) -> Result<(), AppError> {
+        let current = User::find_by_id(payload.id)
+            .one(&state.conn)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
         let mut user =
             users::ActiveModel::from_json(json!(payload)).map_err(|_| AppError::BadRequest)?;
 
@@ -54,6 +151,10 @@ impl UserService {
 
         user.update(&state.conn).await?;
 
+        if current.roles != payload.roles {
+            SessionService::invalidate_tokens(state, payload.id).await?;
+        }
+
         Ok(())
     }
     pub async fn restore(state: &AppState, email: String) -> Result<(), AppError> {
@@ -80,6 +181,9 @@ impl UserService {
 
         user.update(&state.conn).await?;
 
+        SessionService::invalidate_tokens(state, id).await?;
+        SessionService::revoke_all(state, id).await?;
+
         Ok(())
     }
 }
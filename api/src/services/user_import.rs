@@ -0,0 +1,475 @@
+use crate::{
+    AppState,
+    dtos::{
+        invitation::InvitationPayload,
+        user::{
+            UserImportMode, UserImportParams, UserImportResponse, UserImportRow, UserImportRowError,
+        },
+    },
+    error::AppError,
+    services::{authorizer::Authorizer, invitation::InvitationService},
+    utils::{
+        security::{Claims, generate_password, hash_password},
+        spreadsheet::{SpreadsheetFormat, SpreadsheetRow, read_rows},
+    },
+};
+use chrono::Local;
+use entity::{
+    invitation::{self, Entity as Invitation},
+    permission::Permission,
+    role::Role,
+    users::{self, Entity as User},
+};
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, EntityTrait, Iterable, QueryFilter, QuerySelect, SqlErr,
+    TransactionTrait,
+};
+use std::collections::{BTreeMap, HashMap};
+use validator::Validate;
+
+/// How many users are inserted per query.
+const INSERT_CHUNK_SIZE: usize = 500;
+
+#[derive(Clone, Copy)]
+enum Column {
+    Email,
+    FirstName,
+    LastName,
+    StudyGroup,
+    Telephone,
+    Roles,
+}
+
+impl Column {
+    const ALL: [Column; 6] = [
+        Column::Email,
+        Column::FirstName,
+        Column::LastName,
+        Column::StudyGroup,
+        Column::Telephone,
+        Column::Roles,
+    ];
+
+    /// Header name in the file, compared case-insensitively.
+    fn name(self) -> &'static str {
+        match self {
+            Column::Email => "email",
+            Column::FirstName => "first_name",
+            Column::LastName => "last_name",
+            Column::StudyGroup => "study_group",
+            Column::Telephone => "telephone",
+            Column::Roles => "roles",
+        }
+    }
+
+    fn is_required(self) -> bool {
+        !matches!(self, Column::StudyGroup | Column::Telephone)
+    }
+}
+
+/// File column indices in `Column::ALL` order.
+struct ColumnIndices([Option<usize>; Column::ALL.len()]);
+
+impl ColumnIndices {
+    fn parse(header: &[String]) -> Result<Self, AppError> {
+        let indices = Column::ALL.map(|column| {
+            header
+                .iter()
+                .position(|cell| cell.eq_ignore_ascii_case(column.name()))
+        });
+
+        let missing: Vec<&str> = Column::ALL
+            .into_iter()
+            .filter(|column| column.is_required() && indices[*column as usize].is_none())
+            .map(Column::name)
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(AppError::Custom(format!(
+                "В файле нет колонок: {}",
+                missing.join(", ")
+            )));
+        }
+
+        Ok(Self(indices))
+    }
+
+    fn get<'a>(&self, cells: &'a [String], column: Column) -> &'a str {
+        self.0[column as usize]
+            .and_then(|index| cells.get(index))
+            .map_or("", String::as_str)
+    }
+}
+
+/// Accepts a role in any case, with or without `_`: `ProjectOffice`,
+/// `PROJECT_OFFICE`, `project office`.
+fn parse_role(value: &str) -> Option<Role> {
+    let normalized: String = value
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_uppercase)
+        .collect();
+
+    Role::iter().find(|role| role.to_string() == normalized)
+}
+
+fn optional(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn add_error(errors: &mut BTreeMap<String, Vec<String>>, field: &str, message: String) {
+    errors.entry(field.to_string()).or_default().push(message);
+}
+
+type ParsedRow = (usize, UserImportRow, BTreeMap<String, Vec<String>>);
+
+/// Adds the errors that depend on other rows and on the database state:
+/// repeated emails, `existing_users` and `invited` emails. Rows without errors
+/// are returned for import.
+fn collect_errors(
+    rows: Vec<ParsedRow>,
+    existing_users: &[String],
+    invited: &[String],
+) -> (Vec<UserImportRow>, Vec<UserImportRowError>) {
+    let mut first_rows: HashMap<String, usize> = HashMap::new();
+    let mut valid_rows = Vec::new();
+    let mut row_errors = Vec::new();
+
+    for (number, row, mut errors) in rows {
+        if !row.email.is_empty() {
+            if let Some(first_row) = first_rows.get(&row.email) {
+                add_error(
+                    &mut errors,
+                    "email",
+                    format!("Email уже указан в строке {}", first_row),
+                );
+            } else {
+                first_rows.insert(row.email.clone(), number);
+            }
+        }
+        if existing_users.contains(&row.email) {
+            add_error(
+                &mut errors,
+                "email",
+                "Пользователь с таким email уже существует".to_string(),
+            );
+        }
+        if invited.contains(&row.email) {
+            add_error(
+                &mut errors,
+                "email",
+                "Приглашение на этот email уже отправлено".to_string(),
+            );
+        }
+
+        if errors.is_empty() {
+            valid_rows.push(row);
+        } else {
+            row_errors.push(UserImportRowError {
+                row: number,
+                email: row.email,
+                errors,
+            });
+        }
+    }
+
+    (valid_rows, row_errors)
+}
+
+pub struct UserImportService;
+
+impl UserImportService {
+    /// Checks every row of the file and, if there are no errors and this is not
+    /// a `dry_run`, creates the users or sends them invitations. If any row has
+    /// errors, nothing is imported.
+    pub async fn import(
+        state: &AppState,
+        claims: &Claims,
+        params: UserImportParams,
+        file_name: Option<&str>,
+        bytes: &[u8],
+    ) -> Result<UserImportResponse, AppError> {
+        if params.mode == UserImportMode::Invite {
+            Authorizer::require(state, claims, Permission::InvitationSend).await?;
+        }
+
+        let format = SpreadsheetFormat::detect(file_name, bytes).ok_or_else(|| {
+            AppError::Custom("Поддерживаются только файлы CSV и XLSX".to_string())
+        })?;
+
+        let mut rows = read_rows(format, bytes)?.into_iter();
+        let header = rows
+            .next()
+            .ok_or_else(|| AppError::Custom("Файл не содержит строк".to_string()))?;
+        let columns = ColumnIndices::parse(&header.cells)?;
+
+        let rows: Vec<ParsedRow> = rows
+            .map(|row| {
+                let (parsed, errors) = Self::parse_row(&columns, &row);
+                (row.number, parsed, errors)
+            })
+            .collect();
+        let total = rows.len();
+
+        let (rows, mut errors) = Self::check_rows(state, params.mode, rows).await?;
+
+        let valid = rows.len();
+        let mut imported = 0;
+
+        if errors.is_empty() && !params.dry_run {
+            imported = match params.mode {
+                UserImportMode::Create => Self::create_users(state, rows).await?,
+                UserImportMode::Invite => Self::invite_users(state, claims, rows).await?,
+            };
+        }
+
+        errors.sort_by_key(|error| error.row);
+
+        Ok(UserImportResponse {
+            total,
+            valid,
+            imported,
+            dry_run: params.dry_run,
+            errors,
+        })
+    }
+
+    fn parse_row(
+        columns: &ColumnIndices,
+        row: &SpreadsheetRow,
+    ) -> (UserImportRow, BTreeMap<String, Vec<String>>) {
+        let mut errors = BTreeMap::new();
+
+        let mut roles = Vec::new();
+        for value in columns
+            .get(&row.cells, Column::Roles)
+            .split([',', ';'])
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+        {
+            match parse_role(value) {
+                Some(role) if roles.contains(&role) => {}
+                Some(role) => roles.push(role),
+                None => add_error(&mut errors, "roles", format!("Неизвестная роль: {}", value)),
+            }
+        }
+
+        let parsed = UserImportRow {
+            email: columns.get(&row.cells, Column::Email).to_lowercase(),
+            first_name: columns.get(&row.cells, Column::FirstName).to_string(),
+            last_name: columns.get(&row.cells, Column::LastName).to_string(),
+            study_group: optional(columns.get(&row.cells, Column::StudyGroup)),
+            telephone: optional(columns.get(&row.cells, Column::Telephone)),
+            roles,
+        };
+
+        if let Err(validation_errors) = parsed.validate() {
+            for (field, field_errors) in validation_errors.field_errors() {
+                for error in field_errors {
+                    if let Some(message) = &error.message {
+                        add_error(&mut errors, &field, message.to_string());
+                    }
+                }
+            }
+        }
+
+        (parsed, errors)
+    }
+
+    /// Looks up existing users and active invitations for the emails of the
+    /// file and adds the errors of `collect_errors`.
+    async fn check_rows(
+        state: &AppState,
+        mode: UserImportMode,
+        rows: Vec<ParsedRow>,
+    ) -> Result<(Vec<UserImportRow>, Vec<UserImportRowError>), AppError> {
+        let emails: Vec<String> = rows.iter().map(|(_, row, _)| row.email.clone()).collect();
+
+        let existing_users: Vec<String> = User::find()
+            .select_only()
+            .column(users::Column::Email)
+            .filter(users::Column::Email.is_in(emails.clone()))
+            .into_tuple()
+            .all(&state.conn)
+            .await?;
+
+        let invited: Vec<String> = if mode == UserImportMode::Invite {
+            Invitation::find()
+                .select_only()
+                .column(invitation::Column::Email)
+                .filter(invitation::Column::Email.is_in(emails))
+                .filter(invitation::Column::ExpiryDate.gt(Local::now()))
+                .into_tuple()
+                .all(&state.conn)
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(collect_errors(rows, &existing_users, &invited))
+    }
+
+    /// Nobody knows the password of imported users: they set one via password
+    /// reset. The hash is computed once for the whole file. A user registered
+    /// with one of the emails after `check_rows` makes the whole import fail.
+    async fn create_users(state: &AppState, rows: Vec<UserImportRow>) -> Result<usize, AppError> {
+        let password = hash_password(&generate_password())?;
+        let count = rows.len();
+        let mut rows = rows.into_iter().peekable();
+
+        let txn = state.conn.begin().await?;
+
+        while rows.peek().is_some() {
+            let chunk = rows
+                .by_ref()
+                .take(INSERT_CHUNK_SIZE)
+                .map(|row| users::ActiveModel {
+                    email: Set(row.email),
+                    password: Set(password.clone()),
+                    first_name: Set(row.first_name),
+                    last_name: Set(row.last_name),
+                    study_group: Set(row.study_group),
+                    telephone: Set(row.telephone),
+                    roles: Set(row.roles),
+                    ..Default::default()
+                });
+
+            User::insert_many(chunk)
+                .exec(&txn)
+                .await
+                .map_err(|err| match err.sql_err() {
+                    Some(SqlErr::UniqueConstraintViolation(_)) => AppError::Custom(
+                        "Пользователь с одним из email уже зарегистрирован, повторите импорт"
+                            .to_string(),
+                    ),
+                    _ => err.into(),
+                })?;
+        }
+
+        txn.commit().await?;
+
+        Ok(count)
+    }
+
+    /// Invitations are sent in groups sharing the same set of roles.
+    async fn invite_users(
+        state: &AppState,
+        claims: &Claims,
+        rows: Vec<UserImportRow>,
+    ) -> Result<usize, AppError> {
+        let mut groups: Vec<(Vec<Role>, Vec<String>)> = Vec::new();
+        for row in rows {
+            match groups.iter_mut().find(|(roles, _)| *roles == row.roles) {
+                Some((_, emails)) => emails.push(row.email),
+                None => groups.push((row.roles, vec![row.email])),
+            }
+        }
+
+        let mut invited = 0;
+        for (roles, emails) in groups {
+            invited += InvitationService::send_invitations(
+                state,
+                claims.clone(),
+                InvitationPayload { emails, roles },
+            )
+            .await?;
+        }
+
+        Ok(invited)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn row(number: usize, email: &str) -> ParsedRow {
+        let row = UserImportRow {
+            email: email.to_string(),
+            first_name: "Иван".to_string(),
+            last_name: "Иванов".to_string(),
+            study_group: None,
+            telephone: None,
+            roles: vec![Role::Initiator],
+        };
+        (number, row, BTreeMap::new())
+    }
+
+    #[test]
+    fn header_is_matched_case_insensitively_in_any_order() {
+        let columns = ColumnIndices::parse(&header(&[
+            "Roles",
+            "LAST_NAME",
+            "telephone",
+            "Email",
+            "first_name",
+        ]))
+        .unwrap();
+        let cells = header(&["ADMIN", "Иванов", "+7 900", "a@b.ru", "Иван"]);
+
+        assert_eq!(columns.get(&cells, Column::Email), "a@b.ru");
+        assert_eq!(columns.get(&cells, Column::Roles), "ADMIN");
+        assert_eq!(columns.get(&cells, Column::Telephone), "+7 900");
+        assert_eq!(columns.get(&cells, Column::StudyGroup), "");
+    }
+
+    #[test]
+    fn header_without_required_columns_is_rejected() {
+        let result = ColumnIndices::parse(&header(&["email", "first_name", "study_group"]));
+
+        match result {
+            Err(AppError::Custom(message)) => {
+                assert_eq!(message, "В файле нет колонок: last_name, roles")
+            }
+            _ => panic!("missing columns were not reported"),
+        }
+    }
+
+    #[test]
+    fn roles_are_normalised() {
+        for value in ["ProjectOffice", "PROJECT_OFFICE", "project office"] {
+            assert_eq!(parse_role(value), Some(Role::ProjectOffice));
+        }
+        assert_eq!(parse_role("team-leader"), Some(Role::TeamLeader));
+        assert_eq!(parse_role("admin"), Some(Role::Admin));
+        assert_eq!(parse_role("manager"), None);
+        assert_eq!(parse_role(""), None);
+    }
+
+    #[test]
+    fn repeated_and_taken_emails_are_reported() {
+        let rows = vec![
+            row(2, "a@b.ru"),
+            row(3, "c@d.ru"),
+            row(4, "a@b.ru"),
+            row(5, "taken@b.ru"),
+            row(6, "invited@b.ru"),
+        ];
+
+        let (valid, errors) = collect_errors(
+            rows,
+            &["taken@b.ru".to_string()],
+            &["invited@b.ru".to_string()],
+        );
+
+        let valid: Vec<&str> = valid.iter().map(|row| row.email.as_str()).collect();
+        assert_eq!(valid, ["a@b.ru", "c@d.ru"]);
+
+        let errors: Vec<(usize, &str)> = errors
+            .iter()
+            .map(|error| (error.row, error.errors["email"][0].as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (4, "Email уже указан в строке 2"),
+                (5, "Пользователь с таким email уже существует"),
+                (6, "Приглашение на этот email уже отправлено"),
+            ]
+        );
+    }
+}
@@ -1,3 +1,8 @@
+pub mod jwt_keys;
+pub mod rate_limit;
 pub mod security;
 pub mod smtp;
+pub mod spreadsheet;
 pub mod startup;
+#[cfg(test)]
+pub mod testing;
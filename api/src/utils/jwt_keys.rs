@@ -0,0 +1,145 @@
+use aws_lc_rs::signature::{Ed25519KeyPair, KeyPair};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse,
+    },
+};
+use std::{fs, path::Path};
+
+/// JWT signing key. Keys loaded from PEM files have a `kid` and a public part
+/// for JWKS; the `JWT_SECRET` key has no `kid` and is never published.
+pub struct JwtKey {
+    pub kid: Option<String>,
+    pub algorithm: Algorithm,
+    pub encoding_key: EncodingKey,
+    pub decoding_key: DecodingKey,
+    jwk: Option<Jwk>,
+}
+
+/// Key set: tokens are signed with the active key and verified with any loaded
+/// key, so the key can be rotated without logging users out.
+pub struct JwtKeys {
+    active: usize,
+    keys: Vec<JwtKey>,
+}
+
+impl JwtKeys {
+    /// Loads every `<kid>.pem` from `keys_dir` (RSA or Ed25519 private keys) and
+    /// the symmetric `secret` if set. Signs with `active_kid`, or with `secret`
+    /// when there is no key directory.
+    pub fn load(
+        secret: Option<String>,
+        keys_dir: Option<String>,
+        active_kid: Option<String>,
+    ) -> Self {
+        let mut keys = Vec::new();
+
+        if let Some(keys_dir) = keys_dir {
+            let entries =
+                fs::read_dir(&keys_dir).expect("JWT_KEYS_DIR must be a readable directory");
+
+            for entry in entries {
+                let path = entry.expect("JWT_KEYS_DIR must be readable").path();
+
+                if path.extension().is_some_and(|ext| ext == "pem") {
+                    keys.push(load_pem_key(&path));
+                }
+            }
+
+            keys.sort_by(|a, b| a.kid.cmp(&b.kid));
+        }
+
+        let active = match active_kid {
+            Some(kid) => keys
+                .iter()
+                .position(|key| key.kid.as_deref() == Some(kid.as_str()))
+                .expect("JWT_ACTIVE_KID must match a key in JWT_KEYS_DIR"),
+            None if keys.is_empty() => 0,
+            None => panic!("JWT_ACTIVE_KID must be set when JWT_KEYS_DIR is used"),
+        };
+
+        if let Some(secret) = secret {
+            keys.push(JwtKey {
+                kid: None,
+                algorithm: Algorithm::HS256,
+                encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+                decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+                jwk: None,
+            });
+        }
+
+        assert!(!keys.is_empty(), "JWT_SECRET or JWT_KEYS_DIR must be set");
+
+        Self { active, keys }
+    }
+
+    pub fn active(&self) -> &JwtKey {
+        &self.keys[self.active]
+    }
+
+    pub fn find(&self, kid: Option<&str>) -> Option<&JwtKey> {
+        self.keys.iter().find(|key| key.kid.as_deref() == kid)
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.keys.iter().filter_map(|key| key.jwk.clone()).collect(),
+        }
+    }
+}
+
+fn load_pem_key(path: &Path) -> JwtKey {
+    let kid = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .expect("JWT key file name must be a valid kid")
+        .to_string();
+
+    let bytes = fs::read(path).expect("JWT key file must be readable");
+
+    let (algorithm, encoding_key, algorithm_parameters) =
+        if let Ok(encoding_key) = EncodingKey::from_rsa_pem(&bytes) {
+            let jwk = Jwk::from_encoding_key(&encoding_key, Algorithm::RS256)
+                .expect("JWT RSA key must be a valid private key");
+            (Algorithm::RS256, encoding_key, jwk.algorithm)
+        } else if let Ok(encoding_key) = EncodingKey::from_ed_pem(&bytes) {
+            let der = pem::parse(&bytes).expect("JWT key file must be a PEM");
+            let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der.contents())
+                .expect("JWT Ed25519 key must be a PKCS#8 private key");
+
+            let parameters = AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x: URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref()),
+            });
+            (Algorithm::EdDSA, encoding_key, parameters)
+        } else {
+            panic!("JWT key {} must be an RSA or Ed25519 private key", kid);
+        };
+
+    let jwk = Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(match algorithm {
+                Algorithm::EdDSA => KeyAlgorithm::EdDSA,
+                _ => KeyAlgorithm::RS256,
+            }),
+            key_id: Some(kid.clone()),
+            ..Default::default()
+        },
+        algorithm: algorithm_parameters,
+    };
+
+    let decoding_key = DecodingKey::from_jwk(&jwk).expect("JWT public key must be valid");
+
+    JwtKey {
+        kid: Some(kid),
+        algorithm,
+        encoding_key,
+        decoding_key,
+        jwk: Some(jwk),
+    }
+}
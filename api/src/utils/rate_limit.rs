@@ -0,0 +1,128 @@
+use crate::{AppState, error::AppError, utils::security::ClientInfo};
+use axum::{
+    Extension,
+    body::{Body, to_bytes},
+    extract::{RawPathParams, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+use redis::Script;
+use sea_orm::prelude::Uuid;
+use std::sync::LazyLock;
+
+const RATE_LIMIT_PREFIX: &str = "rate_limit";
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Sliding window over a sorted set: every hit is stored with its timestamp in
+/// milliseconds. Returns `0` if the request is allowed, otherwise how many
+/// milliseconds remain until the window has room again.
+static SLIDING_WINDOW_SCRIPT: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+        local now = tonumber(ARGV[1])
+        local window = tonumber(ARGV[2])
+        local limit = tonumber(ARGV[3])
+        redis.call('ZREMRANGEBYSCORE', KEYS[1], 0, now - window)
+        if redis.call('ZCARD', KEYS[1]) >= limit then
+            local oldest = redis.call('ZRANGE', KEYS[1], 0, 0, 'WITHSCORES')
+            return tonumber(oldest[2]) + window - now
+        end
+        redis.call('ZADD', KEYS[1], now, ARGV[4])
+        redis.call('PEXPIRE', KEYS[1], window)
+        return 0
+        ",
+    )
+});
+
+/// Request budget for a route: at most `per_ip` requests per IP and `per_email`
+/// requests per email within `window_secs` seconds.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub scope: &'static str,
+    pub window_secs: u64,
+    pub per_ip: Option<u32>,
+    pub per_email: Option<u32>,
+}
+
+pub async fn rate_limit(
+    State(limit): State<RateLimit>,
+    Extension(state): Extension<AppState>,
+    client: ClientInfo,
+    path_params: RawPathParams,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if let (Some(max_requests), Some(ip)) = (limit.per_ip, &client.ip) {
+        check(&state, &limit, "ip", ip, max_requests).await?;
+    }
+
+    let request = match limit.per_email {
+        Some(max_requests) => {
+            let path_email = path_params
+                .iter()
+                .find(|(name, _)| *name == "email" || *name == "new_email")
+                .map(|(_, value)| value.to_string());
+
+            let (email, request) = match path_email {
+                Some(email) => (Some(email), request),
+                None => body_email(request).await?,
+            };
+
+            if let Some(email) = email {
+                check(&state, &limit, "email", &email.to_lowercase(), max_requests).await?;
+            }
+
+            request
+        }
+        None => request,
+    };
+
+    Ok(next.run(request).await)
+}
+
+async fn check(
+    state: &AppState,
+    limit: &RateLimit,
+    kind: &str,
+    value: &str,
+    max_requests: u32,
+) -> Result<(), AppError> {
+    let mut redis_con = state
+        .redis_client
+        .get_multiplexed_async_connection()
+        .await?;
+
+    let retry_after_ms: u64 = SLIDING_WINDOW_SCRIPT
+        .key(format!(
+            "{}:{}:{}:{}",
+            RATE_LIMIT_PREFIX, limit.scope, kind, value
+        ))
+        .arg(Utc::now().timestamp_millis())
+        .arg(limit.window_secs * 1000)
+        .arg(max_requests)
+        .arg(Uuid::new_v4().to_string())
+        .invoke_async(&mut redis_con)
+        .await?;
+
+    if retry_after_ms > 0 {
+        return Err(AppError::TooManyRequests(retry_after_ms.div_ceil(1000)));
+    }
+
+    Ok(())
+}
+
+/// Extracts the `email` field from a JSON body without consuming the body.
+async fn body_email(request: Request) -> Result<(Option<String>, Request), AppError> {
+    let (parts, body) = request.into_parts();
+
+    let bytes = to_bytes(body, MAX_BODY_SIZE)
+        .await
+        .map_err(|_| AppError::BadRequest)?;
+
+    let email = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .and_then(|value| value.get("email")?.as_str().map(str::to_string));
+
+    Ok((email, Request::from_parts(parts, Body::from(bytes))))
+}
@@ -1,23 +1,46 @@
-use crate::{config::GLOBAL_CONFIG, error::AppError};
+use crate::{
+    AppState,
+    config::GLOBAL_CONFIG,
+    error::AppError,
+    services::{
+        api_token::{API_TOKEN_PREFIX, ApiTokenService},
+        session::SessionService,
+    },
+};
 use argon2::{
     Argon2,
-    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
+    password_hash::{
+        PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+        rand_core::{OsRng, RngCore},
+    },
+};
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{
+        HeaderMap,
+        header::{AUTHORIZATION, USER_AGENT},
+        request::Parts,
+    },
 };
-use axum::{extract::FromRequestParts, http::request::Parts};
 use axum_extra::extract::{
     CookieJar,
     cookie::{Cookie, SameSite},
 };
 use chrono::{Duration, Utc};
-use entity::role::Role;
-use jsonwebtoken::{Header, Validation, decode, encode};
+use entity::{role::Role, users};
+use jsonwebtoken::{Header, Validation, decode, decode_header, encode};
 use sea_orm::prelude::Uuid;
 use serde::{Deserialize, Serialize};
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub enum TokenType {
     Access,
     Refresh,
+    Api,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -30,96 +53,272 @@ pub struct Claims {
     pub iat: usize,
     pub token_type: TokenType,
     pub roles: Vec<Role>,
+    /// Role selected via `/auth/role`. Always one of `roles`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_role: Option<Role>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+    #[serde(default)]
+    pub ver: i32,
+}
+
+impl Claims {
+    /// Roles the user is acting with: the selected role if there is one,
+    /// otherwise all of their roles.
+    pub fn effective_roles(&self) -> &[Role] {
+        match &self.active_role {
+            Some(role) => std::slice::from_ref(role),
+            None => &self.roles,
+        }
+    }
+}
+
+impl FromRequestParts<AppState> for Claims {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let bearer_token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|value| value.trim().to_string());
+
+        let access_token = match bearer_token {
+            Some(token) if token.starts_with(API_TOKEN_PREFIX) => {
+                return ApiTokenService::authenticate(state, &token).await;
+            }
+            Some(token) => token,
+            None => CookieJar::from_headers(&parts.headers)
+                .get("access_token")
+                .ok_or(AppError::WrongCredentials)?
+                .value()
+                .to_string(),
+        };
+
+        let claims = decode_token(&access_token, TokenType::Access)?;
+
+        SessionService::validate(state, &claims).await?;
+
+        Ok(claims)
+    }
 }
 
-impl<S> FromRequestParts<S> for Claims
+/// Claims of a login session. API tokens are refused with `403`: account
+/// management (sessions, API tokens, 2FA, email, active role) needs a real login,
+/// otherwise a scoped token could mint new tokens or take over the account.
+pub struct SessionClaims(pub Claims);
+
+impl FromRequestParts<AppState> for SessionClaims {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let claims = Claims::from_request_parts(parts, state).await?;
+
+        if claims.token_type == TokenType::Api {
+            return Err(AppError::Forbidden);
+        }
+
+        Ok(Self(claims))
+    }
+}
+
+/// Client device details stored with the session.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for ClientInfo
 where
     S: Send + Sync,
 {
-    type Rejection = AppError;
+    type Rejection = Infallible;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let jar = CookieJar::from_headers(&parts.headers);
-
-        let access_token = jar
-            .get("access_token")
-            .ok_or(AppError::WrongCredentials)?
-            .value()
-            .to_string();
-
-        let token_data = decode::<Claims>(
-            &access_token,
-            &GLOBAL_CONFIG.decoding_key,
-            &Validation::default(),
-        )
-        .map_err(|_| AppError::InvalidToken)?;
+        let header = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
 
-        if token_data.claims.token_type != TokenType::Access {
-            return Err(AppError::InvalidToken);
-        }
+        let user_agent = header(USER_AGENT.as_str());
+
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| {
+                client_ip(addr.ip().to_canonical(), &parts.headers).to_string()
+            });
 
-        Ok(token_data.claims)
+        Ok(Self { user_agent, ip })
     }
 }
 
+fn is_trusted_proxy(ip: &IpAddr) -> bool {
+    GLOBAL_CONFIG
+        .trusted_proxies
+        .iter()
+        .any(|proxy| proxy.contains(ip))
+}
+
+/// Address of the client behind the connection. Forwarded headers can be set by
+/// the client to anything, so they are only read when the peer is a trusted
+/// proxy. `X-Forwarded-For` is walked from the right, skipping trusted proxies,
+/// because only the entries appended by our own proxies can be relied on.
+fn client_ip(peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+    if !is_trusted_proxy(&peer) {
+        return peer;
+    }
+
+    let forwarded: Vec<IpAddr> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|ip| ip.trim().parse().ok())
+        .collect();
+
+    if let Some(ip) = forwarded.iter().rev().find(|ip| !is_trusted_proxy(ip)) {
+        return *ip;
+    }
+
+    forwarded
+        .first()
+        .copied()
+        .or_else(|| {
+            headers
+                .get("x-real-ip")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+        })
+        .unwrap_or(peer)
+}
+
+pub const ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+pub const REFRESH_TOKEN_TTL_DAYS: i64 = 7;
+
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+impl TokenPair {
+    pub fn into_cookie_jar(self) -> CookieJar {
+        let is_secure: bool = !cfg!(debug_assertions);
+
+        let access_cookie = Cookie::build(("access_token", self.access_token))
+            .path("/")
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            .secure(is_secure)
+            .max_age(time::Duration::minutes(30));
+
+        let refresh_cookie = Cookie::build(("refresh_token", self.refresh_token))
+            .path("/")
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            .secure(is_secure)
+            .max_age(time::Duration::days(30));
+
+        CookieJar::new().add(access_cookie).add(refresh_cookie)
+    }
+}
+
+/// Creates a token pair. `sid` identifies the refresh token family, `jti` a
+/// particular refresh token within it. `active_role` is only put into the
+/// tokens if it is one of the user's roles.
 pub fn generate_tokens(
-    sub: Uuid,
-    email: String,
-    first_name: String,
-    last_name: String,
-    roles: Vec<Role>,
-) -> Result<CookieJar, AppError> {
+    user: &users::Model,
+    sid: Uuid,
+    jti: Uuid,
+    active_role: Option<Role>,
+) -> Result<TokenPair, AppError> {
+    let key = GLOBAL_CONFIG.jwt_keys.active();
+    let mut header = Header::new(key.algorithm);
+    header.kid = key.kid.clone();
+
     let now = Utc::now();
     let iat = now.timestamp() as usize;
-    let exp = (now + Duration::minutes(15)).timestamp() as usize;
+    let exp = (now + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES)).timestamp() as usize;
 
     let claims = Claims {
-        sub: sub.clone(),
-        email: email.clone(),
-        first_name: first_name.clone(),
-        last_name: last_name.clone(),
+        sub: user.id,
+        email: user.email.clone(),
+        first_name: user.first_name.clone(),
+        last_name: user.last_name.clone(),
         iat,
         exp,
         token_type: TokenType::Access,
-        roles: roles.clone(),
+        roles: user.roles.clone(),
+        active_role: active_role.filter(|role| user.roles.contains(role)),
+        jti: None,
+        sid: Some(sid),
+        ver: user.token_version,
     };
 
-    let access_token = encode(&Header::default(), &claims, &GLOBAL_CONFIG.encoding_key)
-        .map_err(|_| AppError::TokenCreation)?;
-
-    let exp = (now + Duration::days(7)).timestamp() as usize;
+    let access_token =
+        encode(&header, &claims, &key.encoding_key).map_err(|_| AppError::TokenCreation)?;
 
     let claims = Claims {
-        sub,
-        email,
-        first_name,
-        last_name,
-        iat,
-        exp,
+        exp: (now + Duration::days(REFRESH_TOKEN_TTL_DAYS)).timestamp() as usize,
         token_type: TokenType::Refresh,
-        roles,
+        jti: Some(jti),
+        ..claims
     };
 
-    let refresh_token = encode(&Header::default(), &claims, &GLOBAL_CONFIG.encoding_key)
-        .map_err(|_| AppError::TokenCreation)?;
+    let refresh_token =
+        encode(&header, &claims, &key.encoding_key).map_err(|_| AppError::TokenCreation)?;
+
+    Ok(TokenPair {
+        access_token,
+        refresh_token,
+    })
+}
 
-    let is_secure: bool = !cfg!(debug_assertions);
+/// Verifies the signature with the key from the `kid` header, the expiry and the token type.
+pub fn decode_token(token: &str, token_type: TokenType) -> Result<Claims, AppError> {
+    let header = decode_header(token).map_err(|_| AppError::InvalidToken)?;
 
-    let access_cookie = Cookie::build(("access_token", access_token))
-        .path("/")
-        .http_only(true)
-        .same_site(SameSite::Lax)
-        .secure(is_secure)
-        .max_age(time::Duration::minutes(30));
+    let key = GLOBAL_CONFIG
+        .jwt_keys
+        .find(header.kid.as_deref())
+        .ok_or(AppError::InvalidToken)?;
 
-    let refresh_cookie = Cookie::build(("refresh_token", refresh_token))
-        .path("/")
-        .http_only(true)
-        .same_site(SameSite::Lax)
-        .secure(is_secure)
-        .max_age(time::Duration::days(30));
+    let token_data = decode::<Claims>(token, &key.decoding_key, &Validation::new(key.algorithm))
+        .map_err(|_| AppError::InvalidToken)?;
+
+    if token_data.claims.token_type != token_type {
+        return Err(AppError::InvalidToken);
+    }
+
+    Ok(token_data.claims)
+}
+
+pub fn refresh_token_from_jar(jar: &CookieJar) -> Result<String, AppError> {
+    Ok(jar
+        .get("refresh_token")
+        .ok_or(AppError::WrongCredentials)?
+        .value()
+        .to_string())
+}
 
-    Ok(CookieJar::new().add(access_cookie).add(refresh_cookie))
+/// Random password for accounts created without a user-chosen password.
+pub fn generate_password() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
 }
 
 pub fn hash_password(password: &str) -> Result<String, AppError> {
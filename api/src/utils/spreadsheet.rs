@@ -0,0 +1,270 @@
+use crate::error::AppError;
+use calamine::{Reader, Xlsx, open_workbook_from_rs};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use serde::Deserialize;
+use std::{
+    fs::File,
+    io::{Cursor, Seek},
+};
+
+/// Signature of the ZIP archive an XLSX file is stored in.
+const ZIP_SIGNATURE: &[u8] = b"PK\x03\x04";
+
+/// BOM at the start of CSV files so that Excel opens them as UTF-8.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SpreadsheetFormat {
+    #[default]
+    Csv,
+    Xlsx,
+}
+
+impl SpreadsheetFormat {
+    /// Detects the format from the file extension, or from the content if there is none.
+    pub fn detect(file_name: Option<&str>, bytes: &[u8]) -> Option<Self> {
+        let extension = file_name
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(_, extension)| extension.to_lowercase());
+
+        match extension.as_deref() {
+            Some("csv") => Some(Self::Csv),
+            Some("xlsx") => Some(Self::Xlsx),
+            Some(_) => None,
+            None if bytes.starts_with(ZIP_SIGNATURE) => Some(Self::Xlsx),
+            None => Some(Self::Csv),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Xlsx => "xlsx",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+}
+
+/// A spreadsheet row. `number` is the line number in the file, starting at 1.
+pub struct SpreadsheetRow {
+    pub number: usize,
+    pub cells: Vec<String>,
+}
+
+/// Reads the whole CSV file or the first XLSX sheet. Cell values are trimmed
+/// and empty rows are skipped.
+pub fn read_rows(format: SpreadsheetFormat, bytes: &[u8]) -> Result<Vec<SpreadsheetRow>, AppError> {
+    let rows = match format {
+        SpreadsheetFormat::Csv => read_csv(bytes),
+        SpreadsheetFormat::Xlsx => read_xlsx(bytes),
+    }
+    .ok_or_else(|| AppError::Custom("Не удалось прочитать файл".to_string()))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(number, cells)| SpreadsheetRow {
+            number,
+            cells: cells.iter().map(|cell| cell.trim().to_string()).collect(),
+        })
+        .filter(|row| row.cells.iter().any(|cell| !cell.is_empty()))
+        .collect())
+}
+
+fn read_csv(bytes: &[u8]) -> Option<Vec<(usize, Vec<String>)>> {
+    // Excel with a Russian locale saves CSV with `;` as the delimiter.
+    let first_line = bytes
+        .split(|byte| *byte == b'\n')
+        .next()
+        .unwrap_or_default();
+    let delimiter = if first_line.contains(&b';') && !first_line.contains(&b',') {
+        b';'
+    } else {
+        b','
+    };
+
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(bytes)
+        .records()
+        .map(|record| {
+            let record = record.ok()?;
+            let position = record.position()?;
+            // The record position points at the blank lines skipped before it.
+            let skipped_lines = bytes[position.byte() as usize..]
+                .iter()
+                .take_while(|byte| matches!(byte, b'\r' | b'\n'))
+                .filter(|byte| **byte == b'\n')
+                .count();
+            let number = position.line() as usize + skipped_lines;
+            Some((number, record.iter().map(str::to_string).collect()))
+        })
+        .collect()
+}
+
+fn read_xlsx(bytes: &[u8]) -> Option<Vec<(usize, Vec<String>)>> {
+    let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(bytes)).ok()?;
+    let range = workbook.worksheet_range_at(0)?.ok()?;
+    let first_row = range.start().map_or(0, |(row, _)| row as usize);
+
+    Some(
+        range
+            .rows()
+            .enumerate()
+            .map(|(index, row)| {
+                (
+                    first_row + index + 1,
+                    row.iter().map(ToString::to_string).collect(),
+                )
+            })
+            .collect(),
+    )
+}
+
+pub enum Cell {
+    Empty,
+    Text(String),
+    Number(f64),
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(value: i64) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl<T: Into<Cell>> From<Option<T>> for Cell {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Empty, Into::into)
+    }
+}
+
+/// Spreadsheet apps evaluate CSV values starting with these characters as
+/// formulas, so such values are prefixed with `'` to keep them as text.
+fn escape_csv_formula(value: String) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value
+    }
+}
+
+fn xlsx_error(e: XlsxError) -> AppError {
+    tracing::error!("XLSX error: {:?}", e);
+    AppError::InternalServerError
+}
+
+/// Writes CSV in batches of rows. What has been written so far is handed out
+/// via `take_chunk`, so the file can be sent to the client while it is written.
+pub struct CsvWriter(csv::Writer<Vec<u8>>);
+
+impl CsvWriter {
+    pub fn new(header: &[&str]) -> Result<Self, AppError> {
+        let mut writer = csv::Writer::from_writer(UTF8_BOM.to_vec());
+        writer
+            .write_record(header)
+            .map_err(|_| AppError::InternalServerError)?;
+        Ok(Self(writer))
+    }
+
+    pub fn write_row(&mut self, cells: Vec<Cell>) -> Result<(), AppError> {
+        self.0
+            .write_record(cells.into_iter().map(|cell| match cell {
+                Cell::Empty => String::new(),
+                Cell::Text(value) => escape_csv_formula(value),
+                Cell::Number(value) => value.to_string(),
+            }))
+            .map_err(|_| AppError::InternalServerError)
+    }
+
+    /// Bytes written since the previous call.
+    pub fn take_chunk(&mut self) -> Result<Vec<u8>, AppError> {
+        std::mem::replace(&mut self.0, csv::Writer::from_writer(Vec::new()))
+            .into_inner()
+            .map_err(|_| AppError::InternalServerError)
+    }
+}
+
+/// Writes the first sheet of an XLSX file. Rows go to temporary files as they
+/// are written; the archive can only be assembled once all of them are there.
+/// Every method does blocking file I/O and belongs in `spawn_blocking`.
+pub struct XlsxWriter {
+    workbook: Box<Workbook>,
+    next_row: u32,
+}
+
+impl XlsxWriter {
+    pub fn new(header: &[&str]) -> Result<Self, AppError> {
+        let mut workbook = Box::new(Workbook::new());
+        let worksheet = workbook.add_worksheet_with_constant_memory();
+        worksheet
+            .write_row_with_format(0, 0, header.iter().copied(), &Format::new().set_bold())
+            .map_err(xlsx_error)?;
+        worksheet.set_freeze_panes(1, 0).map_err(xlsx_error)?;
+
+        Ok(Self {
+            workbook,
+            next_row: 1,
+        })
+    }
+
+    pub fn write_row(&mut self, cells: Vec<Cell>) -> Result<(), AppError> {
+        let worksheet = self.workbook.worksheet_from_index(0).map_err(xlsx_error)?;
+        for (col, cell) in (0..).zip(cells) {
+            match cell {
+                Cell::Empty => {}
+                Cell::Text(value) => {
+                    worksheet
+                        .write_string(self.next_row, col, value)
+                        .map_err(xlsx_error)?;
+                }
+                Cell::Number(value) => {
+                    worksheet
+                        .write_number(self.next_row, col, value)
+                        .map_err(xlsx_error)?;
+                }
+            }
+        }
+        self.next_row += 1;
+        Ok(())
+    }
+
+    /// Saves the workbook to an anonymous temporary file, rewound to the start.
+    /// The file is removed once it is closed.
+    pub fn finish(mut self) -> Result<File, AppError> {
+        let mut file = tempfile::tempfile().map_err(|e| {
+            tracing::error!("Failed to create a temporary file: {:?}", e);
+            AppError::InternalServerError
+        })?;
+
+        self.workbook
+            .save_to_writer(&mut file)
+            .map_err(xlsx_error)?;
+        file.rewind().map_err(|e| {
+            tracing::error!("Failed to rewind the XLSX file: {:?}", e);
+            AppError::InternalServerError
+        })?;
+
+        Ok(file)
+    }
+}
@@ -10,7 +10,7 @@ pub async fn create_admin(db: DbConn) -> Result<(), AppError> {
         .one(&db)
         .await?;
 
-    if let None = user {
+    if user.is_none() {
         let user = users::ActiveModel {
             first_name: Set("Живая".to_owned()),
             last_name: Set("Легенда".to_owned()),
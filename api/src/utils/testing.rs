@@ -0,0 +1,150 @@
+//! Helpers for tests that run against the database and Redis configured in
+//! `.env`.
+
+use crate::{
+    AppState,
+    config::GLOBAL_CONFIG,
+    utils::security::{Claims, TokenType, generate_password, hash_password},
+};
+use entity::{
+    group::{self, Entity as Group},
+    group_member,
+    role::Role,
+    users::{self, Entity as User},
+};
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, Database, DatabaseConnection, EntityTrait,
+    QueryFilter, prelude::Uuid,
+};
+use tokio::{runtime::Handle, sync::OnceCell, task};
+
+static MIGRATED: OnceCell<()> = OnceCell::const_new();
+
+/// Connects to the database, bringing its schema up to date on first use.
+pub async fn test_state() -> AppState {
+    dotenvy::dotenv().ok();
+
+    let conn = Database::connect(GLOBAL_CONFIG.db_url.to_owned())
+        .await
+        .unwrap();
+    MIGRATED
+        .get_or_init(|| async { Migrator::up(&conn, None).await.unwrap() })
+        .await;
+    let redis_client = redis::Client::open(GLOBAL_CONFIG.redis_url.to_owned()).unwrap();
+
+    AppState { conn, redis_client }
+}
+
+/// An email no other test run has used.
+pub fn unique_email(prefix: &str) -> String {
+    format!("{}-{}@example.com", prefix, Uuid::new_v4())
+}
+
+/// Access token claims of `user`, acting with all of their roles.
+pub fn claims_for(user: &users::Model) -> Claims {
+    Claims {
+        sub: user.id,
+        email: user.email.clone(),
+        first_name: user.first_name.clone(),
+        last_name: user.last_name.clone(),
+        exp: 0,
+        iat: 0,
+        token_type: TokenType::Access,
+        roles: user.roles.clone(),
+        active_role: None,
+        jti: None,
+        sid: None,
+        ver: user.token_version,
+    }
+}
+
+/// Users and groups created by a test. They are deleted when the guard is
+/// dropped, also when an assertion fails, and rows referencing them go along
+/// through `ON DELETE CASCADE`. The cleanup blocks in place, so tests using it
+/// run on the multi-threaded runtime.
+pub struct TestUsers {
+    conn: DatabaseConnection,
+    ids: Vec<Uuid>,
+    group_ids: Vec<Uuid>,
+}
+
+impl TestUsers {
+    pub fn new(state: &AppState) -> Self {
+        Self {
+            conn: state.conn.clone(),
+            ids: Vec::new(),
+            group_ids: Vec::new(),
+        }
+    }
+
+    /// Inserts a user with a random password, like accounts created by an admin.
+    pub async fn create(&mut self, email: &str, roles: Vec<Role>) -> users::Model {
+        let user = users::ActiveModel {
+            email: Set(email.to_string()),
+            password: Set(hash_password(&generate_password()).unwrap()),
+            roles: Set(roles),
+            first_name: Set("Иван".to_string()),
+            last_name: Set("Иванов".to_string()),
+            ..Default::default()
+        }
+        .insert(&self.conn)
+        .await
+        .unwrap();
+
+        self.ids.push(user.id);
+        user
+    }
+
+    pub async fn create_group(&mut self, roles: Vec<Role>, members: &[Uuid]) -> group::Model {
+        let group = group::ActiveModel {
+            name: Set(format!("group-{}", Uuid::new_v4())),
+            roles: Set(roles),
+            ..Default::default()
+        }
+        .insert(&self.conn)
+        .await
+        .unwrap();
+        self.group_ids.push(group.id);
+
+        for user_id in members {
+            group_member::ActiveModel {
+                group_id: Set(group.id),
+                user_id: Set(*user_id),
+            }
+            .insert(&self.conn)
+            .await
+            .unwrap();
+        }
+
+        group
+    }
+}
+
+impl Drop for TestUsers {
+    fn drop(&mut self) {
+        if self.ids.is_empty() && self.group_ids.is_empty() {
+            return;
+        }
+
+        let conn = self.conn.clone();
+        let ids = std::mem::take(&mut self.ids);
+        let group_ids = std::mem::take(&mut self.group_ids);
+        let result = task::block_in_place(|| {
+            Handle::current().block_on(async move {
+                Group::delete_many()
+                    .filter(group::Column::Id.is_in(group_ids))
+                    .exec(&conn)
+                    .await?;
+                User::delete_many()
+                    .filter(users::Column::Id.is_in(ids))
+                    .exec(&conn)
+                    .await
+            })
+        });
+
+        if let Err(err) = result {
+            eprintln!("failed to delete test data: {}", err);
+        }
+    }
+}
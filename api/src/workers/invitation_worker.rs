@@ -36,44 +36,40 @@ pub async fn invitation_worker(redis_client: redis::Client) {
                 .await,
         ];
 
-        for result in results {
-            if let Ok(reply) = result {
-                for stream_key in reply.keys {
-                    for msg in stream_key.ids {
-                        let msg_id = &msg.id;
-                        let mut id = String::new();
-                        let mut receiver = String::new();
-                        let mut sender_first_name = String::new();
-                        let mut sender_last_name = String::new();
+        for reply in results.into_iter().flatten() {
+            for stream_key in reply.keys {
+                for msg in stream_key.ids {
+                    let msg_id = &msg.id;
+                    let mut id = String::new();
+                    let mut receiver = String::new();
+                    let mut sender_first_name = String::new();
+                    let mut sender_last_name = String::new();
 
-                        for (field, value) in &msg.map {
-                            if let Ok(value_str) = redis::from_redis_value::<String>(value.clone())
-                            {
-                                match field.as_str() {
-                                    "id" => id = value_str,
-                                    "receiver" => receiver = value_str,
-                                    "sender_first_name" => sender_first_name = value_str,
-                                    "sender_last_name" => sender_last_name = value_str,
-                                    _ => {}
-                                }
+                    for (field, value) in &msg.map {
+                        if let Ok(value_str) = redis::from_redis_value::<String>(value.clone()) {
+                            match field.as_str() {
+                                "id" => id = value_str,
+                                "receiver" => receiver = value_str,
+                                "sender_first_name" => sender_first_name = value_str,
+                                "sender_last_name" => sender_last_name = value_str,
+                                _ => {}
                             }
                         }
-                        let result =
-                            send_invitation(id, sender_first_name, sender_last_name, receiver)
-                                .await;
+                    }
+                    let result =
+                        send_invitation(id, sender_first_name, sender_last_name, receiver).await;
 
-                        if let Err(e) = result {
-                            tracing::error!("Ошибка отправки {}", e);
-                            continue;
-                        }
+                    if let Err(e) = result {
+                        tracing::error!("Ошибка отправки {}", e);
+                        continue;
+                    }
 
-                        let ack_result: Result<i64, _> = redis_con
-                            .xack(INVITATIONS_STREAM_NAME, GROUP_NAME, &[msg_id])
-                            .await;
+                    let ack_result: Result<i64, _> = redis_con
+                        .xack(INVITATIONS_STREAM_NAME, GROUP_NAME, &[msg_id])
+                        .await;
 
-                        if let Err(e) = ack_result {
-                            tracing::error!("Ошибка при ack {}: {}", msg_id, e);
-                        }
+                    if let Err(e) = ack_result {
+                        tracing::error!("Ошибка при ack {}: {}", msg_id, e);
                     }
                 }
             }
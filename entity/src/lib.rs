@@ -2,17 +2,37 @@
 
 pub mod prelude;
 
+pub mod api_token;
 pub mod company;
 pub mod company_member;
 pub mod group;
 pub mod group_member;
 pub mod idea;
 pub mod idea_checked;
+pub mod idea_comment;
 pub mod idea_skill;
 pub mod idea_status;
+pub mod idea_status_history;
 pub mod invitation;
+pub mod market;
+pub mod market_idea;
+pub mod market_idea_request;
+pub mod market_idea_status;
+pub mod market_request_status;
+pub mod market_status;
+pub mod permission;
+pub mod rating;
+pub mod recovery_code;
 pub mod role;
+pub mod role_permission;
 pub mod skill;
 pub mod skill_type;
+pub mod team;
+pub mod team_invitation;
+pub mod team_invitation_status;
+pub mod team_member;
+pub mod two_factor;
+pub mod two_factor_policy;
+pub mod user_skill;
 pub mod users;
 pub mod verification_code;
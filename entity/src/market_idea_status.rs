@@ -0,0 +1,23 @@
+use sea_orm::entity::prelude::*;
+use strum_macros::{Display, EnumString};
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    EnumString,
+    Display,
+    DeriveActiveEnum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum MarketIdeaStatus {
+    #[sea_orm(string_value = "RECRUITMENT_OPEN")]
+    RecruitmentOpen,
+    #[sea_orm(string_value = "RECRUITMENT_CLOSED")]
+    RecruitmentClosed,
+}
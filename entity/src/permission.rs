@@ -0,0 +1,100 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+/// Permissions in the form `resource:action[:scope]`. The `own` scope covers
+/// only the user's own resources, `any` covers all of them.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+pub enum Permission {
+    #[sea_orm(string_value = "idea:read:any")]
+    #[serde(rename = "idea:read:any")]
+    IdeaReadAny,
+    #[sea_orm(string_value = "idea:update:own")]
+    #[serde(rename = "idea:update:own")]
+    IdeaUpdateOwn,
+    #[sea_orm(string_value = "idea:update:any")]
+    #[serde(rename = "idea:update:any")]
+    IdeaUpdateAny,
+    #[sea_orm(string_value = "idea:delete:own")]
+    #[serde(rename = "idea:delete:own")]
+    IdeaDeleteOwn,
+    #[sea_orm(string_value = "idea:delete:any")]
+    #[serde(rename = "idea:delete:any")]
+    IdeaDeleteAny,
+    #[sea_orm(string_value = "idea:submit:own")]
+    #[serde(rename = "idea:submit:own")]
+    IdeaSubmitOwn,
+    #[sea_orm(string_value = "idea:submit:any")]
+    #[serde(rename = "idea:submit:any")]
+    IdeaSubmitAny,
+    #[sea_orm(string_value = "idea:approve")]
+    #[serde(rename = "idea:approve")]
+    IdeaApprove,
+    #[sea_orm(string_value = "idea:return:approval")]
+    #[serde(rename = "idea:return:approval")]
+    IdeaReturnApproval,
+    #[sea_orm(string_value = "idea:confirm")]
+    #[serde(rename = "idea:confirm")]
+    IdeaConfirm,
+    #[sea_orm(string_value = "idea:return:confirmation")]
+    #[serde(rename = "idea:return:confirmation")]
+    IdeaReturnConfirmation,
+    #[sea_orm(string_value = "idea:publish")]
+    #[serde(rename = "idea:publish")]
+    IdeaPublish,
+    #[sea_orm(string_value = "idea_history:read")]
+    #[serde(rename = "idea_history:read")]
+    IdeaHistoryRead,
+    #[sea_orm(string_value = "idea_match:read")]
+    #[serde(rename = "idea_match:read")]
+    IdeaMatchRead,
+    #[sea_orm(string_value = "comment:delete:any")]
+    #[serde(rename = "comment:delete:any")]
+    CommentDeleteAny,
+    #[sea_orm(string_value = "rating:read:any")]
+    #[serde(rename = "rating:read:any")]
+    RatingReadAny,
+    #[sea_orm(string_value = "rating:manage:own")]
+    #[serde(rename = "rating:manage:own")]
+    RatingManageOwn,
+    #[sea_orm(string_value = "skill:manage:any")]
+    #[serde(rename = "skill:manage:any")]
+    SkillManageAny,
+    #[sea_orm(string_value = "group:manage:any")]
+    #[serde(rename = "group:manage:any")]
+    GroupManageAny,
+    #[sea_orm(string_value = "company:manage:own")]
+    #[serde(rename = "company:manage:own")]
+    CompanyManageOwn,
+    #[sea_orm(string_value = "company:manage:any")]
+    #[serde(rename = "company:manage:any")]
+    CompanyManageAny,
+    #[sea_orm(string_value = "team:read:any")]
+    #[serde(rename = "team:read:any")]
+    TeamReadAny,
+    #[sea_orm(string_value = "team:manage:any")]
+    #[serde(rename = "team:manage:any")]
+    TeamManageAny,
+    #[sea_orm(string_value = "market:manage")]
+    #[serde(rename = "market:manage")]
+    MarketManage,
+    #[sea_orm(string_value = "market_request:read:any")]
+    #[serde(rename = "market_request:read:any")]
+    MarketRequestReadAny,
+    #[sea_orm(string_value = "market_request:manage:any")]
+    #[serde(rename = "market_request:manage:any")]
+    MarketRequestManageAny,
+    #[sea_orm(string_value = "invitation:send")]
+    #[serde(rename = "invitation:send")]
+    InvitationSend,
+    #[sea_orm(string_value = "user:manage:any")]
+    #[serde(rename = "user:manage:any")]
+    UserManageAny,
+    #[sea_orm(string_value = "report:export")]
+    #[serde(rename = "report:export")]
+    ReportExport,
+    #[sea_orm(string_value = "role:manage")]
+    #[serde(rename = "role:manage")]
+    RoleManage,
+}
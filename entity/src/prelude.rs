@@ -1,14 +1,29 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 2.0.0-rc.20
 
+pub use super::api_token::Entity as ApiToken;
 pub use super::company::Entity as Company;
 pub use super::company_member::Entity as CompanyMember;
 pub use super::group::Entity as Group;
 pub use super::group_member::Entity as GroupMember;
 pub use super::idea::Entity as Idea;
 pub use super::idea_checked::Entity as IdeaChecked;
+pub use super::idea_comment::Entity as IdeaComment;
 pub use super::idea_skill::Entity as IdeaSkill;
+pub use super::idea_status_history::Entity as IdeaStatusHistory;
 pub use super::invitation::Entity as Invitation;
+pub use super::market::Entity as Market;
+pub use super::market_idea::Entity as MarketIdea;
+pub use super::market_idea_request::Entity as MarketIdeaRequest;
+pub use super::rating::Entity as Rating;
+pub use super::recovery_code::Entity as RecoveryCode;
+pub use super::role::Role;
+pub use super::role_permission::Entity as RolePermission;
 pub use super::skill::Entity as Skill;
+pub use super::team::Entity as Team;
+pub use super::team_invitation::Entity as TeamInvitation;
+pub use super::team_member::Entity as TeamMember;
+pub use super::two_factor::Entity as TwoFactor;
+pub use super::two_factor_policy::Entity as TwoFactorPolicy;
+pub use super::user_skill::Entity as UserSkill;
 pub use super::users::Entity as Users;
 pub use super::verification_code::Entity as VerificationCode;
-pub use super::role::Role;
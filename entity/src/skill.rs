@@ -48,6 +48,8 @@ pub struct Model {
     pub users_1: HasOne<super::users::Entity>,
     #[sea_orm(has_many, via = "idea_skill")]
     pub ideas: HasMany<super::idea::Entity>,
+    #[sea_orm(has_many, via = "user_skill")]
+    pub users: HasMany<super::users::Entity>,
 }
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,8 +1,17 @@
 use sea_orm::entity::prelude::*;
-use strum_macros::{EnumString, Display};
+use strum_macros::{Display, EnumString};
 
 #[derive(
-    Debug, Clone, PartialEq, Eq, EnumIter, EnumString, Display, DeriveActiveEnum, serde::Serialize, serde::Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    EnumString,
+    Display,
+    DeriveActiveEnum,
+    serde::Serialize,
+    serde::Deserialize,
 )]
 #[sea_orm(rs_type = "String", db_type = "Text")]
 #[strum(serialize_all = "UPPERCASE")]
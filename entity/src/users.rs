@@ -20,12 +20,18 @@ pub struct Model {
     pub first_name: String,
     pub created_at: DateTimeWithTimeZone,
     pub is_deleted: bool,
+    pub token_version: i32,
+    pub is_directory_user: bool,
     #[sea_orm(has_many, via = "company_member")]
     pub companies: HasMany<super::company::Entity>,
     #[sea_orm(has_many, via = "group_member")]
     pub groups: HasMany<super::group::Entity>,
     #[sea_orm(has_many, via = "idea_checked")]
     pub ideas: HasMany<super::idea::Entity>,
+    #[sea_orm(has_many, via = "team_member")]
+    pub teams: HasMany<super::team::Entity>,
+    #[sea_orm(has_many, via = "user_skill")]
+    pub skills: HasMany<super::skill::Entity>,
 }
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,22 +1,23 @@
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
-    FnArg, Ident, ItemFn, Pat, Type, parse_macro_input, punctuated::Punctuated, token::Comma,
+    FnArg, Ident, ItemFn, LitStr, Pat, Type,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    token::Comma,
 };
 
 fn find_claims_arg(input: &mut ItemFn) -> syn::Result<(Ident, Box<Type>)> {
     let claims_arg = input.sig.inputs.iter_mut().find_map(|arg| {
-        if let FnArg::Typed(pat_type) = arg {
-            if let Type::Path(type_path) = &*pat_type.ty {
-                if type_path
-                    .path
-                    .segments
-                    .last()
-                    .map_or(false, |segment| segment.ident == "Claims")
-                {
-                    return Some(pat_type);
-                }
-            }
+        if let FnArg::Typed(pat_type) = arg
+            && let Type::Path(type_path) = &*pat_type.ty
+            && type_path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Claims")
+        {
+            return Some(pat_type);
         }
         None
     });
@@ -38,64 +39,100 @@ fn find_claims_arg(input: &mut ItemFn) -> syn::Result<(Ident, Box<Type>)> {
     }
 }
 
-#[proc_macro_attribute]
-pub fn has_role(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let required_role = parse_macro_input!(attr as Ident); // e.g., Admin
-    let mut input = parse_macro_input!(item as ItemFn);
+fn find_state_arg(input: &ItemFn) -> syn::Result<Ident> {
+    let state_arg = input.sig.inputs.iter().find_map(|arg| {
+        if let FnArg::Typed(pat_type) = arg
+            && let Pat::TupleStruct(pat_tuple) = &*pat_type.pat
+            && pat_tuple
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "State")
+            && pat_tuple.elems.len() == 1
+            && let Some(Pat::Ident(pat_ident)) = pat_tuple.elems.first()
+        {
+            return Some(pat_ident.ident.clone());
+        }
+        None
+    });
 
-    let (claims_ident, _) = match find_claims_arg(&mut input) {
-        Ok(args) => args,
-        Err(e) => return e.to_compile_error().into(),
-    };
+    state_arg.ok_or_else(|| {
+        syn::Error::new_spanned(
+            input.sig.ident.clone(),
+            "Handler function must have a `State(state): State<AppState>` argument",
+        )
+    })
+}
 
-    let check_code = quote! {
-        if !#claims_ident.roles.contains(&entity::role::Role::#required_role) {
-            return Err(crate::error::AppError::Forbidden);
-        }
-    };
+/// `"idea:update:any"` -> `IdeaUpdateAny`
+fn permission_variant(permission: &LitStr) -> syn::Result<Ident> {
+    let value = permission.value();
+    let variant: String = value
+        .split([':', '_'])
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect();
+
+    if variant.is_empty() || !variant.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(syn::Error::new_spanned(
+            permission,
+            "Expected permission like \"idea:update:any\"",
+        ));
+    }
 
-    let check_code_as_tokenstream: TokenStream = check_code.into();
-    input.block.stmts.insert(
-        0,
-        parse_macro_input!(check_code_as_tokenstream as syn::Stmt),
-    );
+    Ok(Ident::new(&variant, permission.span()))
+}
 
-    quote! { #input }.into()
+/// `#[requires]` arguments: permissions with an optional trailing `active` flag,
+/// e.g. `#[requires("rating:read:any", active)]`.
+struct RequiresArgs {
+    permissions: Vec<LitStr>,
+    active: bool,
 }
 
-#[proc_macro_attribute]
-pub fn has_any_role(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let required_roles = parse_macro_input!(attr with Punctuated::<Ident, Comma>::parse_terminated);
-    let mut input = parse_macro_input!(item as ItemFn);
+impl Parse for RequiresArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut permissions = Vec::new();
+        let mut active = false;
 
-    let (claims_ident, _) = match find_claims_arg(&mut input) {
-        Ok(args) => args,
-        Err(e) => return e.to_compile_error().into(),
-    };
+        while !input.is_empty() {
+            if active {
+                return Err(input.error("`active` must be the last argument"));
+            }
 
-    let roles_check = required_roles.iter().map(|role| {
-        quote! { #claims_ident.roles.contains(&entity::role::Role::#role) }
-    });
+            if input.peek(LitStr) {
+                permissions.push(input.parse()?);
+            } else {
+                let ident = input.parse::<Ident>()?;
+                if ident != "active" {
+                    return Err(syn::Error::new_spanned(ident, "Expected `active`"));
+                }
+                active = true;
+            }
 
-    let check_code = quote! {
-        let has_any_role = #(#roles_check)||*;
-        if !has_any_role {
-            return Err(crate::error::AppError::Forbidden);
+            if !input.is_empty() {
+                input.parse::<Comma>()?;
+            }
         }
-    };
-
-    let check_code_as_tokenstream: TokenStream = check_code.into();
-    input.block.stmts.insert(
-        0,
-        parse_macro_input!(check_code_as_tokenstream as syn::Stmt),
-    );
 
-    quote! { #input }.into()
+        Ok(Self {
+            permissions,
+            active,
+        })
+    }
 }
 
+/// Requires every listed permission, e.g. `#[requires("idea:update:any")]`.
+/// Permissions are resolved from the caller's roles by `Authorizer`; with a
+/// trailing `active` only the active role is taken into account.
 #[proc_macro_attribute]
-pub fn has_all_roles(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let required_roles = parse_macro_input!(attr with Punctuated::<Ident, Comma>::parse_terminated);
+pub fn requires(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as RequiresArgs);
     let mut input = parse_macro_input!(item as ItemFn);
 
     let (claims_ident, _) = match find_claims_arg(&mut input) {
@@ -103,22 +140,45 @@ pub fn has_all_roles(attr: TokenStream, item: TokenStream) -> TokenStream {
         Err(e) => return e.to_compile_error().into(),
     };
 
-    let roles_check = required_roles.iter().map(|role| {
-        quote! { #claims_ident.roles.contains(&entity::role::Role::#role) }
+    let state_ident = match find_state_arg(&input) {
+        Ok(ident) => ident,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let variants = match args
+        .permissions
+        .iter()
+        .map(permission_variant)
+        .collect::<syn::Result<Vec<_>>>()
+    {
+        Ok(variants) => variants,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let require_fn = if args.active {
+        quote! { require_active }
+    } else {
+        quote! { require }
+    };
+
+    let checks = variants.iter().map(|variant| {
+        let check_code = quote! {
+            crate::services::authorizer::Authorizer::#require_fn(
+                &#state_ident,
+                &#claims_ident,
+                entity::permission::Permission::#variant,
+            )
+            .await?;
+        };
+        syn::parse2::<syn::Stmt>(check_code)
     });
 
-    let check_code = quote! {
-        let has_all_roles = #(#roles_check)&&*;
-        if !has_all_roles {
-            return Err(crate::error::AppError::Forbidden);
-        }
+    let checks = match checks.collect::<syn::Result<Vec<_>>>() {
+        Ok(checks) => checks,
+        Err(e) => return e.to_compile_error().into(),
     };
 
-    let check_code_as_tokenstream: TokenStream = check_code.into();
-    input.block.stmts.insert(
-        0,
-        parse_macro_input!(check_code_as_tokenstream as syn::Stmt),
-    );
+    input.block.stmts.splice(0..0, checks);
 
     quote! { #input }.into()
 }
@@ -127,8 +187,9 @@ pub fn has_all_roles(attr: TokenStream, item: TokenStream) -> TokenStream {
 pub fn into_data_response_derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as syn::DeriveInput);
     let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
     let generated_impl = quote! {
-        impl axum::response::IntoResponse for #name {
+        impl #impl_generics axum::response::IntoResponse for #name #ty_generics #where_clause {
             fn into_response(self) -> axum::response::Response {
                 axum::Json(self).into_response()
             }
@@ -14,6 +14,23 @@ impl MigratorTrait for Migrator {
             Box::new(m20251222_064711_create_groups_table::Migration),
             Box::new(m20251225_134356_create_idea_table::Migration),
             Box::new(m20251225_134357_create_idea_checked_table::Migration),
+            Box::new(m20251228_090000_create_idea_status_history_table::Migration),
+            Box::new(m20251229_080000_create_rating_table::Migration),
+            Box::new(m20251230_070000_create_idea_comment_table::Migration),
+            Box::new(m20251231_090000_add_search_vector_columns::Migration),
+            Box::new(m20260110_090000_create_team_table::Migration),
+            Box::new(m20260110_090001_create_user_skill_table::Migration),
+            Box::new(m20260115_090000_create_market_table::Migration),
+            Box::new(m20260120_090000_add_level_to_user_skill::Migration),
+            Box::new(m20260125_090000_add_token_version_to_users::Migration),
+            Box::new(m20260201_090000_create_api_token_table::Migration),
+            Box::new(m20260208_090000_create_two_factor_tables::Migration),
+            Box::new(m20260215_090000_create_role_permission_table::Migration),
+            Box::new(m20260222_090000_add_actor_role_to_idea_status_history::Migration),
+            Box::new(m20260301_090000_add_report_export_permission::Migration),
+            Box::new(m20260308_090000_add_is_directory_user_to_users::Migration),
+            Box::new(m20260315_090000_grant_company_manage_own::Migration),
+            Box::new(m20260322_090000_add_name_search_vector_to_users::Migration),
         ]
     }
 }
@@ -25,3 +42,20 @@ mod m20251221_103728_create_skill_table;
 mod m20251222_064711_create_groups_table;
 mod m20251225_134356_create_idea_table;
 mod m20251225_134357_create_idea_checked_table;
+mod m20251228_090000_create_idea_status_history_table;
+mod m20251229_080000_create_rating_table;
+mod m20251230_070000_create_idea_comment_table;
+mod m20251231_090000_add_search_vector_columns;
+mod m20260110_090000_create_team_table;
+mod m20260110_090001_create_user_skill_table;
+mod m20260115_090000_create_market_table;
+mod m20260120_090000_add_level_to_user_skill;
+mod m20260125_090000_add_token_version_to_users;
+mod m20260201_090000_create_api_token_table;
+mod m20260208_090000_create_two_factor_tables;
+mod m20260215_090000_create_role_permission_table;
+mod m20260222_090000_add_actor_role_to_idea_status_history;
+mod m20260301_090000_add_report_export_permission;
+mod m20260308_090000_add_is_directory_user_to_users;
+mod m20260315_090000_grant_company_manage_own;
+mod m20260322_090000_add_name_search_vector_to_users;
@@ -16,55 +16,35 @@ impl MigrationTrait for Migration {
                             .uuid()
                             .not_null()
                             .primary_key()
-                            .default(Expr::cust("gen_random_uuid()")) 
-                    )
-                    .col(
-                        ColumnDef::new(Users::StudyGroup)
-                            .string()
-                            .null()
-                    )
-                    .col(
-                        ColumnDef::new(Users::Telephone)
-                            .string()
-                            .null()
+                            .default(Expr::cust("gen_random_uuid()")),
                     )
+                    .col(ColumnDef::new(Users::StudyGroup).string().null())
+                    .col(ColumnDef::new(Users::Telephone).string().null())
                     .col(
                         ColumnDef::new(Users::Roles)
                             .array(ColumnType::String(StringLen::None))
-                            .not_null()
+                            .not_null(),
                     )
                     .col(
                         ColumnDef::new(Users::Email)
                             .string()
                             .unique_key()
-                            .not_null()
-                    )
-                    .col(
-                        ColumnDef::new(Users::Password)
-                            .string()
-                            .not_null()
-                    )
-                    .col(
-                        ColumnDef::new(Users::LastName)
-                            .string()
-                            .not_null()
-                    )
-                    .col(
-                        ColumnDef::new(Users::FirstName)
-                            .string()
-                            .not_null()
+                            .not_null(),
                     )
+                    .col(ColumnDef::new(Users::Password).string().not_null())
+                    .col(ColumnDef::new(Users::LastName).string().not_null())
+                    .col(ColumnDef::new(Users::FirstName).string().not_null())
                     .col(
                         ColumnDef::new(Users::CreatedAt)
                             .timestamp_with_time_zone()
                             .not_null()
-                            .default(Expr::current_timestamp())
+                            .default(Expr::current_timestamp()),
                     )
                     .col(
                         ColumnDef::new(Users::IsDeleted)
                             .boolean()
                             .not_null()
-                            .default(false)
+                            .default(false),
                     )
                     .to_owned(),
             )
@@ -72,7 +52,6 @@ impl MigrationTrait for Migration {
     }
 }
 
-
 #[derive(Iden)]
 pub enum Users {
     Table,
@@ -86,4 +65,4 @@ pub enum Users {
     FirstName,
     CreatedAt,
     IsDeleted,
-}
\ No newline at end of file
+}
@@ -70,6 +70,7 @@ impl MigrationTrait for Migration {
 }
 
 #[derive(Iden)]
+#[allow(clippy::enum_variant_names)]
 pub enum Skill {
     Table,
     Id,
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+use super::{m20251202_065032_create_user_table::Users, m20251225_134356_create_idea_table::Idea};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IdeaStatusHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(IdeaStatusHistory::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(IdeaStatusHistory::IdeaId).uuid().not_null())
+                    .col(ColumnDef::new(IdeaStatusHistory::ActorId).uuid())
+                    .col(
+                        ColumnDef::new(IdeaStatusHistory::PreviousStatus)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(IdeaStatusHistory::NewStatus)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(IdeaStatusHistory::Comment).string())
+                    .col(
+                        ColumnDef::new(IdeaStatusHistory::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(IdeaStatusHistory::Table, IdeaStatusHistory::IdeaId)
+                            .to(Idea::Table, Idea::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(IdeaStatusHistory::Table, IdeaStatusHistory::ActorId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-idea_status_history-idea_id")
+                    .table(IdeaStatusHistory::Table)
+                    .col(IdeaStatusHistory::IdeaId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum IdeaStatusHistory {
+    Table,
+    Id,
+    IdeaId,
+    ActorId,
+    PreviousStatus,
+    NewStatus,
+    Comment,
+    CreatedAt,
+}
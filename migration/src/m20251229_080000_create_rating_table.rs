@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+use super::{m20251202_065032_create_user_table::Users, m20251225_134356_create_idea_table::Idea};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Rating::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Rating::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(Rating::IdeaId).uuid().not_null())
+                    .col(ColumnDef::new(Rating::ExpertId).uuid().not_null())
+                    .col(ColumnDef::new(Rating::MarketValue).small_integer())
+                    .col(ColumnDef::new(Rating::Originality).small_integer())
+                    .col(ColumnDef::new(Rating::TechnicalRealizability).small_integer())
+                    .col(ColumnDef::new(Rating::Suitability).small_integer())
+                    .col(ColumnDef::new(Rating::Budget).small_integer())
+                    .col(ColumnDef::new(Rating::Rating).double())
+                    .col(
+                        ColumnDef::new(Rating::IsConfirmed)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(Rating::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Rating::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Rating::Table, Rating::IdeaId)
+                            .to(Idea::Table, Idea::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Rating::Table, Rating::ExpertId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-rating-idea_id-expert_id")
+                    .table(Rating::Table)
+                    .col(Rating::IdeaId)
+                    .col(Rating::ExpertId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+#[allow(clippy::enum_variant_names)]
+enum Rating {
+    Table,
+    Id,
+    IdeaId,
+    ExpertId,
+    MarketValue,
+    Originality,
+    TechnicalRealizability,
+    Suitability,
+    Budget,
+    Rating,
+    IsConfirmed,
+    CreatedAt,
+    UpdatedAt,
+}
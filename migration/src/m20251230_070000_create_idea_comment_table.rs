@@ -0,0 +1,83 @@
+use sea_orm_migration::prelude::*;
+
+use super::{m20251202_065032_create_user_table::Users, m20251225_134356_create_idea_table::Idea};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IdeaComment::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(IdeaComment::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(IdeaComment::IdeaId).uuid().not_null())
+                    .col(ColumnDef::new(IdeaComment::AuthorId).uuid().not_null())
+                    .col(ColumnDef::new(IdeaComment::ParentId).uuid())
+                    .col(ColumnDef::new(IdeaComment::Text).text().not_null())
+                    .col(
+                        ColumnDef::new(IdeaComment::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(IdeaComment::EditedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(IdeaComment::DeletedAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(IdeaComment::Table, IdeaComment::IdeaId)
+                            .to(Idea::Table, Idea::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(IdeaComment::Table, IdeaComment::AuthorId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(IdeaComment::Table, IdeaComment::ParentId)
+                            .to(IdeaComment::Table, IdeaComment::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-idea_comment-idea_id")
+                    .table(IdeaComment::Table)
+                    .col(IdeaComment::IdeaId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum IdeaComment {
+    Table,
+    Id,
+    IdeaId,
+    AuthorId,
+    ParentId,
+    Text,
+    CreatedAt,
+    EditedAt,
+    DeletedAt,
+}
@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Generated `tsvector` columns for `/search`. Every document is indexed with
+/// both the Russian and the English dictionaries.
+const SEARCH_COLUMNS: &[(&str, &str)] = &[
+    (
+        "idea",
+        "coalesce(name, '') || ' ' || coalesce(problem, '') || ' ' || coalesce(solution, '') || ' ' || coalesce(description, '')",
+    ),
+    (
+        "users",
+        "coalesce(first_name, '') || ' ' || coalesce(last_name, '') || ' ' || coalesce(email, '') || ' ' || coalesce(study_group, '')",
+    ),
+    ("skill", "coalesce(name, '')"),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (table, document) in SEARCH_COLUMNS {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE {table} ADD COLUMN IF NOT EXISTS search_vector tsvector \
+                 GENERATED ALWAYS AS (\
+                 to_tsvector('russian', {document}) || to_tsvector('english', {document})\
+                 ) STORED"
+            ))
+            .await?;
+
+            db.execute_unprepared(&format!(
+                "CREATE INDEX IF NOT EXISTS idx_{table}_search_vector \
+                 ON {table} USING GIN (search_vector)"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251202_065032_create_user_table::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Team::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Team::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(Team::Name).string().not_null())
+                    .col(ColumnDef::new(Team::Description).text())
+                    .col(ColumnDef::new(Team::OwnerId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Team::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Team::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Team::Table, Team::OwnerId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Restrict)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TeamMember::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(TeamMember::TeamId).uuid().not_null())
+                    .col(ColumnDef::new(TeamMember::UserId).uuid().not_null())
+                    .col(ColumnDef::new(TeamMember::Role).string().not_null())
+                    .col(
+                        ColumnDef::new(TeamMember::JoinedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(TeamMember::TeamId)
+                            .col(TeamMember::UserId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TeamMember::Table, TeamMember::TeamId)
+                            .to(Team::Table, Team::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TeamMember::Table, TeamMember::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TeamInvitation::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TeamInvitation::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(TeamInvitation::TeamId).uuid().not_null())
+                    .col(ColumnDef::new(TeamInvitation::UserId).uuid())
+                    .col(ColumnDef::new(TeamInvitation::Email).string().not_null())
+                    .col(ColumnDef::new(TeamInvitation::InviterId).uuid().not_null())
+                    .col(ColumnDef::new(TeamInvitation::Status).string().not_null())
+                    .col(
+                        ColumnDef::new(TeamInvitation::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(TeamInvitation::RespondedAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TeamInvitation::Table, TeamInvitation::TeamId)
+                            .to(Team::Table, Team::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TeamInvitation::Table, TeamInvitation::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TeamInvitation::Table, TeamInvitation::InviterId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-team_invitation-email")
+                    .table(TeamInvitation::Table)
+                    .col(TeamInvitation::Email)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Team {
+    Table,
+    Id,
+    Name,
+    Description,
+    OwnerId,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum TeamMember {
+    Table,
+    TeamId,
+    UserId,
+    Role,
+    JoinedAt,
+}
+
+#[derive(Iden)]
+enum TeamInvitation {
+    Table,
+    Id,
+    TeamId,
+    UserId,
+    Email,
+    InviterId,
+    Status,
+    CreatedAt,
+    RespondedAt,
+}
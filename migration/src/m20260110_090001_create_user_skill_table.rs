@@ -0,0 +1,50 @@
+use sea_orm_migration::prelude::*;
+
+use super::{
+    m20251202_065032_create_user_table::Users, m20251221_103728_create_skill_table::Skill,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserSkill::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(UserSkill::UserId).uuid().not_null())
+                    .col(ColumnDef::new(UserSkill::SkillId).uuid().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(UserSkill::UserId)
+                            .col(UserSkill::SkillId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UserSkill::Table, UserSkill::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UserSkill::Table, UserSkill::SkillId)
+                            .to(Skill::Table, Skill::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum UserSkill {
+    Table,
+    UserId,
+    SkillId,
+}
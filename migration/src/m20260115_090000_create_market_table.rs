@@ -0,0 +1,189 @@
+use sea_orm_migration::prelude::*;
+
+use super::{m20251225_134356_create_idea_table::Idea, m20260110_090000_create_team_table::Team};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Market::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Market::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(Market::Name).string().not_null())
+                    .col(ColumnDef::new(Market::StartDate).date().not_null())
+                    .col(ColumnDef::new(Market::FinishDate).date().not_null())
+                    .col(ColumnDef::new(Market::Status).string().not_null())
+                    .col(
+                        ColumnDef::new(Market::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MarketIdea::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MarketIdea::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(MarketIdea::MarketId).uuid().not_null())
+                    .col(ColumnDef::new(MarketIdea::IdeaId).uuid().not_null())
+                    .col(ColumnDef::new(MarketIdea::TeamId).uuid())
+                    .col(ColumnDef::new(MarketIdea::Status).string().not_null())
+                    .col(
+                        ColumnDef::new(MarketIdea::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MarketIdea::Table, MarketIdea::MarketId)
+                            .to(Market::Table, Market::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MarketIdea::Table, MarketIdea::IdeaId)
+                            .to(Idea::Table, Idea::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MarketIdea::Table, MarketIdea::TeamId)
+                            .to(Team::Table, Team::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-market_idea-market_id-idea_id")
+                    .table(MarketIdea::Table)
+                    .col(MarketIdea::MarketId)
+                    .col(MarketIdea::IdeaId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MarketIdeaRequest::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MarketIdeaRequest::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(
+                        ColumnDef::new(MarketIdeaRequest::MarketIdeaId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MarketIdeaRequest::TeamId).uuid().not_null())
+                    .col(ColumnDef::new(MarketIdeaRequest::Letter).text().not_null())
+                    .col(
+                        ColumnDef::new(MarketIdeaRequest::Status)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MarketIdeaRequest::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(MarketIdeaRequest::RespondedAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MarketIdeaRequest::Table, MarketIdeaRequest::MarketIdeaId)
+                            .to(MarketIdea::Table, MarketIdea::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MarketIdeaRequest::Table, MarketIdeaRequest::TeamId)
+                            .to(Team::Table, Team::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-market_idea_request-market_idea_id")
+                    .table(MarketIdeaRequest::Table)
+                    .col(MarketIdeaRequest::MarketIdeaId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Market {
+    Table,
+    Id,
+    Name,
+    StartDate,
+    FinishDate,
+    Status,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum MarketIdea {
+    Table,
+    Id,
+    MarketId,
+    IdeaId,
+    TeamId,
+    Status,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum MarketIdeaRequest {
+    Table,
+    Id,
+    MarketIdeaId,
+    TeamId,
+    Letter,
+    Status,
+    CreatedAt,
+    RespondedAt,
+}
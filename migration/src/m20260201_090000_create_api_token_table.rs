@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251202_065032_create_user_table::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApiToken::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ApiToken::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(ApiToken::UserId).uuid().not_null())
+                    .col(ColumnDef::new(ApiToken::Name).string().not_null())
+                    .col(ColumnDef::new(ApiToken::TokenHash).string().not_null())
+                    .col(
+                        ColumnDef::new(ApiToken::Scopes)
+                            .array(ColumnType::String(StringLen::None))
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ApiToken::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(ApiToken::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ApiToken::LastUsedAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ApiToken::Table, ApiToken::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-api_token-user_id")
+                    .table(ApiToken::Table)
+                    .col(ApiToken::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum ApiToken {
+    Table,
+    Id,
+    UserId,
+    Name,
+    TokenHash,
+    Scopes,
+    CreatedAt,
+    ExpiresAt,
+    LastUsedAt,
+}
@@ -0,0 +1,116 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251202_065032_create_user_table::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TwoFactor::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TwoFactor::UserId)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TwoFactor::Secret).string().not_null())
+                    .col(
+                        ColumnDef::new(TwoFactor::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(TwoFactor::EnabledAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TwoFactor::Table, TwoFactor::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RecoveryCode::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RecoveryCode::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(RecoveryCode::UserId).uuid().not_null())
+                    .col(ColumnDef::new(RecoveryCode::CodeHash).string().not_null())
+                    .col(ColumnDef::new(RecoveryCode::UsedAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RecoveryCode::Table, RecoveryCode::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-recovery_code-user_id")
+                    .table(RecoveryCode::Table)
+                    .col(RecoveryCode::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TwoFactorPolicy::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TwoFactorPolicy::Role)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum TwoFactor {
+    Table,
+    UserId,
+    Secret,
+    CreatedAt,
+    EnabledAt,
+}
+
+#[derive(Iden)]
+enum RecoveryCode {
+    Table,
+    Id,
+    UserId,
+    CodeHash,
+    UsedAt,
+}
+
+#[derive(Iden)]
+enum TwoFactorPolicy {
+    Table,
+    Role,
+}
@@ -0,0 +1,116 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Default role permissions. They mirror the role checks that used to be
+/// hardcoded in the handlers; admins change them later via `/permissions`.
+const DEFAULT_PERMISSIONS: &[(&str, &[&str])] = &[
+    (
+        "INITIATOR",
+        &["idea:update:own", "idea:delete:own", "idea:submit:own"],
+    ),
+    (
+        "EXPERT",
+        &[
+            "idea:approve",
+            "idea:return:approval",
+            "idea_history:read",
+            "rating:read:any",
+            "rating:manage:own",
+        ],
+    ),
+    (
+        "PROJECT_OFFICE",
+        &[
+            "idea:return:approval",
+            "idea:confirm",
+            "idea:return:confirmation",
+            "idea:publish",
+            "idea_history:read",
+            "idea_match:read",
+            "rating:read:any",
+            "market:manage",
+            "market_request:read:any",
+        ],
+    ),
+    (
+        "ADMIN",
+        &[
+            "idea:read:any",
+            "idea:update:any",
+            "idea:delete:any",
+            "idea:submit:any",
+            "idea:approve",
+            "idea:return:approval",
+            "idea:confirm",
+            "idea:return:confirmation",
+            "idea:publish",
+            "idea_history:read",
+            "idea_match:read",
+            "comment:delete:any",
+            "rating:read:any",
+            "skill:manage:any",
+            "group:manage:any",
+            "company:manage:any",
+            "team:read:any",
+            "team:manage:any",
+            "market:manage",
+            "market_request:read:any",
+            "market_request:manage:any",
+            "invitation:send",
+            "user:manage:any",
+            "role:manage",
+        ],
+    ),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RolePermission::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(RolePermission::Role).string().not_null())
+                    .col(
+                        ColumnDef::new(RolePermission::Permission)
+                            .string()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(RolePermission::Role)
+                            .col(RolePermission::Permission),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let mut insert = Query::insert()
+            .into_table(RolePermission::Table)
+            .columns([RolePermission::Role, RolePermission::Permission])
+            .on_conflict(
+                OnConflict::columns([RolePermission::Role, RolePermission::Permission])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .to_owned();
+
+        for (role, permissions) in DEFAULT_PERMISSIONS {
+            for permission in *permissions {
+                insert.values_panic([(*role).into(), (*permission).into()]);
+            }
+        }
+
+        manager.exec_stmt(insert).await
+    }
+}
+
+#[derive(Iden)]
+enum RolePermission {
+    Table,
+    Role,
+    Permission,
+}
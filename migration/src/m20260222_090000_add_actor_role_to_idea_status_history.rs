@@ -0,0 +1,24 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IdeaStatusHistory::Table)
+                    .add_column_if_not_exists(ColumnDef::new(IdeaStatusHistory::ActorRole).string())
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum IdeaStatusHistory {
+    Table,
+    ActorRole,
+}
@@ -0,0 +1,35 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Exporting users, ideas and companies to CSV/XLSX for reports.
+const REPORT_EXPORT_ROLES: [&str; 2] = ["PROJECT_OFFICE", "ADMIN"];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let mut insert = Query::insert()
+            .into_table(RolePermission::Table)
+            .columns([RolePermission::Role, RolePermission::Permission])
+            .on_conflict(
+                OnConflict::columns([RolePermission::Role, RolePermission::Permission])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .to_owned();
+
+        for role in REPORT_EXPORT_ROLES {
+            insert.values_panic([role.into(), "report:export".into()]);
+        }
+
+        manager.exec_stmt(insert).await
+    }
+}
+
+#[derive(Iden)]
+enum RolePermission {
+    Table,
+    Role,
+    Permission,
+}
@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// `company:manage:own` wasn't granted by the defaults, so owners without
+/// `company:manage:any` couldn't change their companies.
+const COMPANY_OWNER_ROLES: [&str; 1] = ["INITIATOR"];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let mut insert = Query::insert()
+            .into_table(RolePermission::Table)
+            .columns([RolePermission::Role, RolePermission::Permission])
+            .on_conflict(
+                OnConflict::columns([RolePermission::Role, RolePermission::Permission])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .to_owned();
+
+        for role in COMPANY_OWNER_ROLES {
+            insert.values_panic([role.into(), "company:manage:own".into()]);
+        }
+
+        manager.exec_stmt(insert).await
+    }
+}
+
+#[derive(Iden)]
+enum RolePermission {
+    Table,
+    Role,
+    Permission,
+}
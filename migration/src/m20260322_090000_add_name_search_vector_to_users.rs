@@ -0,0 +1,32 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// `users.search_vector` also covers email and study group, which only
+/// holders of `user:manage:any` may search by. Everyone else searches this
+/// index over the names.
+const NAME_DOCUMENT: &str = "coalesce(first_name, '') || ' ' || coalesce(last_name, '')";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(&format!(
+            "ALTER TABLE users ADD COLUMN IF NOT EXISTS name_search_vector tsvector \
+             GENERATED ALWAYS AS (\
+             to_tsvector('russian', {NAME_DOCUMENT}) || to_tsvector('english', {NAME_DOCUMENT})\
+             ) STORED"
+        ))
+        .await?;
+
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_users_name_search_vector \
+             ON users USING GIN (name_search_vector)",
+        )
+        .await?;
+
+        Ok(())
+    }
+}
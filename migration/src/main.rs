@@ -3,4 +3,4 @@ use sea_orm_migration::prelude::*;
 #[tokio::main]
 async fn main() {
     cli::run_cli(migration::Migrator).await;
-}
\ No newline at end of file
+}
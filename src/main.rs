@@ -1,3 +1,3 @@
 fn main() {
     let _ = hits_api::start();
-}
\ No newline at end of file
+}